@@ -6,8 +6,10 @@ use std::path::Path;
 
 fn main() {
     let local_path = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    // Applies to the unit test binary too, which boots just like the kernel.
     println!(
-        "cargo:rustc-link-arg-bins=--script={}",
+        "cargo:rustc-link-arg=--script={}",
         local_path.join("kernel.ld").display()
     )
 }
@@ -0,0 +1,76 @@
+# SPDX-FileCopyrightText: 2026 SunnyMonster
+#
+# SPDX-License-Identifier: GPL-3.0-or-later
+
+# Writes a sorted, demangled table of the kernel's function symbols into its reserved .ksyms
+# section so that backtraces can be symbolized at panic time.
+#
+# Table format, all little endian:
+#   u64 count
+#   count entries of { u64 start, u32 size, u32 name offset }, sorted by start address
+#   the names, each terminated by a zero byte, that the name offsets point into
+# The rest of the section is left zeroed.
+
+set -e
+
+KERNEL="$1"
+TABLE="$(mktemp)"
+trap 'rm -f "$TABLE"' EXIT
+
+# The C locale makes awk's %c write single bytes instead of UTF-8 characters.
+export LC_ALL=C
+
+nm --defined-only --numeric-sort --print-size --demangle "$KERNEL" \
+    | awk '
+    # awk numbers are doubles, so 64 bit addresses are converted in two 32 bit halves.
+    function hex(digits,    value, i) {
+        value = 0
+        for (i = 1; i <= length(digits); i++)
+            value = value * 16 + index("0123456789abcdef", tolower(substr(digits, i, 1))) - 1
+        return value
+    }
+
+    function put(value, bytes,    i) {
+        for (i = 0; i < bytes; i++) {
+            printf "%c", value % 256
+            value = int(value / 256)
+        }
+    }
+
+    BEGIN { n = 0; names_size = 0 }
+
+    NF >= 4 && $3 ~ /^[tTwW]$/ {
+        name = $4
+        for (i = 5; i <= NF; i++) name = name " " $i
+        name = substr(name, 1, 160)
+
+        start[n] = $1
+        size[n] = $2
+        names[n] = name
+        offset[n] = names_size
+        names_size += length(name) + 1
+        n++
+    }
+
+    END {
+        put(n, 8)
+        for (i = 0; i < n; i++) {
+            put(hex(substr(start[i], length(start[i]) - 7)), 4)
+            put(hex(substr(start[i], 1, length(start[i]) - 8)), 4)
+            put(hex(size[i]), 4)
+            put(offset[i], 4)
+        }
+        for (i = 0; i < n; i++) printf "%s%c", names[i], 0
+    }' > "$TABLE"
+
+SECTION_SIZE=$(printf "%d" "0x$(objdump -h "$KERNEL" | awk '$2 == ".ksyms" { print $3 }')")
+TABLE_SIZE=$(wc -c < "$TABLE")
+
+if [ "$TABLE_SIZE" -gt "$SECTION_SIZE" ]; then
+    echo "Symbol table is $TABLE_SIZE bytes but .ksyms only has room for $SECTION_SIZE." >&2
+    echo "Increase KSYMS_SIZE in src/debug/symbols.rs." >&2
+    exit 1
+fi
+
+truncate -s "$SECTION_SIZE" "$TABLE"
+objcopy --update-section .ksyms="$TABLE" "$KERNEL"
@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::Word;
+
+/// Every word that can be an answer or a guess, one per line.
+const WORDS: &str = include_str!("words.txt");
+
+pub fn dictionary() -> impl Iterator<Item = Word> {
+    WORDS.lines().filter_map(|line| line.trim().parse().ok())
+}
+
+pub fn is_in_dictionary(word: &Word) -> bool {
+    dictionary().any(|candidate| candidate == *word)
+}
+
+/// Picks an answer from the dictionary. The same seed always picks the same word.
+pub fn pick_answer(seed: u64) -> Word {
+    // splitmix64, so that consecutive seeds don't give consecutive words.
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+
+    let count = dictionary().count() as u64;
+    dictionary()
+        .nth((z % count) as usize)
+        .expect("Index is within the dictionary.")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeSet;
+
+    use super::*;
+
+    #[test]
+    fn every_line_is_a_word() {
+        assert_eq!(dictionary().count(), WORDS.lines().count());
+    }
+
+    #[test]
+    fn has_no_duplicates() {
+        let unique: BTreeSet<_> = dictionary().map(|word| *word.letters()).collect();
+        assert_eq!(unique.len(), dictionary().count());
+    }
+
+    #[test]
+    fn lookup() {
+        assert!(is_in_dictionary(&"apple".parse().unwrap()));
+        assert!(!is_in_dictionary(&"zzzzz".parse().unwrap()));
+    }
+
+    #[test]
+    fn answers_are_deterministic() {
+        assert_eq!(pick_answer(1234), pick_answer(1234));
+        assert_ne!(pick_answer(0), pick_answer(1));
+    }
+}
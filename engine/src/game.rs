@@ -0,0 +1,243 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{LetterScore, MAX_GUESSES, WORD_LENGTH, Word, is_in_dictionary, score};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuessError {
+    WrongLength,
+    NotALetter,
+    NotInDictionary,
+    /// Hard mode: a letter known to be at this position was not used there.
+    MustKeep {
+        position: usize,
+        letter: u8,
+    },
+    /// Hard mode: a letter known to be in the answer was not used.
+    MustUse(u8),
+    GameOver,
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongLength => write!(f, "guesses must be {WORD_LENGTH} letters long"),
+            Self::NotALetter => f.write_str("guesses may only contain letters"),
+            Self::NotInDictionary => f.write_str("not in the word list"),
+            Self::MustKeep { position, letter } => write!(
+                f,
+                "letter {} must be {}",
+                position + 1,
+                letter.to_ascii_uppercase() as char
+            ),
+            Self::MustUse(letter) => {
+                write!(
+                    f,
+                    "guess must contain {}",
+                    letter.to_ascii_uppercase() as char
+                )
+            }
+            Self::GameOver => f.write_str("the game is over"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Guess {
+    pub word: Word,
+    pub scores: [LetterScore; WORD_LENGTH],
+}
+
+/// One round of Wordle.
+#[derive(Debug, Clone)]
+pub struct Game {
+    answer: Word,
+    guesses: Vec<Guess>,
+    hard_mode: bool,
+}
+
+impl Game {
+    pub fn new(answer: Word, hard_mode: bool) -> Self {
+        Self {
+            answer,
+            guesses: Vec::new(),
+            hard_mode,
+        }
+    }
+
+    pub fn hard_mode(&self) -> bool {
+        self.hard_mode
+    }
+
+    pub fn answer(&self) -> Word {
+        self.answer
+    }
+
+    pub fn guesses(&self) -> &[Guess] {
+        &self.guesses
+    }
+
+    pub fn status(&self) -> Status {
+        match self.guesses.last() {
+            Some(guess) if guess.word == self.answer => Status::Won,
+            _ if self.guesses.len() == MAX_GUESSES => Status::Lost,
+            _ => Status::Playing,
+        }
+    }
+
+    /// The best score each letter of the alphabet has had so far, for coloring a keyboard.
+    pub fn letter_scores(&self) -> [Option<LetterScore>; 26] {
+        let mut letters = [None; 26];
+
+        for guess in &self.guesses {
+            for (&letter, score) in guess.word.letters().iter().zip(guess.scores) {
+                let best = &mut letters[(letter - b'a') as usize];
+                *best = (*best).max(Some(score));
+            }
+        }
+
+        letters
+    }
+
+    /// Checks that `word` uses every hint revealed so far.
+    fn check_hard_mode(&self, word: &Word) -> Result<(), GuessError> {
+        let letters = word.letters();
+
+        for guess in &self.guesses {
+            for (position, (&letter, score)) in
+                guess.word.letters().iter().zip(guess.scores).enumerate()
+            {
+                match score {
+                    LetterScore::Correct if letters[position] != letter => {
+                        return Err(GuessError::MustKeep { position, letter });
+                    }
+                    LetterScore::Present if !letters.contains(&letter) => {
+                        return Err(GuessError::MustUse(letter));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn guess(&mut self, word: &str) -> Result<&Guess, GuessError> {
+        if self.status() != Status::Playing {
+            return Err(GuessError::GameOver);
+        }
+
+        let word: Word = word.parse()?;
+        if !is_in_dictionary(&word) {
+            return Err(GuessError::NotInDictionary);
+        }
+        if self.hard_mode {
+            self.check_hard_mode(&word)?;
+        }
+
+        self.guesses.push(Guess {
+            word,
+            scores: score(&word, &self.answer),
+        });
+        Ok(self.guesses.last().expect("A guess was just pushed."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(answer: &str, hard_mode: bool) -> Game {
+        Game::new(answer.parse().unwrap(), hard_mode)
+    }
+
+    #[test]
+    fn rejects_invalid_guesses() {
+        let mut game = game("apple", false);
+
+        assert_eq!(game.guess("abc").err(), Some(GuessError::WrongLength));
+        assert_eq!(game.guess("ab1de").err(), Some(GuessError::NotALetter));
+        assert_eq!(game.guess("zzzzz").err(), Some(GuessError::NotInDictionary));
+        assert!(game.guesses().is_empty());
+    }
+
+    #[test]
+    fn wins_on_the_answer() {
+        let mut game = game("apple", false);
+
+        game.guess("paper").unwrap();
+        assert_eq!(game.status(), Status::Playing);
+        game.guess("APPLE").unwrap();
+        assert_eq!(game.status(), Status::Won);
+        assert_eq!(game.guess("apple").err(), Some(GuessError::GameOver));
+    }
+
+    #[test]
+    fn runs_out_of_guesses() {
+        let mut game = game("apple", false);
+
+        for guess in ["about", "above", "abuse", "actor", "acute", "admit"] {
+            assert_eq!(game.status(), Status::Playing);
+            game.guess(guess).unwrap();
+        }
+
+        assert_eq!(game.status(), Status::Lost);
+        assert_eq!(game.guess("apple").err(), Some(GuessError::GameOver));
+    }
+
+    #[test]
+    fn hard_mode_keeps_correct_letters() {
+        let mut game = game("apple", true);
+
+        game.guess("angle").unwrap();
+        assert_eq!(
+            game.guess("admit").err(),
+            Some(GuessError::MustKeep {
+                position: 3,
+                letter: b'l'
+            })
+        );
+    }
+
+    #[test]
+    fn hard_mode_uses_present_letters() {
+        let mut game = game("apple", true);
+
+        game.guess("alarm").unwrap();
+        assert_eq!(game.guess("abuse").err(), Some(GuessError::MustUse(b'l')));
+        assert!(game.guess("alike").is_ok());
+    }
+
+    #[test]
+    fn easy_mode_ignores_hints() {
+        let mut game = game("apple", false);
+
+        game.guess("paper").unwrap();
+        assert!(game.guess("about").is_ok());
+    }
+
+    #[test]
+    fn letter_scores_keep_the_best() {
+        let mut game = game("apple", false);
+
+        game.guess("paper").unwrap();
+        game.guess("alike").unwrap();
+
+        let letters = game.letter_scores();
+        assert_eq!(letters[(b'p' - b'a') as usize], Some(LetterScore::Correct));
+        assert_eq!(letters[(b'a' - b'a') as usize], Some(LetterScore::Correct));
+        assert_eq!(letters[(b'r' - b'a') as usize], Some(LetterScore::Absent));
+        assert_eq!(letters[(b'z' - b'a') as usize], None);
+    }
+}
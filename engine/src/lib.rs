@@ -0,0 +1,26 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The rules of Wordle, independent of how the game is shown or where it runs.
+//!
+//! Nothing in here depends on the kernel, so it can be tested on the host with `cargo test`.
+
+#![no_std]
+
+extern crate alloc;
+
+mod dictionary;
+mod game;
+mod score;
+mod stats;
+mod word;
+
+pub use dictionary::{dictionary, is_in_dictionary, pick_answer};
+pub use game::{Game, Guess, GuessError, Status};
+pub use score::{LetterScore, score};
+pub use stats::Stats;
+pub use word::Word;
+
+pub const WORD_LENGTH: usize = 5;
+pub const MAX_GUESSES: usize = 6;
@@ -0,0 +1,82 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::{WORD_LENGTH, Word};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LetterScore {
+    /// The letter is not in the answer, or every occurrence is already accounted for.
+    Absent,
+    /// The letter is in the answer at a different position.
+    Present,
+    Correct,
+}
+
+/// Scores `guess` against `answer`. Repeated letters are only marked present as many times as
+/// they appear in the answer, with exact matches taking priority.
+pub fn score(guess: &Word, answer: &Word) -> [LetterScore; WORD_LENGTH] {
+    let (guess, answer) = (guess.letters(), answer.letters());
+    let mut scores = [LetterScore::Absent; WORD_LENGTH];
+    let mut unmatched = [0u8; 26];
+
+    for i in 0..WORD_LENGTH {
+        if guess[i] == answer[i] {
+            scores[i] = LetterScore::Correct;
+        } else {
+            unmatched[(answer[i] - b'a') as usize] += 1;
+        }
+    }
+
+    for i in 0..WORD_LENGTH {
+        let remaining = &mut unmatched[(guess[i] - b'a') as usize];
+        if scores[i] != LetterScore::Correct && *remaining > 0 {
+            scores[i] = LetterScore::Present;
+            *remaining -= 1;
+        }
+    }
+
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use LetterScore::{Absent, Correct, Present};
+
+    fn score(guess: &str, answer: &str) -> [LetterScore; WORD_LENGTH] {
+        super::score(&guess.parse().unwrap(), &answer.parse().unwrap())
+    }
+
+    #[test]
+    fn exact_match() {
+        assert_eq!(score("apple", "apple"), [Correct; WORD_LENGTH]);
+    }
+
+    #[test]
+    fn no_common_letters() {
+        assert_eq!(score("crumb", "faith"), [Absent; WORD_LENGTH]);
+    }
+
+    #[test]
+    fn repeated_guess_letters() {
+        assert_eq!(
+            score("paper", "apple"),
+            [Present, Present, Correct, Present, Absent]
+        );
+        // Only one E in the answer, so the second E in the guess gets nothing.
+        assert_eq!(
+            score("speed", "apple"),
+            [Absent, Correct, Present, Absent, Absent]
+        );
+    }
+
+    #[test]
+    fn exact_matches_take_priority() {
+        // The answer's only L is matched exactly, so the first L in the guess is absent.
+        assert_eq!(
+            score("lolly", "belly"),
+            [Absent, Absent, Correct, Correct, Correct]
+        );
+    }
+}
@@ -0,0 +1,104 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::{Game, MAX_GUESSES, Status};
+
+/// Results across finished rounds.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    played: u32,
+    won: u32,
+    current_streak: u32,
+    max_streak: u32,
+    /// How many wins took each number of guesses, indexed by guesses minus one.
+    distribution: [u32; MAX_GUESSES],
+}
+
+impl Stats {
+    /// Adds the result of `game`. Games that are still being played are ignored.
+    pub fn record(&mut self, game: &Game) {
+        match game.status() {
+            Status::Playing => return,
+            Status::Won => {
+                self.won += 1;
+                self.current_streak += 1;
+                self.max_streak = self.max_streak.max(self.current_streak);
+                self.distribution[game.guesses().len() - 1] += 1;
+            }
+            Status::Lost => self.current_streak = 0,
+        }
+
+        self.played += 1;
+    }
+
+    pub fn played(&self) -> u32 {
+        self.played
+    }
+
+    pub fn won(&self) -> u32 {
+        self.won
+    }
+
+    /// Percentage of played games that were won, rounded down.
+    pub fn win_percentage(&self) -> u32 {
+        (self.won * 100).checked_div(self.played).unwrap_or(0)
+    }
+
+    pub fn current_streak(&self) -> u32 {
+        self.current_streak
+    }
+
+    pub fn max_streak(&self) -> u32 {
+        self.max_streak
+    }
+
+    pub fn distribution(&self) -> &[u32; MAX_GUESSES] {
+        &self.distribution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished(guesses: &[&str]) -> Game {
+        let mut game = Game::new("apple".parse().unwrap(), false);
+        for guess in guesses {
+            game.guess(guess).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn empty() {
+        let stats = Stats::default();
+        assert_eq!(stats.played(), 0);
+        assert_eq!(stats.win_percentage(), 0);
+    }
+
+    #[test]
+    fn ignores_unfinished_games() {
+        let mut stats = Stats::default();
+        stats.record(&finished(&["paper"]));
+        assert_eq!(stats, Stats::default());
+    }
+
+    #[test]
+    fn tracks_streaks_and_distribution() {
+        let won_in_two = finished(&["paper", "apple"]);
+        let lost = finished(&["about", "above", "abuse", "actor", "acute", "admit"]);
+
+        let mut stats = Stats::default();
+        for game in [&won_in_two, &won_in_two, &lost, &won_in_two] {
+            stats.record(game);
+        }
+
+        assert_eq!(stats.played(), 4);
+        assert_eq!(stats.won(), 3);
+        assert_eq!(stats.win_percentage(), 75);
+        assert_eq!(stats.current_streak(), 1);
+        assert_eq!(stats.max_streak(), 2);
+        assert_eq!(stats.distribution(), &[0, 3, 0, 0, 0, 0]);
+    }
+}
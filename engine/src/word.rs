@@ -0,0 +1,67 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use core::{fmt, str::FromStr};
+
+use crate::{GuessError, WORD_LENGTH};
+
+/// A five letter word in lowercase ASCII.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Word([u8; WORD_LENGTH]);
+
+impl Word {
+    pub fn letters(&self) -> &[u8; WORD_LENGTH] {
+        &self.0
+    }
+}
+
+impl FromStr for Word {
+    type Err = GuessError;
+
+    fn from_str(s: &str) -> Result<Self, GuessError> {
+        let letters: [u8; WORD_LENGTH] = s
+            .as_bytes()
+            .try_into()
+            .map_err(|_| GuessError::WrongLength)?;
+
+        if !letters.iter().all(u8::is_ascii_alphabetic) {
+            return Err(GuessError::NotALetter);
+        }
+
+        Ok(Self(letters.map(|letter| letter.to_ascii_lowercase())))
+    }
+}
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &letter in &self.0 {
+            write!(f, "{}", letter as char)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn parses_case_insensitively() {
+        let word: Word = "CrAnE".parse().unwrap();
+        assert_eq!(word.letters(), b"crane");
+        assert_eq!(word.to_string(), "crane");
+    }
+
+    #[test]
+    fn rejects_malformed_words() {
+        assert_eq!("".parse::<Word>(), Err(GuessError::WrongLength));
+        assert_eq!("cranes".parse::<Word>(), Err(GuessError::WrongLength));
+        assert_eq!("cr4ne".parse::<Word>(), Err(GuessError::NotALetter));
+        // Five bytes, but not five letters.
+        assert_eq!("cran\u{e9}".parse::<Word>(), Err(GuessError::WrongLength));
+    }
+}
@@ -0,0 +1,138 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Property tests of the engine against random words and games.
+
+use proptest::prelude::*;
+use wordle_engine::{
+    Game, LetterScore, MAX_GUESSES, Stats, Status, WORD_LENGTH, Word, dictionary, pick_answer,
+    score,
+};
+
+fn word() -> impl Strategy<Value = Word> {
+    "[a-z]{5}".prop_map(|s| s.parse().unwrap())
+}
+
+fn dictionary_word() -> impl Strategy<Value = Word> {
+    let words: Vec<Word> = dictionary().collect();
+    proptest::sample::select(words)
+}
+
+fn count(word: &Word, letter: u8) -> usize {
+    word.letters().iter().filter(|&&l| l == letter).count()
+}
+
+proptest! {
+    #[test]
+    fn correct_exactly_where_letters_match(guess in word(), answer in word()) {
+        let scores = score(&guess, &answer);
+        for (i, score) in scores.into_iter().enumerate() {
+            let matches = guess.letters()[i] == answer.letters()[i];
+            prop_assert_eq!(score == LetterScore::Correct, matches);
+        }
+    }
+
+    #[test]
+    fn only_the_answer_scores_all_correct(guess in word(), answer in word()) {
+        let all_correct = score(&guess, &answer) == [LetterScore::Correct; WORD_LENGTH];
+        prop_assert_eq!(all_correct, guess == answer);
+    }
+
+    #[test]
+    fn hints_never_exceed_the_answers_letters(guess in word(), answer in word()) {
+        let scores = score(&guess, &answer);
+        for letter in b'a'..=b'z' {
+            let hinted = (0..WORD_LENGTH)
+                .filter(|&i| guess.letters()[i] == letter && scores[i] != LetterScore::Absent)
+                .count();
+            // Every occurrence is hinted until the answer runs out of that letter.
+            prop_assert_eq!(hinted, count(&guess, letter).min(count(&answer, letter)));
+        }
+    }
+
+    #[test]
+    fn hint_count_is_symmetric(guess in word(), answer in word()) {
+        let forward = score(&guess, &answer);
+        let backward = score(&answer, &guess);
+        let hints = |scores: [LetterScore; WORD_LENGTH]| {
+            scores.iter().filter(|&&s| s != LetterScore::Absent).count()
+        };
+        prop_assert_eq!(hints(forward), hints(backward));
+    }
+
+    #[test]
+    fn answers_are_in_the_dictionary(seed in any::<u64>()) {
+        let answer = pick_answer(seed);
+        prop_assert!(dictionary().any(|word| word == answer));
+    }
+
+    #[test]
+    fn games_end_within_the_guess_limit(
+        answer in dictionary_word(),
+        guesses in proptest::collection::vec(dictionary_word(), 0..10),
+    ) {
+        let mut game = Game::new(answer, false);
+        for guess in &guesses {
+            let playing = game.status() == Status::Playing;
+            prop_assert_eq!(game.guess(&guess.to_string()).is_ok(), playing);
+        }
+
+        prop_assert!(game.guesses().len() <= MAX_GUESSES);
+        let won = game.guesses().last().is_some_and(|guess| guess.word == answer);
+        prop_assert_eq!(game.status() == Status::Won, won);
+    }
+
+    #[test]
+    fn hard_mode_guesses_keep_every_hint(
+        answer in dictionary_word(),
+        guesses in proptest::collection::vec(dictionary_word(), 0..20),
+    ) {
+        let mut game = Game::new(answer, true);
+        for guess in &guesses {
+            let _ = game.guess(&guess.to_string());
+        }
+
+        // Every accepted guess is consistent with all hints that came before it.
+        let accepted = game.guesses();
+        for (i, later) in accepted.iter().enumerate() {
+            for earlier in &accepted[..i] {
+                for position in 0..WORD_LENGTH {
+                    let letter = earlier.word.letters()[position];
+                    match earlier.scores[position] {
+                        LetterScore::Correct => {
+                            prop_assert_eq!(later.word.letters()[position], letter);
+                        }
+                        LetterScore::Present => prop_assert!(count(&later.word, letter) > 0),
+                        LetterScore::Absent => {}
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stats_add_up(outcomes in proptest::collection::vec(proptest::option::of(1..=MAX_GUESSES), 0..50)) {
+        let answer: Word = "apple".parse().unwrap();
+        let wrong = ["about", "above", "abuse", "actor", "acute", "admit"];
+
+        let mut stats = Stats::default();
+        for outcome in &outcomes {
+            let mut game = Game::new(answer, false);
+            // `Some(n)` wins on guess `n`, `None` loses.
+            let misses = outcome.map_or(MAX_GUESSES, |n| n - 1);
+            for guess in &wrong[..misses] {
+                game.guess(guess).unwrap();
+            }
+            if outcome.is_some() {
+                game.guess("apple").unwrap();
+            }
+            stats.record(&game);
+        }
+
+        prop_assert_eq!(stats.played() as usize, outcomes.len());
+        prop_assert_eq!(stats.distribution().iter().sum::<u32>(), stats.won());
+        prop_assert!(stats.current_streak() <= stats.max_streak());
+        prop_assert!(stats.win_percentage() <= 100);
+    }
+}
@@ -0,0 +1,207 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Layout and drawing of the board and the on-screen keyboard.
+
+use embedded_graphics::{
+    Drawable,
+    mono_font::{MonoTextStyle, ascii::FONT_10X20},
+    pixelcolor::Rgb888,
+    prelude::{DrawTarget, Point, Primitive, Size},
+    primitives::{PrimitiveStyle, PrimitiveStyleBuilder, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+use wordle_engine::{Game, LetterScore, MAX_GUESSES, Status, WORD_LENGTH};
+
+/// Colors the game is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub background: Rgb888,
+    pub foreground: Rgb888,
+    /// Used for the answer once the game is lost.
+    pub error: Rgb888,
+    pub correct: Rgb888,
+    pub present: Rgb888,
+    pub absent: Rgb888,
+    pub empty_tile: Rgb888,
+}
+
+const TILE_SIZE: i32 = 56;
+const TILE_GAP: i32 = 6;
+const KEY_WIDTH: i32 = 36;
+const WIDE_KEY_WIDTH: i32 = 60;
+const KEY_HEIGHT: i32 = 48;
+const KEY_GAP: i32 = 6;
+const SECTION_GAP: i32 = 32;
+
+pub const KEYBOARD_ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+/// The on-screen Enter key, at the start of the last row.
+pub const ENTER: u8 = b'\r';
+/// The on-screen Backspace key, at the end of the last row.
+pub const BACKSPACE: u8 = 0x08;
+
+fn key_width(key: u8) -> i32 {
+    match key {
+        ENTER | BACKSPACE => WIDE_KEY_WIDTH,
+        _ => KEY_WIDTH,
+    }
+}
+
+fn key_label(key: u8, tmp: &mut [u8; 4]) -> &str {
+    match key {
+        ENTER => "ENTER",
+        BACKSPACE => "DEL",
+        letter => (letter.to_ascii_uppercase() as char).encode_utf8(tmp),
+    }
+}
+
+fn score_color(palette: &Palette, score: Option<LetterScore>) -> Rgb888 {
+    match score {
+        Some(LetterScore::Correct) => palette.correct,
+        Some(LetterScore::Present) => palette.present,
+        Some(LetterScore::Absent) => palette.absent,
+        None => palette.empty_tile,
+    }
+}
+
+fn draw_key<D: DrawTarget<Color = Rgb888>>(
+    target: &mut D,
+    palette: &Palette,
+    area: Rectangle,
+    key: u8,
+    score: Option<LetterScore>,
+) -> Result<(), D::Error> {
+    area.into_styled(PrimitiveStyle::with_fill(score_color(palette, score)))
+        .draw(target)?;
+
+    let text_color = match score {
+        Some(_) => palette.background,
+        None => palette.foreground,
+    };
+
+    Text::with_text_style(
+        key_label(key, &mut [0; 4]),
+        area.center(),
+        MonoTextStyle::new(&FONT_10X20, text_color),
+        TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Middle)
+            .build(),
+    )
+    .draw(target)?;
+
+    Ok(())
+}
+
+/// Where each on-screen keyboard key is, for a target of the given size. Letters are identified by
+/// their lowercase ASCII byte, the other keys by [`ENTER`] and [`BACKSPACE`].
+pub fn keyboard_layout(size: Size) -> impl Iterator<Item = (u8, Rectangle)> {
+    let board_height = MAX_GUESSES as i32 * (TILE_SIZE + TILE_GAP) - TILE_GAP;
+    let keyboard_height = KEYBOARD_ROWS.len() as i32 * (KEY_HEIGHT + KEY_GAP) - KEY_GAP;
+    let top = (size.height as i32 - board_height - SECTION_GAP - keyboard_height) / 2
+        + board_height
+        + SECTION_GAP;
+
+    KEYBOARD_ROWS
+        .iter()
+        .enumerate()
+        .flat_map(move |(row, letters)| {
+            let (first, last) = if row == KEYBOARD_ROWS.len() - 1 {
+                (Some(ENTER), Some(BACKSPACE))
+            } else {
+                (None, None)
+            };
+            let keys = move || first.into_iter().chain(letters.bytes()).chain(last);
+
+            let row_width = keys().map(|key| key_width(key) + KEY_GAP).sum::<i32>() - KEY_GAP;
+            let left = (size.width as i32 - row_width) / 2;
+            let y = top + row as i32 * (KEY_HEIGHT + KEY_GAP);
+
+            keys().scan(left, move |x, key| {
+                let area = Rectangle::new(
+                    Point::new(*x, y),
+                    Size::new(key_width(key) as u32, KEY_HEIGHT as u32),
+                );
+                *x += key_width(key) + KEY_GAP;
+                Some((key, area))
+            })
+        })
+}
+
+/// Draws the whole game: the board of guesses with the keyboard below it, centered on `target`.
+pub fn draw<D: DrawTarget<Color = Rgb888>>(
+    target: &mut D,
+    game: &Game,
+    palette: &Palette,
+) -> Result<(), D::Error> {
+    let size = target.bounding_box().size;
+    target.clear(palette.background)?;
+
+    let board_width = WORD_LENGTH as i32 * (TILE_SIZE + TILE_GAP) - TILE_GAP;
+    let board_height = MAX_GUESSES as i32 * (TILE_SIZE + TILE_GAP) - TILE_GAP;
+    let keyboard_height = KEYBOARD_ROWS.len() as i32 * (KEY_HEIGHT + KEY_GAP) - KEY_GAP;
+    let left = (size.width as i32 - board_width) / 2;
+    let top = (size.height as i32 - board_height - SECTION_GAP - keyboard_height) / 2;
+
+    for row in 0..MAX_GUESSES {
+        let guess = game.guesses().get(row);
+
+        for column in 0..WORD_LENGTH {
+            let tile = Rectangle::new(
+                Point::new(
+                    left + column as i32 * (TILE_SIZE + TILE_GAP),
+                    top + row as i32 * (TILE_SIZE + TILE_GAP),
+                ),
+                Size::new(TILE_SIZE as u32, TILE_SIZE as u32),
+            );
+
+            match guess {
+                Some(guess) => draw_key(
+                    target,
+                    palette,
+                    tile,
+                    guess.word.letters()[column],
+                    Some(guess.scores[column]),
+                )?,
+                None => tile
+                    .into_styled(
+                        PrimitiveStyleBuilder::new()
+                            .stroke_color(palette.empty_tile)
+                            .stroke_width(2)
+                            .build(),
+                    )
+                    .draw(target)?,
+            }
+        }
+    }
+
+    let letters = game.letter_scores();
+    for (key, area) in keyboard_layout(size) {
+        let score = key
+            .is_ascii_lowercase()
+            .then(|| letters[(key - b'a') as usize])
+            .flatten();
+        draw_key(target, palette, area, key, score)?;
+    }
+
+    if game.status() == Status::Lost {
+        let mut tmp = [0u8; WORD_LENGTH];
+        for (byte, letter) in tmp.iter_mut().zip(game.answer().letters()) {
+            *byte = letter.to_ascii_uppercase();
+        }
+
+        Text::with_text_style(
+            core::str::from_utf8(&tmp).unwrap_or_default(),
+            Point::new(size.width as i32 / 2, top - SECTION_GAP / 2),
+            MonoTextStyle::new(&FONT_10X20, palette.error),
+            TextStyleBuilder::new()
+                .alignment(Alignment::Center)
+                .baseline(Baseline::Middle)
+                .build(),
+        )
+        .draw(target)?;
+    }
+
+    Ok(())
+}
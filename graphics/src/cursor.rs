@@ -0,0 +1,40 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The mouse cursor, shown as an [`Overlay`] so that moving it doesn't redraw the game.
+
+use embedded_graphics::prelude::Point;
+
+use crate::{Palette, framebuffer::Overlay};
+
+/// An arrow pointing up and to the left, with its tip in the top left corner.
+pub const ARROW: &[&str] = &[
+    "#",
+    "##",
+    "#.#",
+    "#..#",
+    "#...#",
+    "#....#",
+    "#.....#",
+    "#......#",
+    "#.......#",
+    "#........#",
+    "#.....#####",
+    "#..#..#",
+    "#.# #..#",
+    "##  #..#",
+    "#    #..#",
+    "     #..#",
+    "      ##",
+];
+
+/// The cursor with its tip at `position`, in the colors of `palette`.
+pub fn cursor(position: Point, palette: &Palette) -> Overlay {
+    Overlay {
+        position,
+        image: ARROW,
+        outline: palette.background,
+        fill: palette.foreground,
+    }
+}
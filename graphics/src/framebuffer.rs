@@ -0,0 +1,439 @@
+// SPDX-FileCopyrightText: 2025 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use core::{convert::Infallible, fmt};
+
+use embedded_graphics::{
+    Pixel,
+    pixelcolor::Rgb888,
+    prelude::{DrawTarget, OriginDimensions, Point, RgbColor, Size},
+};
+
+/// How the color channels are packed into a pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelFormat {
+    pub bpp: u16,
+    pub red_mask_size: u8,
+    pub red_mask_shift: u8,
+    pub green_mask_size: u8,
+    pub green_mask_shift: u8,
+    pub blue_mask_size: u8,
+    pub blue_mask_shift: u8,
+}
+
+impl PixelFormat {
+    /// 32 bits per pixel with blue in the lowest byte, which is what firmware usually sets up.
+    pub const XRGB8888: Self = Self {
+        bpp: 32,
+        red_mask_size: 8,
+        red_mask_shift: 16,
+        green_mask_size: 8,
+        green_mask_shift: 8,
+        blue_mask_size: 8,
+        blue_mask_shift: 0,
+    };
+
+    pub fn bytes_per_pixel(&self) -> usize {
+        self.bpp as usize / 8
+    }
+
+    fn is_supported(&self) -> bool {
+        let fits = |size: u8, shift: u8| size <= 8 && size as u16 + shift as u16 <= self.bpp;
+
+        matches!(self.bpp, 8 | 16 | 24 | 32)
+            && fits(self.red_mask_size, self.red_mask_shift)
+            && fits(self.green_mask_size, self.green_mask_shift)
+            && fits(self.blue_mask_size, self.blue_mask_shift)
+    }
+
+    pub fn encode(&self, color: Rgb888) -> u32 {
+        fn channel(value: u8, size: u8, shift: u8) -> u32 {
+            let max = (1u32 << size) - 1;
+            ((value as u32 * max + 127) / 255) << shift
+        }
+
+        channel(color.r(), self.red_mask_size, self.red_mask_shift)
+            | channel(color.g(), self.green_mask_size, self.green_mask_shift)
+            | channel(color.b(), self.blue_mask_size, self.blue_mask_shift)
+    }
+
+    pub fn decode(&self, value: u32) -> Rgb888 {
+        fn channel(value: u32, size: u8, shift: u8) -> u8 {
+            let max = (1u32 << size) - 1;
+            (((value >> shift) & max) * 255 + max / 2)
+                .checked_div(max)
+                .unwrap_or(0) as u8
+        }
+
+        Rgb888::new(
+            channel(value, self.red_mask_size, self.red_mask_shift),
+            channel(value, self.green_mask_size, self.green_mask_shift),
+            channel(value, self.blue_mask_size, self.blue_mask_shift),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramebufferError {
+    /// The bits per pixel aren't a whole number of bytes up to 4, or a channel doesn't fit.
+    UnsupportedFormat,
+    /// A row of pixels is wider than the pitch.
+    PitchTooSmall,
+    /// A buffer can't hold `pitch * height` bytes.
+    BufferTooSmall,
+}
+
+impl fmt::Display for FramebufferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedFormat => f.write_str("unsupported pixel format"),
+            Self::PitchTooSmall => f.write_str("pitch is smaller than a row of pixels"),
+            Self::BufferTooSmall => f.write_str("buffer is smaller than the framebuffer"),
+        }
+    }
+}
+
+/// A small image shown over the framebuffer's contents without being drawn into the back buffer,
+/// so that it can move without redrawing everything underneath, like a mouse cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overlay {
+    /// Where the top left corner of the image goes. Parts off screen are cut off.
+    pub position: Point,
+    /// Rows of pixels. `#` is drawn in `outline`, `.` in `fill`, and anything else is transparent.
+    pub image: &'static [&'static str],
+    pub outline: Rgb888,
+    pub fill: Rgb888,
+}
+
+impl Overlay {
+    fn pixels(&self) -> impl Iterator<Item = (Point, Rgb888)> + '_ {
+        self.image.iter().enumerate().flat_map(move |(y, row)| {
+            row.bytes().enumerate().filter_map(move |(x, byte)| {
+                let color = match byte {
+                    b'#' => self.outline,
+                    b'.' => self.fill,
+                    _ => return None,
+                };
+                Some((self.position + Point::new(x as i32, y as i32), color))
+            })
+        })
+    }
+}
+
+/// A double buffered framebuffer. Drawing goes to the back buffer and [`Framebuffer::flush`]
+/// copies it to the front buffer, which is usually the one on screen, with the [`Overlay`] on top.
+pub struct Framebuffer<'a> {
+    back: &'a mut [u8],
+    front: &'a mut [u8],
+    width: usize,
+    height: usize,
+    pitch: usize,
+    format: PixelFormat,
+    overlay: Option<Overlay>,
+}
+
+impl<'a> Framebuffer<'a> {
+    /// Wraps two buffers of at least `pitch * height` bytes each, with rows `pitch` bytes apart.
+    pub fn new(
+        back: &'a mut [u8],
+        front: &'a mut [u8],
+        width: usize,
+        height: usize,
+        pitch: usize,
+        format: PixelFormat,
+    ) -> Result<Self, FramebufferError> {
+        if !format.is_supported() {
+            return Err(FramebufferError::UnsupportedFormat);
+        }
+        if pitch < width * format.bytes_per_pixel() {
+            return Err(FramebufferError::PitchTooSmall);
+        }
+
+        let length = pitch * height;
+        if back.len() < length || front.len() < length {
+            return Err(FramebufferError::BufferTooSmall);
+        }
+
+        Ok(Self {
+            back: &mut back[..length],
+            front: &mut front[..length],
+            width,
+            height,
+            pitch,
+            format,
+            overlay: None,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    fn pixel_range(&self, x: usize, y: usize) -> core::ops::Range<usize> {
+        assert!(x < self.width);
+        assert!(y < self.height);
+
+        let start = y * self.pitch + x * self.format.bytes_per_pixel();
+        start..start + self.format.bytes_per_pixel()
+    }
+
+    pub fn write_pixel(&mut self, x: usize, y: usize, color: Rgb888) {
+        let range = self.pixel_range(x, y);
+        let value = self.format.encode(color).to_le_bytes();
+        self.back[range].copy_from_slice(&value[..self.format.bytes_per_pixel()]);
+    }
+
+    /// Reads a pixel back from the back buffer.
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb888 {
+        let range = self.pixel_range(x, y);
+        let mut value = [0; 4];
+        value[..range.len()].copy_from_slice(&self.back[range]);
+        self.format.decode(u32::from_le_bytes(value))
+    }
+
+    pub fn back(&self) -> &[u8] {
+        self.back
+    }
+
+    pub fn front(&self) -> &[u8] {
+        self.front
+    }
+
+    // TODO: Potentially test out techniques like dirty rectangles etc if this is not fast enough.
+    pub fn flush(&mut self) {
+        self.flush_with(|front, back| front.copy_from_slice(back));
+    }
+
+    /// Like [`Framebuffer::flush`], but `copy` copies the back buffer into the front buffer, so
+    /// that the caller can split the work up.
+    pub fn flush_with(&mut self, copy: impl FnOnce(&mut [u8], &[u8])) {
+        copy(self.front, self.back);
+        self.draw_overlay();
+    }
+
+    /// The pixel at `point`, if it is on screen.
+    fn on_screen(&self, point: Point) -> Option<(usize, usize)> {
+        let (x, y) = (
+            usize::try_from(point.x).ok()?,
+            usize::try_from(point.y).ok()?,
+        );
+        (x < self.width && y < self.height).then_some((x, y))
+    }
+
+    fn draw_overlay(&mut self) {
+        let Some(overlay) = self.overlay else {
+            return;
+        };
+
+        for (point, color) in overlay.pixels() {
+            if let Some((x, y)) = self.on_screen(point) {
+                let range = self.pixel_range(x, y);
+                let value = self.format.encode(color).to_le_bytes();
+                self.front[range].copy_from_slice(&value[..self.format.bytes_per_pixel()]);
+            }
+        }
+    }
+
+    pub fn overlay(&self) -> Option<Overlay> {
+        self.overlay
+    }
+
+    /// Shows `overlay` on the front buffer in place of the previous one. Only the pixels under
+    /// either of them change, and those under the previous one are restored from the back buffer,
+    /// so anything drawn since the last flush shows up there.
+    pub fn set_overlay(&mut self, overlay: Option<Overlay>) {
+        if overlay == self.overlay {
+            return;
+        }
+
+        if let Some(previous) = self.overlay.take() {
+            for (point, _) in previous.pixels() {
+                if let Some((x, y)) = self.on_screen(point) {
+                    let range = self.pixel_range(x, y);
+                    self.front[range.clone()].copy_from_slice(&self.back[range]);
+                }
+            }
+        }
+
+        self.overlay = overlay;
+        self.draw_overlay();
+    }
+}
+
+impl OriginDimensions for Framebuffer<'_> {
+    fn size(&self) -> Size {
+        Size::new(self.width as u32, self.height as u32)
+    }
+}
+
+impl DrawTarget for Framebuffer<'_> {
+    type Color = Rgb888;
+
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels {
+            if let (Ok(x), Ok(y)) = (usize::try_from(coord.x), usize::try_from(coord.y))
+                && x < self.width
+                && y < self.height
+            {
+                self.write_pixel(x, y, color);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RGB565: PixelFormat = PixelFormat {
+        bpp: 16,
+        red_mask_size: 5,
+        red_mask_shift: 11,
+        green_mask_size: 6,
+        green_mask_shift: 5,
+        blue_mask_size: 5,
+        blue_mask_shift: 0,
+    };
+
+    #[test]
+    fn encodes_channels_into_their_masks() {
+        let format = PixelFormat::XRGB8888;
+        assert_eq!(format.encode(Rgb888::new(0x12, 0x34, 0x56)), 0x0012_3456);
+        assert_eq!(RGB565.encode(Rgb888::WHITE), 0xffff);
+        assert_eq!(RGB565.encode(Rgb888::RED), 0xf800);
+    }
+
+    #[test]
+    fn decode_inverts_encode() {
+        let color = Rgb888::new(0x12, 0x34, 0x56);
+        assert_eq!(
+            PixelFormat::XRGB8888.decode(PixelFormat::XRGB8888.encode(color)),
+            color
+        );
+        assert_eq!(RGB565.decode(RGB565.encode(Rgb888::WHITE)), Rgb888::WHITE);
+    }
+
+    #[test]
+    fn validates_layout() {
+        let (mut back, mut front) = ([0; 64], [0; 64]);
+
+        let odd = PixelFormat { bpp: 12, ..RGB565 };
+        assert_eq!(
+            Framebuffer::new(&mut back, &mut front, 4, 4, 8, odd).err(),
+            Some(FramebufferError::UnsupportedFormat)
+        );
+        assert_eq!(
+            Framebuffer::new(&mut back, &mut front, 4, 4, 6, RGB565).err(),
+            Some(FramebufferError::PitchTooSmall)
+        );
+        assert_eq!(
+            Framebuffer::new(&mut back, &mut front, 4, 5, 16, RGB565).err(),
+            Some(FramebufferError::BufferTooSmall)
+        );
+        assert!(Framebuffer::new(&mut back, &mut front, 4, 4, 16, RGB565).is_ok());
+    }
+
+    #[test]
+    fn pixels_stay_inside_their_row() {
+        // 24 bits per pixel with two bytes of padding at the end of each row.
+        let (mut back, mut front) = ([0; 22], [0; 22]);
+        let format = PixelFormat {
+            bpp: 24,
+            ..PixelFormat::XRGB8888
+        };
+        let mut framebuffer = Framebuffer::new(&mut back, &mut front, 3, 2, 11, format).unwrap();
+
+        framebuffer.write_pixel(2, 0, Rgb888::WHITE);
+        assert_eq!(framebuffer.back()[6..11], [0xff, 0xff, 0xff, 0, 0]);
+        assert_eq!(framebuffer.pixel(2, 0), Rgb888::WHITE);
+        assert_eq!(framebuffer.pixel(0, 1), Rgb888::BLACK);
+    }
+
+    #[test]
+    fn drawing_clips_and_flush_copies() {
+        let (mut back, mut front) = ([0; 64], [0; 64]);
+        let mut framebuffer =
+            Framebuffer::new(&mut back, &mut front, 4, 4, 16, PixelFormat::XRGB8888).unwrap();
+
+        let pixels = [(-1, 0), (0, -1), (4, 0), (0, 4), (3, 3)]
+            .map(|(x, y)| Pixel(embedded_graphics::prelude::Point::new(x, y), Rgb888::WHITE));
+        framebuffer.draw_iter(pixels).unwrap();
+        assert_eq!(framebuffer.pixel(3, 3), Rgb888::WHITE);
+        assert_eq!(framebuffer.back().iter().filter(|&&b| b != 0).count(), 3);
+        assert!(framebuffer.front().iter().all(|&b| b == 0));
+
+        framebuffer.flush();
+        assert_eq!(framebuffer.front(), framebuffer.back());
+    }
+
+    const DOT: Overlay = Overlay {
+        position: Point::new(1, 1),
+        image: &["#.", " #"],
+        outline: Rgb888::WHITE,
+        fill: Rgb888::RED,
+    };
+
+    /// Reads a pixel from the front buffer, which `pixel` doesn't.
+    fn shown(framebuffer: &Framebuffer, x: usize, y: usize) -> u32 {
+        let start = y * framebuffer.pitch + x * 4;
+        u32::from_le_bytes(framebuffer.front()[start..start + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn overlay_is_only_drawn_on_the_front_buffer() {
+        let (mut back, mut front) = ([0; 64], [0; 64]);
+        let mut framebuffer =
+            Framebuffer::new(&mut back, &mut front, 4, 4, 16, PixelFormat::XRGB8888).unwrap();
+
+        framebuffer.set_overlay(Some(DOT));
+        assert!(framebuffer.back().iter().all(|&b| b == 0));
+        assert_eq!(shown(&framebuffer, 1, 1), 0xffffff);
+        assert_eq!(shown(&framebuffer, 2, 1), 0xff0000);
+        assert_eq!(shown(&framebuffer, 1, 2), 0);
+        assert_eq!(shown(&framebuffer, 2, 2), 0xffffff);
+
+        // Flushing keeps it on top.
+        framebuffer.flush();
+        assert_eq!(shown(&framebuffer, 2, 2), 0xffffff);
+    }
+
+    #[test]
+    fn moving_the_overlay_restores_what_was_under_it() {
+        let (mut back, mut front) = ([0; 64], [0; 64]);
+        let mut framebuffer =
+            Framebuffer::new(&mut back, &mut front, 4, 4, 16, PixelFormat::XRGB8888).unwrap();
+        framebuffer.write_pixel(1, 1, Rgb888::BLUE);
+        framebuffer.flush();
+
+        framebuffer.set_overlay(Some(DOT));
+        // Partly off screen, which is cut off.
+        framebuffer.set_overlay(Some(Overlay {
+            position: Point::new(2, -1),
+            ..DOT
+        }));
+
+        assert_eq!(shown(&framebuffer, 1, 1), 0x0000ff);
+        assert_eq!(shown(&framebuffer, 2, 1), 0);
+        assert_eq!(shown(&framebuffer, 2, 2), 0);
+        assert_eq!(shown(&framebuffer, 3, 0), 0xffffff);
+
+        framebuffer.set_overlay(None);
+        assert_eq!(framebuffer.front(), framebuffer.back());
+    }
+}
@@ -0,0 +1,16 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Drawing the game into a framebuffer. Kept out of the kernel so that rendering can be tested on
+//! the host against in-memory framebuffers.
+
+#![no_std]
+
+pub mod board;
+pub mod cursor;
+pub mod framebuffer;
+
+pub use board::{BACKSPACE, ENTER, KEYBOARD_ROWS, Palette, draw, keyboard_layout};
+pub use cursor::cursor;
+pub use framebuffer::{Framebuffer, FramebufferError, Overlay, PixelFormat};
@@ -0,0 +1,190 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Renders the board into in-memory framebuffers and compares the result against the PNG
+//! snapshots in `tests/snapshots`.
+//!
+//! Run with `UPDATE_SNAPSHOTS=1` to rewrite the snapshots after an intended change to the
+//! rendering, and check the new images before committing them.
+
+use std::{fs::File, io::BufWriter, path::PathBuf};
+
+use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+use wordle_engine::Game;
+use wordle_graphics::{Framebuffer, Palette, PixelFormat, draw};
+
+const WIDTH: usize = 480;
+const HEIGHT: usize = 600;
+
+/// The kernel's palette for Catppuccin Mocha, the default theme.
+const MOCHA: Palette = Palette {
+    background: Rgb888::new(0x11, 0x11, 0x1b),
+    foreground: Rgb888::new(0xcd, 0xd6, 0xf4),
+    error: Rgb888::new(0xf3, 0x8b, 0xa8),
+    correct: Rgb888::new(0xa6, 0xe3, 0xa1),
+    present: Rgb888::new(0xf9, 0xe2, 0xaf),
+    absent: Rgb888::new(0x45, 0x47, 0x5a),
+    empty_tile: Rgb888::new(0x31, 0x32, 0x44),
+};
+
+/// Blue in the lowest byte, three bytes per pixel.
+const BGR888: PixelFormat = PixelFormat {
+    bpp: 24,
+    red_mask_size: 8,
+    red_mask_shift: 0,
+    green_mask_size: 8,
+    green_mask_shift: 8,
+    blue_mask_size: 8,
+    blue_mask_shift: 16,
+};
+
+const RGB565: PixelFormat = PixelFormat {
+    bpp: 16,
+    red_mask_size: 5,
+    red_mask_shift: 11,
+    green_mask_size: 6,
+    green_mask_shift: 5,
+    blue_mask_size: 5,
+    blue_mask_shift: 0,
+};
+
+fn game(answer: &str, guesses: &[&str]) -> Game {
+    let mut game = Game::new(answer.parse().unwrap(), false);
+    for guess in guesses {
+        game.guess(guess).unwrap();
+    }
+    game
+}
+
+/// Draws `game` and returns what ended up in the front buffer as packed RGB.
+fn render(game: &Game, format: PixelFormat, padding: usize) -> Vec<u8> {
+    let pitch = WIDTH * format.bytes_per_pixel() + padding;
+    let (mut back, mut front) = (vec![0; pitch * HEIGHT], vec![0; pitch * HEIGHT]);
+    let mut framebuffer =
+        Framebuffer::new(&mut back, &mut front, WIDTH, HEIGHT, pitch, format).unwrap();
+
+    draw(&mut framebuffer, game, &MOCHA).unwrap();
+    framebuffer.flush();
+
+    // Read through a second framebuffer over the front buffer to check what flush copied.
+    let mut scratch = vec![0; pitch * HEIGHT];
+    let shown = Framebuffer::new(&mut front, &mut scratch, WIDTH, HEIGHT, pitch, format).unwrap();
+
+    let mut rgb = Vec::with_capacity(WIDTH * HEIGHT * 3);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let color = shown.pixel(x, y);
+            rgb.extend([color.r(), color.g(), color.b()]);
+        }
+    }
+    rgb
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.png"))
+}
+
+fn write_png(path: &PathBuf, rgb: &[u8]) {
+    let file = BufWriter::new(File::create(path).unwrap());
+    let mut encoder = png::Encoder::new(file, WIDTH as u32, HEIGHT as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::High);
+    encoder
+        .write_header()
+        .unwrap()
+        .write_image_data(rgb)
+        .unwrap();
+}
+
+fn read_png(path: &PathBuf) -> Vec<u8> {
+    let file = File::open(path).unwrap_or_else(|e| {
+        panic!(
+            "Can't open snapshot {}: {e}. Run with UPDATE_SNAPSHOTS=1 to create it.",
+            path.display()
+        )
+    });
+    let mut reader = png::Decoder::new(std::io::BufReader::new(file))
+        .read_info()
+        .unwrap();
+    let mut rgb = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut rgb).unwrap();
+
+    assert_eq!((info.width, info.height), (WIDTH as u32, HEIGHT as u32));
+    assert_eq!(info.color_type, png::ColorType::Rgb);
+    rgb.truncate(info.buffer_size());
+    rgb
+}
+
+fn assert_matches_snapshot(name: &str, rgb: &[u8]) {
+    let path = snapshot_path(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        write_png(&path, rgb);
+        return;
+    }
+
+    let expected = read_png(&path);
+    if expected != rgb {
+        let actual = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.png"));
+        write_png(&actual, rgb);
+
+        let differing = expected
+            .chunks(3)
+            .zip(rgb.chunks(3))
+            .filter(|(a, b)| a != b)
+            .count();
+        panic!(
+            "{differing} pixels differ from {}, the render is at {}.",
+            path.display(),
+            actual.display()
+        );
+    }
+}
+
+#[test]
+fn empty_board() {
+    let rgb = render(&game("apple", &[]), PixelFormat::XRGB8888, 0);
+    assert_matches_snapshot("empty", &rgb);
+}
+
+#[test]
+fn in_progress() {
+    let rgb = render(
+        &game("apple", &["alarm", "speed"]),
+        PixelFormat::XRGB8888,
+        0,
+    );
+    assert_matches_snapshot("in_progress", &rgb);
+}
+
+#[test]
+fn won() {
+    let rgb = render(
+        &game("apple", &["paper", "alike", "apple"]),
+        PixelFormat::XRGB8888,
+        0,
+    );
+    assert_matches_snapshot("won", &rgb);
+}
+
+#[test]
+fn lost_shows_the_answer() {
+    let guesses = ["about", "above", "abuse", "actor", "acute", "admit"];
+    let rgb = render(&game("apple", &guesses), PixelFormat::XRGB8888, 0);
+    assert_matches_snapshot("lost", &rgb);
+}
+
+#[test]
+fn padded_24_bit_matches_32_bit() {
+    let game = game("apple", &["alarm", "speed"]);
+    assert!(render(&game, BGR888, 13) == render(&game, PixelFormat::XRGB8888, 0));
+}
+
+#[test]
+fn rgb565() {
+    let rgb = render(&game("apple", &["alarm", "speed"]), RGB565, 6);
+    assert_matches_snapshot("in_progress_rgb565", &rgb);
+}
@@ -2,6 +2,11 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use core::{
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
 use embedded_graphics::pixelcolor::Rgb888;
 
 macro_rules! color_impl {
@@ -13,14 +18,135 @@ macro_rules! color_impl {
     };
 }
 
-pub const COLOR_SCHEME: catppuccin::Flavor = catppuccin::PALETTE.mocha;
+macro_rules! delegate_impl {
+    ($($func_name:ident),* $(,)?) => {
+        $(
+            fn $func_name(&self) -> Rgb888 {
+                self.flavor().$func_name()
+            }
+        )*
+    };
+}
+
+/// The color scheme everything is drawn with. It can be switched at runtime with
+/// [`ActiveTheme::set`], after which the screen has to be redrawn.
+pub static COLOR_SCHEME: ActiveTheme = ActiveTheme(AtomicU8::new(Theme::Mocha as u8));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum Theme {
+    Latte,
+    Frappe,
+    Macchiato,
+    #[default]
+    Mocha,
+}
+
+impl Theme {
+    pub const ALL: [Self; 4] = [Self::Latte, Self::Frappe, Self::Macchiato, Self::Mocha];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Latte => "latte",
+            Self::Frappe => "frappe",
+            Self::Macchiato => "macchiato",
+            Self::Mocha => "mocha",
+        }
+    }
+
+    fn flavor(self) -> &'static catppuccin::Flavor {
+        match self {
+            Self::Latte => &catppuccin::PALETTE.latte,
+            Self::Frappe => &catppuccin::PALETTE.frappe,
+            Self::Macchiato => &catppuccin::PALETTE.macchiato,
+            Self::Mocha => &catppuccin::PALETTE.mocha,
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.name() == s)
+            .ok_or(())
+    }
+}
+
+/// Lock-free handle to the current [`Theme`] so that it can still be read while panicking.
+pub struct ActiveTheme(AtomicU8);
+
+impl ActiveTheme {
+    pub fn get(&self) -> Theme {
+        Theme::ALL[self.0.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set(&self, theme: Theme) {
+        self.0.store(theme as u8, Ordering::Relaxed);
+    }
+
+    fn flavor(&self) -> &'static catppuccin::Flavor {
+        self.get().flavor()
+    }
+}
 
 pub trait ColorScheme {
     fn background(&self) -> Rgb888;
+    fn foreground(&self) -> Rgb888;
+    fn subtle_foreground(&self) -> Rgb888;
     fn error_foreground(&self) -> Rgb888;
+    fn crash_background(&self) -> Rgb888;
+    fn ansi_black(&self) -> Rgb888;
+    fn ansi_green(&self) -> Rgb888;
+    fn ansi_yellow(&self) -> Rgb888;
+    fn ansi_blue(&self) -> Rgb888;
+    fn ansi_magenta(&self) -> Rgb888;
+    fn ansi_cyan(&self) -> Rgb888;
+    fn ansi_white(&self) -> Rgb888;
+    fn correct(&self) -> Rgb888;
+    fn present(&self) -> Rgb888;
+    fn absent(&self) -> Rgb888;
+    fn empty_tile(&self) -> Rgb888;
 }
 
 impl ColorScheme for catppuccin::Flavor {
     color_impl!(background, crust);
+    color_impl!(foreground, text);
+    color_impl!(subtle_foreground, subtext0);
     color_impl!(error_foreground, red);
+    color_impl!(crash_background, base);
+    color_impl!(ansi_black, surface1);
+    color_impl!(ansi_green, green);
+    color_impl!(ansi_yellow, yellow);
+    color_impl!(ansi_blue, blue);
+    color_impl!(ansi_magenta, pink);
+    color_impl!(ansi_cyan, teal);
+    color_impl!(ansi_white, subtext1);
+    color_impl!(correct, green);
+    color_impl!(present, yellow);
+    color_impl!(absent, surface1);
+    color_impl!(empty_tile, surface0);
+}
+
+impl ColorScheme for ActiveTheme {
+    delegate_impl!(
+        background,
+        foreground,
+        subtle_foreground,
+        error_foreground,
+        crash_background,
+        ansi_black,
+        ansi_green,
+        ansi_yellow,
+        ansi_blue,
+        ansi_magenta,
+        ansi_cyan,
+        ansi_white,
+        correct,
+        present,
+        absent,
+        empty_tile,
+    );
 }
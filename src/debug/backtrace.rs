@@ -0,0 +1,165 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Frame pointer based stack walking. The kernel is built with `force-frame-pointers` so every
+//! frame starts with the caller's `rbp` followed by the return address.
+
+use core::{
+    arch::asm,
+    fmt,
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use x86_64::structures::paging::{PageSize, Size4KiB};
+
+use crate::{
+    debug::symbols,
+    trampoline::{
+        gdt,
+        memory::{STACK_BASE, STACK_PAGES},
+        smp,
+    },
+};
+
+const MAX_FRAMES: usize = 32;
+
+/// Limine guarantees at least this much stack for the entry point.
+const BOOT_STACK_SIZE: u64 = 64 * 1024;
+
+/// Top of the stack Limine entered the kernel on, or 0 before [`record_boot_stack`].
+static BOOT_STACK_TOP: AtomicU64 = AtomicU64::new(0);
+
+/// Remembers the stack the caller runs on as the one Limine entered the kernel on, so that
+/// backtraces taken on it know where it ends. Must be called first thing in the entry point.
+#[inline(always)]
+pub fn record_boot_stack() {
+    let rbp: u64;
+    // SAFETY: Reading rbp has no side effects.
+    unsafe {
+        asm!("mov {}, rbp", out(reg) rbp, options(nomem, nostack, preserves_flags));
+    }
+
+    // The entry point's saved rbp and return address are the last things on the stack.
+    BOOT_STACK_TOP.store(rbp + 16, Ordering::Relaxed);
+}
+
+/// Return addresses collected by walking the stack, innermost first.
+pub struct Backtrace {
+    frames: [u64; MAX_FRAMES],
+    len: usize,
+    truncated: bool,
+}
+
+/// Returns the bounds of the stack that `addr` lies on.
+fn stack_bounds(addr: u64) -> Range<u64> {
+    let kernel_stack = STACK_BASE.start_address().as_u64()
+        ..STACK_BASE.start_address().as_u64() + STACK_PAGES * Size4KiB::SIZE;
+
+    if kernel_stack.contains(&addr) {
+        return kernel_stack;
+    }
+
+    for stack in gdt::ist_stacks().chain(smp::cpus().iter().map(|cpu| cpu.stack.clone())) {
+        if stack.contains(&addr) {
+            return stack;
+        }
+    }
+
+    let top = BOOT_STACK_TOP.load(Ordering::Relaxed);
+    let boot_stack = top.saturating_sub(BOOT_STACK_SIZE)..top;
+    if boot_stack.contains(&addr) {
+        return boot_stack;
+    }
+
+    // Not a stack we know the bounds of, so don't walk it at all.
+    addr..addr
+}
+
+impl Backtrace {
+    /// Walks the stack of the caller.
+    #[inline(always)]
+    pub fn capture() -> Self {
+        let rbp: u64;
+        // SAFETY: Reading rbp has no side effects.
+        unsafe {
+            asm!("mov {}, rbp", out(reg) rbp, options(nomem, nostack, preserves_flags));
+        }
+
+        // SAFETY: rbp of the current function is a valid frame pointer.
+        unsafe { Self::from_frame_pointer(rbp) }
+    }
+
+    /// Walks the chain of frames starting at `rbp`, stopping at the first frame pointer that is
+    /// misaligned, leaves the stack it started on, or doesn't move towards the stack's base.
+    ///
+    /// # SAFETY
+    /// `rbp` must be a frame pointer on a mapped stack.
+    pub unsafe fn from_frame_pointer(mut rbp: u64) -> Self {
+        let mut backtrace = Self {
+            frames: [0; MAX_FRAMES],
+            len: 0,
+            truncated: false,
+        };
+
+        let bounds = stack_bounds(rbp);
+
+        while rbp.is_multiple_of(8) && rbp >= bounds.start && rbp + 16 <= bounds.end {
+            if backtrace.len == MAX_FRAMES {
+                backtrace.truncated = true;
+                break;
+            }
+
+            // SAFETY: rbp was checked to be inside the stack, which the caller guarantees is
+            // mapped.
+            let (next, return_address) = unsafe {
+                let frame = rbp as *const u64;
+                (frame.read(), frame.add(1).read())
+            };
+
+            if return_address == 0 {
+                break;
+            }
+
+            backtrace.frames[backtrace.len] = return_address;
+            backtrace.len += 1;
+
+            // Stacks grow down, so the caller's frame must be at a higher address.
+            if next <= rbp {
+                break;
+            }
+            rbp = next;
+        }
+
+        backtrace
+    }
+
+    pub fn frames(&self) -> &[u64] {
+        &self.frames[..self.len]
+    }
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Backtrace:")?;
+        for (i, &address) in self.frames().iter().enumerate() {
+            // Return addresses point after the call, which may already be in the next function if
+            // the call was the last instruction. Look up the call itself instead.
+            match symbols::resolve(address - 1) {
+                Some(symbol) => writeln!(
+                    f,
+                    "  {i:>2}: {address:#018x} {}+{:#x}",
+                    symbol.name,
+                    symbol.offset + 1
+                )?,
+                None => writeln!(f, "  {i:>2}: {address:#018x}")?,
+            }
+        }
+        if self.truncated {
+            writeln!(f, "  ...")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! A scrolling text console backed by a ring buffer of lines.
+//!
+//! The console only keeps track of text. Call [`Console::render`] to bring a draw target up to
+//! date, which only redraws what changed unless the console scrolled.
+//!
+//! The subset of ANSI escape sequences understood is `ESC[<n>m` for colors (0, 30-37, 39 and
+//! 90-97) and `ESC[2J` to clear.
+
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyleBuilder, ascii::FONT_9X18_BOLD},
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, renderer::TextRenderer},
+};
+
+use crate::color::{COLOR_SCHEME, ColorScheme};
+
+/// Number of lines kept for scrollback.
+pub const CAPACITY: usize = 200;
+/// Lines longer than this are wrapped even if the screen is wider.
+pub const MAX_COLUMNS: usize = 128;
+
+const FONT: &MonoFont = &FONT_9X18_BOLD;
+const ESCAPE: char = '\x1b';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ConsoleColor {
+    Foreground,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl ConsoleColor {
+    pub fn to_rgb(self) -> Rgb888 {
+        match self {
+            Self::Foreground => COLOR_SCHEME.foreground(),
+            Self::Black => COLOR_SCHEME.ansi_black(),
+            Self::Red => COLOR_SCHEME.error_foreground(),
+            Self::Green => COLOR_SCHEME.ansi_green(),
+            Self::Yellow => COLOR_SCHEME.ansi_yellow(),
+            Self::Blue => COLOR_SCHEME.ansi_blue(),
+            Self::Magenta => COLOR_SCHEME.ansi_magenta(),
+            Self::Cyan => COLOR_SCHEME.ansi_cyan(),
+            Self::White => COLOR_SCHEME.ansi_white(),
+        }
+    }
+
+    /// Maps an SGR foreground parameter (30-37 or 90-97) to a color. Bright colors map to the
+    /// same color as their normal counterpart.
+    fn from_sgr(parameter: u16) -> Option<Self> {
+        let index = match parameter {
+            30..=37 => parameter - 30,
+            90..=97 => parameter - 90,
+            _ => return None,
+        };
+
+        Some(match index {
+            0 => Self::Black,
+            1 => Self::Red,
+            2 => Self::Green,
+            3 => Self::Yellow,
+            4 => Self::Blue,
+            5 => Self::Magenta,
+            6 => Self::Cyan,
+            _ => Self::White,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Cell {
+    c: u8,
+    color: ConsoleColor,
+}
+
+impl Cell {
+    const BLANK: Self = Self {
+        c: b' ',
+        color: ConsoleColor::Foreground,
+    };
+}
+
+#[derive(Clone, Copy)]
+struct Line {
+    cells: [Cell; MAX_COLUMNS],
+    len: u8,
+}
+
+impl Line {
+    const EMPTY: Self = Self {
+        cells: [Cell::BLANK; MAX_COLUMNS],
+        len: 0,
+    };
+}
+
+#[derive(Clone, Copy)]
+enum EscapeState {
+    None,
+    Escape,
+    /// Inside `ESC[`, with the parameter collected so far.
+    Csi(u16),
+}
+
+pub struct Console {
+    lines: [Line; CAPACITY],
+    /// Total number of lines ever started. The current line is `total_lines - 1` and lives at
+    /// index `(total_lines - 1) % CAPACITY`.
+    total_lines: usize,
+    default_color: ConsoleColor,
+    color: ConsoleColor,
+    escape: EscapeState,
+    /// How many lines the view is scrolled back from the bottom.
+    scroll_offset: usize,
+    /// Columns the console wraps at, set from the draw target on each render.
+    columns: usize,
+    /// Everything before this (line, column) position has already been drawn.
+    drawn: (usize, usize),
+    /// The line that was at the top of the view when it was last drawn.
+    drawn_top: usize,
+    needs_redraw: bool,
+}
+
+impl Console {
+    pub const fn new(default_color: ConsoleColor) -> Self {
+        Self {
+            lines: [Line::EMPTY; CAPACITY],
+            total_lines: 1,
+            default_color,
+            color: default_color,
+            escape: EscapeState::None,
+            scroll_offset: 0,
+            columns: MAX_COLUMNS,
+            drawn: (0, 0),
+            drawn_top: 0,
+            needs_redraw: true,
+        }
+    }
+
+    fn line(&self, line: usize) -> &Line {
+        &self.lines[line % CAPACITY]
+    }
+
+    fn current_line(&mut self) -> &mut Line {
+        &mut self.lines[(self.total_lines - 1) % CAPACITY]
+    }
+
+    fn oldest_line(&self) -> usize {
+        self.total_lines.saturating_sub(CAPACITY)
+    }
+
+    fn new_line(&mut self) {
+        self.total_lines += 1;
+        *self.current_line() = Line::EMPTY;
+
+        // Keep a scrolled back view on the same lines while new output arrives.
+        if self.scroll_offset > 0 {
+            self.scroll_offset =
+                (self.scroll_offset + 1).min(self.total_lines - 1 - self.oldest_line());
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        if self.current_line().len as usize >= self.columns {
+            self.new_line();
+        }
+
+        let color = self.color;
+        let line = self.current_line();
+        line.cells[line.len as usize] = Cell {
+            c: if c.is_ascii() && !c.is_ascii_control() {
+                c as u8
+            } else {
+                b'?'
+            },
+            color,
+        };
+        line.len += 1;
+    }
+
+    fn apply_sgr(&mut self, parameter: u16) {
+        match parameter {
+            0 | 39 => self.color = self.default_color,
+            _ => {
+                if let Some(color) = ConsoleColor::from_sgr(parameter) {
+                    self.color = color;
+                }
+            }
+        }
+    }
+
+    pub fn write(&mut self, s: &str) {
+        for c in s.chars() {
+            self.escape = match (self.escape, c) {
+                (EscapeState::None, ESCAPE) => EscapeState::Escape,
+                (EscapeState::None, '\n') => {
+                    self.new_line();
+                    EscapeState::None
+                }
+                (EscapeState::None, '\r') => EscapeState::None,
+                (EscapeState::None, c) => {
+                    self.put(c);
+                    EscapeState::None
+                }
+                (EscapeState::Escape, '[') => EscapeState::Csi(0),
+                (EscapeState::Escape, _) => EscapeState::None,
+                (EscapeState::Csi(parameter), '0'..='9') => EscapeState::Csi(
+                    parameter
+                        .saturating_mul(10)
+                        .saturating_add(c as u16 - '0' as u16),
+                ),
+                (EscapeState::Csi(parameter), ';') => {
+                    self.apply_sgr(parameter);
+                    EscapeState::Csi(0)
+                }
+                (EscapeState::Csi(parameter), 'm') => {
+                    self.apply_sgr(parameter);
+                    EscapeState::None
+                }
+                (EscapeState::Csi(2), 'J') => {
+                    self.clear();
+                    EscapeState::None
+                }
+                (EscapeState::Csi(_), _) => EscapeState::None,
+            };
+        }
+    }
+
+    /// Removes all text, including scrollback.
+    pub fn clear(&mut self) {
+        self.total_lines = 1;
+        *self.current_line() = Line::EMPTY;
+        self.scroll_offset = 0;
+        self.needs_redraw = true;
+    }
+
+    /// Scrolls the view `lines` lines back into history.
+    pub fn scroll_up(&mut self, lines: usize) {
+        let max = self.total_lines - 1 - self.oldest_line();
+        self.scroll_offset = (self.scroll_offset + lines).min(max);
+        self.needs_redraw = true;
+    }
+
+    /// Scrolls the view `lines` lines towards the newest output.
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll_offset = self.scroll_offset.saturating_sub(lines);
+        self.needs_redraw = true;
+    }
+
+    /// Makes the next [`Console::render`] draw everything, for when something else drew over it.
+    pub fn invalidate(&mut self) {
+        self.needs_redraw = true;
+    }
+
+    pub fn is_scrolled_back(&self) -> bool {
+        self.scroll_offset > 0
+    }
+
+    /// Draws the console into `area` of `target`, redrawing as little as possible.
+    pub fn render<D: DrawTarget<Color = Rgb888>>(&mut self, target: &mut D, area: Rectangle) {
+        let char_size = FONT.character_size;
+        let rows = (area.size.height / char_size.height).max(1) as usize;
+        let columns = ((area.size.width / char_size.width) as usize).clamp(1, MAX_COLUMNS);
+
+        if columns != self.columns {
+            self.columns = columns;
+            self.needs_redraw = true;
+        }
+
+        let bottom = self.total_lines - 1 - self.scroll_offset;
+        let top = (bottom + 1).saturating_sub(rows).max(self.oldest_line());
+
+        // If the view moved, every row shifted.
+        if top != self.drawn_top {
+            self.needs_redraw = true;
+        }
+
+        let (start_line, start_column) = if self.needs_redraw {
+            let _ = target.fill_solid(&area, COLOR_SCHEME.background());
+            (top, 0)
+        } else {
+            self.drawn
+        };
+
+        for line_index in start_line..=bottom {
+            let line = self.line(line_index);
+            let row = (line_index - top) as i32;
+            let first = if line_index == start_line {
+                start_column
+            } else {
+                0
+            };
+
+            for (column, cell) in line.cells[..line.len as usize]
+                .iter()
+                .enumerate()
+                .skip(first)
+            {
+                let position = area.top_left
+                    + Point::new(
+                        column as i32 * char_size.width as i32,
+                        row * char_size.height as i32,
+                    );
+
+                let style = MonoTextStyleBuilder::new()
+                    .font(FONT)
+                    .text_color(cell.color.to_rgb())
+                    .background_color(COLOR_SCHEME.background())
+                    .build();
+
+                let mut tmp = [0; 4];
+                let _ = style.draw_string(
+                    (cell.c as char).encode_utf8(&mut tmp),
+                    position,
+                    Baseline::Top,
+                    target,
+                );
+            }
+        }
+
+        self.drawn = (bottom, self.line(bottom).len as usize);
+        self.drawn_top = top;
+        self.needs_redraw = false;
+    }
+}
@@ -0,0 +1,230 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Full screen report drawn when the kernel panics. Everything here runs inside the panic handler,
+//! so it must not allocate or take any lock that might already be held.
+
+use core::{
+    fmt::{self, Write},
+    panic::PanicInfo,
+};
+
+use embedded_graphics::{
+    mono_font::{
+        MonoTextStyle,
+        ascii::{FONT_8X13, FONT_9X18_BOLD},
+    },
+    pixelcolor::Rgb888,
+    prelude::*,
+    text::{Baseline, renderer::TextRenderer},
+};
+
+use crate::{
+    color::{COLOR_SCHEME, ColorScheme},
+    debug::{backtrace::Backtrace, rendering::FramebufferWriter},
+    serial_println,
+    trampoline::interrupts::{ControlRegisters, ExceptionInfo},
+    util::InfallibleResultExt,
+};
+
+const MARGIN: i32 = 20;
+const REPORT_LINES: i32 = 6;
+
+/// A block of text on screen that wraps at its width and stops drawing at its bottom edge.
+struct Column<'a> {
+    target: &'a mut FramebufferWriter,
+    style: MonoTextStyle<'static, Rgb888>,
+    left: i32,
+    right: i32,
+    bottom: i32,
+    position: Point,
+}
+
+impl<'a> Column<'a> {
+    fn new(
+        target: &'a mut FramebufferWriter,
+        style: MonoTextStyle<'static, Rgb888>,
+        top_left: Point,
+        right: i32,
+        bottom: i32,
+    ) -> Self {
+        Self {
+            target,
+            style,
+            left: top_left.x,
+            right,
+            bottom,
+            position: top_left + Point::new(0, style.line_height() as i32),
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.position = Point::new(self.left, self.position.y + self.style.line_height() as i32);
+    }
+
+    fn set_color(&mut self, color: Rgb888) {
+        self.style.text_color = Some(color);
+    }
+
+    fn gap(&mut self) {
+        self.position.y += self.style.line_height() as i32 / 2;
+    }
+}
+
+impl Write for Column<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '\n' {
+                self.new_line();
+                continue;
+            }
+
+            if self.position.x + self.style.font.character_size.width as i32 > self.right {
+                self.new_line();
+            }
+
+            if self.position.y > self.bottom {
+                return Ok(());
+            }
+
+            let mut tmp = [0; 4];
+            self.position = self
+                .style
+                .draw_string(
+                    c.encode_utf8(&mut tmp),
+                    self.position,
+                    Baseline::Bottom,
+                    &mut *self.target,
+                )
+                .infallible();
+        }
+
+        Ok(())
+    }
+}
+
+/// One line summary that users can paste into a bug report.
+struct Summary<'a> {
+    info: &'a PanicInfo<'a>,
+    exception: Option<&'a ExceptionInfo>,
+    backtrace: &'a Backtrace,
+}
+
+impl fmt::Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} panic",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        )?;
+        if let Some(location) = self.info.location() {
+            write!(f, " at {location}")?;
+        }
+        write!(f, ": {}", self.info.message())?;
+        if let Some(exception) = self.exception {
+            write!(
+                f,
+                " | {} RIP={:#x}",
+                exception.name,
+                exception.frame.instruction_pointer.as_u64()
+            )?;
+        }
+        f.write_str(" | bt")?;
+        for address in self.backtrace.frames() {
+            write!(f, " {address:#x}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Clears `target` and draws everything we know about the panic onto it. The report summary is
+/// also written to serial.
+pub fn show(
+    target: &mut FramebufferWriter,
+    info: &PanicInfo,
+    exception: Option<&ExceptionInfo>,
+    backtrace: &Backtrace,
+) {
+    let summary = Summary {
+        info,
+        exception,
+        backtrace,
+    };
+    serial_println!("Please report this crash, including the following line:");
+    serial_println!("{summary}");
+
+    target.clear(COLOR_SCHEME.crash_background()).infallible();
+
+    let size = target.size();
+    let width = size.width as i32;
+    let height = size.height as i32;
+
+    let title_style = MonoTextStyle::new(&FONT_9X18_BOLD, COLOR_SCHEME.error_foreground());
+    let body_style = MonoTextStyle::new(&FONT_8X13, COLOR_SCHEME.foreground());
+    let body_line_height = body_style.line_height() as i32;
+
+    let report_top = height - MARGIN - REPORT_LINES * body_line_height;
+    let split = MARGIN + (width - 2 * MARGIN) * 3 / 5;
+
+    let mut title = Column::new(
+        target,
+        title_style,
+        Point::new(MARGIN, MARGIN),
+        width - MARGIN,
+        report_top,
+    );
+    let _ = write!(title, "The kernel crashed");
+    let columns_top = title.position.y + body_line_height;
+
+    // Left column: what happened and the machine state.
+    let mut left = Column::new(
+        target,
+        body_style,
+        Point::new(MARGIN, columns_top),
+        split - MARGIN,
+        report_top - body_line_height,
+    );
+    let _ = write!(left, "{}", info.message());
+    left.new_line();
+    left.set_color(COLOR_SCHEME.subtle_foreground());
+    if let Some(location) = info.location() {
+        let _ = write!(left, "at {location}");
+    }
+    left.new_line();
+    left.gap();
+    left.set_color(COLOR_SCHEME.foreground());
+    if let Some(exception) = exception {
+        let _ = writeln!(left, "{exception}");
+        left.gap();
+    }
+    let _ = write!(left, "{ControlRegisters}");
+
+    // Right column: where it happened.
+    let mut right = Column::new(
+        target,
+        body_style,
+        Point::new(split, columns_top),
+        width - MARGIN,
+        report_top - body_line_height,
+    );
+    let _ = write!(right, "{backtrace}");
+
+    // Bottom: plain text summary for bug reports.
+    let mut report = Column::new(
+        target,
+        body_style,
+        Point::new(MARGIN, report_top),
+        width - MARGIN,
+        height - MARGIN,
+    );
+    report.set_color(COLOR_SCHEME.subtle_foreground());
+    let _ = writeln!(
+        report,
+        "Please report this crash, including the following text (also sent to serial):"
+    );
+    report.set_color(COLOR_SCHEME.foreground());
+    let _ = write!(report, "{summary}");
+}
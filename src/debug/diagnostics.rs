@@ -0,0 +1,266 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Dumps of the memory layout and firmware tables over serial, for when things go wrong on real
+//! hardware.
+
+use limine::memory_map::EntryType;
+use x86_64::{
+    PhysAddr, VirtAddr,
+    structures::paging::{PageSize, PageTableFlags, Size4KiB},
+};
+
+use crate::{
+    serial_println,
+    trampoline::{
+        acpi::{self, signature_str},
+        memory::{
+            self, HHDM_OFFSET,
+            allocators::ProperFrameAllocator,
+            get_pagetable,
+            walk::{self, Mapping},
+        },
+    },
+};
+
+fn entry_type_name(entry_type: EntryType) -> &'static str {
+    match entry_type {
+        EntryType::USABLE => "usable",
+        EntryType::RESERVED => "reserved",
+        EntryType::ACPI_RECLAIMABLE => "ACPI reclaimable",
+        EntryType::ACPI_NVS => "ACPI NVS",
+        EntryType::BAD_MEMORY => "bad memory",
+        EntryType::BOOTLOADER_RECLAIMABLE => "bootloader reclaimable",
+        EntryType::EXECUTABLE_AND_MODULES => "executable and modules",
+        EntryType::FRAMEBUFFER => "framebuffer",
+        _ => "unknown",
+    }
+}
+
+/// Prints the memory map exactly as Limine handed it to us.
+pub fn dump_memory_map() {
+    let Some(entries) = memory::memory_map() else {
+        serial_println!("No memory map was provided by Limine.");
+        return;
+    };
+
+    serial_println!("Limine memory map:");
+    for entry in entries {
+        serial_println!(
+            "  {:#018x}-{:#018x} {:>10} KiB  {}",
+            entry.base,
+            entry.base + entry.length,
+            entry.length / 1024,
+            entry_type_name(entry.entry_type)
+        );
+    }
+}
+
+/// Prints how much of the kernel heap is in use.
+pub fn dump_heap() {
+    let (used, size) = memory::heap_usage();
+
+    serial_println!(
+        "Kernel heap: {} of {} KiB used ({}%), {} KiB free",
+        used / 1024,
+        size / 1024,
+        used * 100 / size.max(1),
+        (size - used) / 1024
+    );
+}
+
+/// Prints the physical ranges the frame allocator can still hand out.
+pub fn dump_free_frames(frame_allocator: &ProperFrameAllocator) {
+    let mut total = 0;
+
+    serial_println!("Free physical memory:");
+    for range in frame_allocator.free_ranges() {
+        if range.is_empty() {
+            continue;
+        }
+
+        let size = (range.end - range.start) * Size4KiB::SIZE;
+        total += size;
+        serial_println!(
+            "  {:#018x}-{:#018x} {:>10} KiB",
+            range.start.start_address().as_u64(),
+            range.end.start_address().as_u64(),
+            size / 1024
+        );
+    }
+    serial_println!("  total {} KiB", total / 1024);
+}
+
+/// A run of consecutive mappings with the same flags that are also physically contiguous.
+struct MappedRange {
+    virt: VirtAddr,
+    phys: PhysAddr,
+    size: u64,
+    flags: PageTableFlags,
+}
+
+impl MappedRange {
+    fn try_extend(&mut self, mapping: &Mapping) -> bool {
+        if self.virt + self.size == mapping.virt
+            && self.phys + self.size == mapping.phys
+            && self.flags == mapping.flags
+        {
+            self.size += mapping.size;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn print(&self) {
+        let flag = |flag, c| if self.flags.contains(flag) { c } else { '-' };
+
+        serial_println!(
+            "  {:#018x}-{:#018x} -> {:#014x} {:>10} KiB  r{}{}{}{}",
+            self.virt.as_u64(),
+            (self.virt + self.size).as_u64(),
+            self.phys.as_u64(),
+            self.size / 1024,
+            flag(PageTableFlags::WRITABLE, 'w'),
+            if self.flags.contains(PageTableFlags::NO_EXECUTE) {
+                '-'
+            } else {
+                'x'
+            },
+            flag(PageTableFlags::USER_ACCESSIBLE, 'u'),
+            flag(PageTableFlags::GLOBAL, 'g'),
+        );
+    }
+}
+
+/// Walks the active page tables and prints every mapping, merging runs that are contiguous in both
+/// virtual and physical memory and share the same flags.
+pub fn dump_page_tables() {
+    let mut current: Option<MappedRange> = None;
+
+    serial_println!("Active page tables:");
+
+    // SAFETY: This is only called once the kernel runs on its own page tables, so our HHDM is
+    // active and covers every table.
+    unsafe {
+        walk::for_each_mapping(get_pagetable(), HHDM_OFFSET, 0..512, |mapping| {
+            if let Some(range) = &mut current
+                && range.try_extend(&mapping)
+            {
+                return;
+            }
+
+            if let Some(range) = current.take() {
+                range.print();
+            }
+
+            current = Some(MappedRange {
+                virt: mapping.virt,
+                phys: mapping.phys,
+                size: mapping.size,
+                flags: mapping.flags,
+            });
+        });
+    }
+
+    if let Some(range) = current {
+        range.print();
+    }
+}
+
+/// Prints the ACPI tables found at boot and what was parsed from them.
+pub fn dump_acpi() {
+    let Some(acpi) = acpi::get() else {
+        serial_println!("No ACPI tables were found.");
+        return;
+    };
+
+    serial_println!(
+        "ACPI revision {} from {}:",
+        acpi.revision,
+        core::str::from_utf8(&acpi.oem_id).unwrap_or("?")
+    );
+    for table in &acpi.tables {
+        serial_println!(
+            "  {} {:#018x} {:>6} bytes  rev {}  {}",
+            signature_str(&table.signature),
+            table.address.as_u64(),
+            table.length,
+            table.revision,
+            core::str::from_utf8(&table.oem_id).unwrap_or("?")
+        );
+    }
+
+    if let Some(madt) = &acpi.madt {
+        serial_println!(
+            "MADT: local APIC at {:#x}, 8259 PICs: {}",
+            madt.local_apic_address.as_u64(),
+            madt.has_8259
+        );
+        for cpu in &madt.cpus {
+            serial_println!(
+                "  CPU {} APIC ID {} enabled: {} online capable: {}",
+                cpu.processor_uid,
+                cpu.apic_id,
+                cpu.enabled,
+                cpu.online_capable
+            );
+        }
+        for io_apic in &madt.io_apics {
+            serial_println!(
+                "  IO APIC {} at {:#x}, GSI base {}",
+                io_apic.id,
+                io_apic.address.as_u64(),
+                io_apic.gsi_base
+            );
+        }
+        for o in &madt.overrides {
+            serial_println!(
+                "  IRQ {} -> GSI {} ({:?}, {:?})",
+                o.irq,
+                o.gsi,
+                o.polarity,
+                o.trigger
+            );
+        }
+        for nmi in &madt.nmis {
+            serial_println!(
+                "  NMI on LINT{} of processor {:?}",
+                nmi.lint,
+                nmi.processor_uid
+            );
+        }
+    }
+
+    if let Some(fadt) = &acpi.fadt {
+        serial_println!(
+            "FADT: SCI {}, DSDT at {:#x}, century register {:?}, 8042: {}, hardware reduced: {}",
+            fadt.sci_interrupt,
+            fadt.dsdt.as_u64(),
+            fadt.century_register,
+            fadt.has_8042,
+            fadt.hardware_reduced
+        );
+        serial_println!("  PM1a control: {:?}", fadt.pm1a_control_block);
+        serial_println!("  PM1b control: {:?}", fadt.pm1b_control_block);
+        serial_println!("  PM timer: {:?}", fadt.pm_timer_block);
+        serial_println!(
+            "  Reset: {:?} <- {:#x}",
+            fadt.reset_register,
+            fadt.reset_value
+        );
+        serial_println!("  S5 sleep type: {:?}", acpi.s5);
+    }
+
+    if let Some(hpet) = &acpi.hpet {
+        serial_println!(
+            "HPET {} at {:#x}: {} comparators, 64 bit: {}, minimum tick {}",
+            hpet.hpet_number,
+            hpet.address.as_u64(),
+            hpet.comparators,
+            hpet.counter_64bit,
+            hpet.minimum_tick
+        );
+    }
+}
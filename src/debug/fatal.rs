@@ -0,0 +1,57 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use embedded_graphics::prelude::DrawTarget;
+
+use crate::{
+    color::{COLOR_SCHEME, ColorScheme},
+    debug::{diagnostics, rendering::DEBUG_FRAMEBUFFER, text::DEBUG_WRITER},
+    hcf,
+    trampoline::memory::{MemoryStats, error::MemoryError},
+    util::InfallibleResultExt,
+};
+
+const MIB: u64 = 1024 * 1024;
+
+/// Writes a line to both the screen and the serial port.
+macro_rules! report {
+    () => {{
+        $crate::eprint!("\n");
+        $crate::serial_println!();
+    }};
+    ($($arg:tt)*) => {{
+        $crate::eprintln!($($arg)*);
+        $crate::serial_println!($($arg)*);
+    }};
+}
+
+/// Clears the screen and reports a memory setup failure that happened during boot, then halts.
+pub fn boot_failure(error: &MemoryError) -> ! {
+    let stats = MemoryStats::from_memmap();
+
+    if let Some(framebuffer) = DEBUG_FRAMEBUFFER.lock().as_mut() {
+        framebuffer.clear(COLOR_SCHEME.background()).infallible();
+    }
+    DEBUG_WRITER.lock().clear();
+
+    report!("FATAL BOOT ERROR");
+    report!();
+    report!("Region:      {}", error.region());
+    report!("Cause:       {error}");
+    report!(
+        "Memory:      {} MiB usable, {} MiB reclaimable ({} memory map entries)",
+        stats.usable / MIB,
+        stats.reclaimable / MIB,
+        stats.entries
+    );
+
+    if error.is_out_of_memory() {
+        report!();
+        report!("The machine does not have enough memory to run the kernel.");
+    }
+
+    diagnostics::dump_memory_map();
+
+    hcf();
+}
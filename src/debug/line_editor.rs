@@ -0,0 +1,189 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! A minimal line editor for VT100 style terminals, fed one byte at a time.
+//!
+//! Supports inserting and deleting at the cursor, moving with the arrow keys, Home/End, Ctrl-A,
+//! Ctrl-E, Ctrl-U to clear the line, Ctrl-C to abandon it and scrolling through history with the
+//! up and down arrows. Page Up and Page Down are left to the caller.
+
+use alloc::{collections::VecDeque, string::String};
+use core::fmt::Write;
+
+const HISTORY_SIZE: usize = 16;
+
+const CTRL_A: u8 = 0x01;
+const CTRL_C: u8 = 0x03;
+const CTRL_E: u8 = 0x05;
+const BACKSPACE: u8 = 0x08;
+const CTRL_U: u8 = 0x15;
+const ESCAPE: u8 = 0x1b;
+const DELETE: u8 = 0x7f;
+
+#[derive(Clone, Copy)]
+enum EscapeState {
+    None,
+    Escape,
+    /// Inside `ESC[`, with the numeric parameter collected so far.
+    Csi(u8),
+}
+
+/// What a byte fed to the [`LineEditor`] completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    /// Enter was pressed.
+    Line(String),
+    PageUp,
+    PageDown,
+}
+
+pub struct LineEditor {
+    prompt: &'static str,
+    line: String,
+    /// Byte offset of the cursor in `line`. Only ASCII is accepted, so bytes are characters.
+    cursor: usize,
+    history: VecDeque<String>,
+    /// Which history entry is shown, counting back from the newest.
+    history_index: Option<usize>,
+    escape: EscapeState,
+}
+
+impl LineEditor {
+    pub fn new(prompt: &'static str) -> Self {
+        Self {
+            prompt,
+            line: String::new(),
+            cursor: 0,
+            history: VecDeque::new(),
+            history_index: None,
+            escape: EscapeState::None,
+        }
+    }
+
+    /// Prints the prompt with whatever has been typed so far.
+    pub fn redraw(&self, out: &mut impl Write) {
+        let _ = write!(out, "\r\x1b[K{}{}", self.prompt, self.line);
+
+        let back = self.line.len() - self.cursor;
+        if back > 0 {
+            let _ = write!(out, "\x1b[{back}D");
+        }
+    }
+
+    fn set_line(&mut self, line: String, out: &mut impl Write) {
+        self.line = line;
+        self.cursor = self.line.len();
+        self.redraw(out);
+    }
+
+    fn move_cursor(&mut self, to: usize, out: &mut impl Write) {
+        let _ = match to.cmp(&self.cursor) {
+            core::cmp::Ordering::Less => write!(out, "\x1b[{}D", self.cursor - to),
+            core::cmp::Ordering::Greater => write!(out, "\x1b[{}C", to - self.cursor),
+            core::cmp::Ordering::Equal => Ok(()),
+        };
+        self.cursor = to;
+    }
+
+    fn recall(&mut self, index: Option<usize>, out: &mut impl Write) {
+        self.history_index = index;
+        let line = match index {
+            Some(index) => self.history[self.history.len() - 1 - index].clone(),
+            None => String::new(),
+        };
+        self.set_line(line, out);
+    }
+
+    fn finish(&mut self, out: &mut impl Write) -> String {
+        let _ = out.write_str("\r\n");
+
+        let line = core::mem::take(&mut self.line);
+        self.cursor = 0;
+        self.history_index = None;
+
+        if !line.trim().is_empty() && self.history.back() != Some(&line) {
+            if self.history.len() == HISTORY_SIZE {
+                self.history.pop_front();
+            }
+            self.history.push_back(line.clone());
+        }
+
+        line
+    }
+
+    fn handle_csi(&mut self, parameter: u8, command: u8, out: &mut impl Write) -> Option<Input> {
+        match (parameter, command) {
+            (_, b'A') => {
+                let next = self.history_index.map_or(0, |index| index + 1);
+                if next < self.history.len() {
+                    self.recall(Some(next), out);
+                }
+            }
+            (_, b'B') => {
+                if let Some(index) = self.history_index {
+                    self.recall(index.checked_sub(1), out);
+                }
+            }
+            (_, b'C') => self.move_cursor((self.cursor + 1).min(self.line.len()), out),
+            (_, b'D') => self.move_cursor(self.cursor.saturating_sub(1), out),
+            (_, b'H') | (1, b'~') => self.move_cursor(0, out),
+            (_, b'F') | (4, b'~') => self.move_cursor(self.line.len(), out),
+            (3, b'~') if self.cursor < self.line.len() => {
+                self.line.remove(self.cursor);
+                self.redraw(out);
+            }
+            (5, b'~') => return Some(Input::PageUp),
+            (6, b'~') => return Some(Input::PageDown),
+            _ => {}
+        }
+
+        None
+    }
+
+    /// Handles one received byte, echoing to `out`. Returns the line once Enter is pressed, and
+    /// Page Up and Page Down as they come.
+    pub fn feed(&mut self, byte: u8, out: &mut impl Write) -> Option<Input> {
+        match (self.escape, byte) {
+            (EscapeState::None, ESCAPE) => self.escape = EscapeState::Escape,
+            (EscapeState::Escape, b'[') => self.escape = EscapeState::Csi(0),
+            (EscapeState::Csi(parameter), b'0'..=b'9') => {
+                self.escape =
+                    EscapeState::Csi(parameter.saturating_mul(10).saturating_add(byte - b'0'));
+            }
+            (EscapeState::Csi(parameter), command) => {
+                self.escape = EscapeState::None;
+                return self.handle_csi(parameter, command, out);
+            }
+            (EscapeState::Escape, _) => self.escape = EscapeState::None,
+            (EscapeState::None, b'\r' | b'\n') => return Some(Input::Line(self.finish(out))),
+            (EscapeState::None, BACKSPACE | DELETE) => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    self.line.remove(self.cursor);
+                    self.redraw(out);
+                }
+            }
+            (EscapeState::None, CTRL_A) => self.move_cursor(0, out),
+            (EscapeState::None, CTRL_E) => self.move_cursor(self.line.len(), out),
+            (EscapeState::None, CTRL_U) => self.set_line(String::new(), out),
+            (EscapeState::None, CTRL_C) => {
+                let _ = out.write_str("^C\r\n");
+                self.history_index = None;
+                self.set_line(String::new(), out);
+            }
+            (EscapeState::None, byte) if byte.is_ascii_graphic() || byte == b' ' => {
+                self.line.insert(self.cursor, byte as char);
+                self.cursor += 1;
+                if self.cursor == self.line.len() {
+                    let _ = out.write_char(byte as char);
+                } else {
+                    self.redraw(out);
+                }
+            }
+            (EscapeState::None, _) => {}
+        }
+
+        None
+    }
+}
@@ -0,0 +1,258 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Backend for the [`log`] facade.
+//!
+//! Every record is written to the enabled [`Sinks`]: COM1, the on-screen debug console and an
+//! in-memory ring buffer that can be read back later. Which records get through is decided by a
+//! filter in the same format as `env_logger`: a comma separated list of `level` or
+//! `module::path=level` directives, where the longest matching module prefix wins.
+
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::{self, Write},
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+    time::Duration,
+};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use spin::{Mutex, Once};
+
+use crate::{
+    debug::{panic::is_panicking, text::DEBUG_WRITER},
+    serial_print,
+    trampoline::clock,
+};
+
+pub const DEFAULT_FILTER: &str = "info";
+
+const RING_SIZE: usize = 16 * 1024;
+
+/// Where log records are written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sinks(u8);
+
+impl Sinks {
+    pub const SERIAL: Self = Self(1 << 0);
+    pub const CONSOLE: Self = Self(1 << 1);
+    pub const MEMORY: Self = Self(1 << 2);
+    pub const ALL: Self = Self(Self::SERIAL.0 | Self::CONSOLE.0 | Self::MEMORY.0);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for Sinks {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+static SINKS: AtomicU8 = AtomicU8::new(Sinks::ALL.0);
+static FILTER: Once<Filter> = Once::new();
+static RING: Mutex<LogRing> = Mutex::new(LogRing::new());
+static LOGGER: KernelLogger = KernelLogger;
+
+struct Directive {
+    target: String,
+    level: LevelFilter,
+}
+
+/// Parsed filter directives.
+pub struct Filter {
+    default: LevelFilter,
+    directives: Vec<Directive>,
+}
+
+impl Filter {
+    /// Parses a filter such as `warn,wordle_kernel::trampoline=debug`. Invalid directives are
+    /// skipped.
+    pub fn parse(spec: &str) -> Self {
+        let mut filter = Self {
+            default: LevelFilter::Info,
+            directives: Vec::new(),
+        };
+
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    if let Ok(level) = LevelFilter::from_str(level) {
+                        filter.directives.push(Directive {
+                            target: String::from(target),
+                            level,
+                        });
+                    }
+                }
+                None => {
+                    if let Ok(level) = LevelFilter::from_str(directive) {
+                        filter.default = level;
+                    }
+                }
+            }
+        }
+
+        filter
+    }
+
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .filter(|directive| {
+                target
+                    .strip_prefix(directive.target.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|directive| directive.target.len())
+            .map_or(self.default, |directive| directive.level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|directive| directive.level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+/// Fixed size buffer that keeps the most recent log output, dropping the oldest bytes.
+pub struct LogRing {
+    buffer: [u8; RING_SIZE],
+    /// Index the next byte is written to.
+    head: usize,
+    len: usize,
+}
+
+impl LogRing {
+    const fn new() -> Self {
+        Self {
+            buffer: [0; RING_SIZE],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Calls `f` with the buffered output, oldest first, in at most two chunks.
+    pub fn read(&self, mut f: impl FnMut(&[u8])) {
+        let start = (self.head + RING_SIZE - self.len) % RING_SIZE;
+        if start + self.len <= RING_SIZE {
+            f(&self.buffer[start..start + self.len]);
+        } else {
+            f(&self.buffer[start..]);
+            f(&self.buffer[..self.head]);
+        }
+    }
+}
+
+impl Write for LogRing {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for &byte in s.as_bytes() {
+            self.buffer[self.head] = byte;
+            self.head = (self.head + 1) % RING_SIZE;
+            self.len = (self.len + 1).min(RING_SIZE);
+        }
+
+        Ok(())
+    }
+}
+
+/// Gives access to the in-memory log.
+pub fn with_ring<R>(f: impl FnOnce(&LogRing) -> R) -> R {
+    f(&RING.lock())
+}
+
+fn level_color(level: Level) -> u8 {
+    match level {
+        Level::Error => 31,
+        Level::Warn => 33,
+        Level::Info => 32,
+        Level::Debug => 34,
+        Level::Trace => 35,
+    }
+}
+
+/// Formats a record the same way for every sink.
+struct Line<'a> {
+    record: &'a Record<'a>,
+    /// When the record was logged, taken once so that every sink shows the same time.
+    time: Duration,
+    color: bool,
+}
+
+impl fmt::Display for Line<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = self.record.level();
+
+        let time = self.time;
+        write!(f, "[{:>5}.{:06}] ", time.as_secs(), time.subsec_micros())?;
+        if self.color {
+            write!(f, "\x1b[{}m{level:<5}\x1b[0m", level_color(level))?;
+        } else {
+            write!(f, "{level:<5}")?;
+        }
+        writeln!(f, " {}: {}", self.record.target(), self.record.args())
+    }
+}
+
+struct KernelLogger;
+
+impl Log for KernelLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let level = FILTER.get().map_or(LevelFilter::Info, |filter| {
+            filter.level_for(metadata.target())
+        });
+
+        metadata.level() <= level
+    }
+
+    fn log(&self, record: &Record) {
+        // The panic handler has taken over the output locks.
+        if !self.enabled(record.metadata()) || is_panicking() {
+            return;
+        }
+
+        let sinks = Sinks(SINKS.load(Ordering::Relaxed));
+        let line = Line {
+            record,
+            time: clock::since_boot(),
+            color: true,
+        };
+
+        if sinks.contains(Sinks::SERIAL) {
+            serial_print!("{line}");
+        }
+
+        if sinks.contains(Sinks::CONSOLE) {
+            let _ = write!(DEBUG_WRITER.lock(), "{line}");
+        }
+
+        if sinks.contains(Sinks::MEMORY) {
+            let _ = write!(
+                RING.lock(),
+                "{}",
+                Line {
+                    color: false,
+                    ..line
+                }
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the kernel logger with the given filter. Must be called after the heap is set up.
+pub fn init(filter: &str) {
+    let filter = FILTER.call_once(|| Filter::parse(filter));
+
+    log::set_logger(&LOGGER).expect("Logger should only be initialized once.");
+    log::set_max_level(filter.max_level());
+}
+
+pub fn set_sinks(sinks: Sinks) {
+    SINKS.store(sinks.0, Ordering::Relaxed);
+}
@@ -2,6 +2,16 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod backtrace;
+pub mod console;
+pub mod crash_screen;
+pub mod diagnostics;
+pub mod fatal;
+pub mod line_editor;
+pub mod logger;
+pub mod panic;
 pub mod rendering;
 pub mod serial;
+pub mod shell;
+pub mod symbols;
 pub mod text;
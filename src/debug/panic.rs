@@ -0,0 +1,145 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Output path used by the panic handler. A panic can happen while `DEBUG_FRAMEBUFFER` or
+//! `SERIAL` is locked (for example inside `DebugWriter::write`), so the normal macros would spin
+//! forever. The first CPU to panic stops the others with an NMI, after which nothing else will
+//! ever run again, so it is fine to forcibly take over those locks.
+
+use core::{
+    fmt::{self, Write},
+    panic::PanicInfo,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use uart_16550::SerialPort;
+
+use crate::{
+    debug::{backtrace::Backtrace, crash_screen, rendering::DEBUG_FRAMEBUFFER, serial},
+    hcf, serial_println,
+    trampoline::{
+        apic,
+        interrupts::{self, ControlRegisters},
+        smp,
+    },
+};
+
+/// How often to check whether the other CPUs have stopped before reporting anyway.
+const PARK_ATTEMPTS: usize = 10_000_000;
+
+/// Set by the CPU that reports its panic. Only one ever does.
+static PANICKING: AtomicBool = AtomicBool::new(false);
+/// How many CPUs have stopped for good.
+static PARKED: AtomicUsize = AtomicUsize::new(0);
+/// How deep each CPU is in panics, indexed by [`smp::current_index`].
+static PANIC_DEPTH: [AtomicUsize; smp::MAX_CPUS] = [const { AtomicUsize::new(0) }; smp::MAX_CPUS];
+
+/// Whether any CPU is panicking.
+pub fn is_panicking() -> bool {
+    PANICKING.load(Ordering::SeqCst)
+}
+
+/// Stops the current CPU for good, letting the panicking CPU know.
+fn park() -> ! {
+    PARKED.fetch_add(1, Ordering::SeqCst);
+    hcf();
+}
+
+/// Called on every NMI. Another CPU's panic sends one to stop this CPU, which then never returns.
+/// Returns whether the NMI was the panicking CPU's own, which it ignores.
+pub fn handle_nmi() -> bool {
+    if !is_panicking() {
+        return false;
+    }
+
+    if PANIC_DEPTH[smp::current_index()].load(Ordering::SeqCst) == 0 {
+        park();
+    }
+    true
+}
+
+/// Sends the other CPUs an NMI and waits a while for them to stop.
+fn stop_other_cpus() {
+    let others = smp::cpus()
+        .iter()
+        .filter(|cpu| cpu.is_online())
+        .count()
+        .saturating_sub(1);
+    if others == 0 || !apic::is_available() {
+        return;
+    }
+
+    apic::send_nmi_to_others();
+    for _ in 0..PARK_ATTEMPTS {
+        if PARKED.load(Ordering::SeqCst) >= others {
+            break;
+        }
+        core::hint::spin_loop();
+    }
+}
+
+/// Writes straight to COM1 without going through any lock.
+struct RawSerial(SerialPort);
+
+impl RawSerial {
+    fn new() -> Self {
+        // SAFETY: 0x3F8 is COM1, which was initialized when SERIAL was first used. If it wasn't,
+        // writing to it is still harmless.
+        Self(unsafe { SerialPort::new(0x3F8) })
+    }
+}
+
+impl Write for RawSerial {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_str(s)
+    }
+}
+
+pub fn handle_panic(info: &PanicInfo) -> ! {
+    x86_64::instructions::interrupts::disable();
+
+    let backtrace = Backtrace::capture();
+    let exception = interrupts::current_exception();
+
+    match PANIC_DEPTH[smp::current_index()].fetch_add(1, Ordering::SeqCst) {
+        0 => {
+            // Another CPU is already reporting its panic, and stops this one too.
+            if PANICKING.swap(true, Ordering::SeqCst) {
+                park();
+            }
+            stop_other_cpus();
+
+            // SAFETY: Interrupts are disabled, we never return and the other CPUs were stopped,
+            // so whoever held these locks will never run again.
+            unsafe {
+                serial::force_unlock();
+                DEBUG_FRAMEBUFFER.force_unlock();
+            }
+
+            // Serial first since it is far less likely to fail than drawing to the screen.
+            serial_println!("{}", info);
+            if let Some(exception) = &exception {
+                serial_println!("{}", exception);
+            }
+            serial_println!("{}", ControlRegisters);
+            serial_println!("{}", backtrace);
+
+            if let Some(framebuffer) = DEBUG_FRAMEBUFFER.lock().as_mut() {
+                crash_screen::show(framebuffer, info, exception.as_ref(), &backtrace);
+            }
+        }
+        1 => {
+            // Printing the first panic is what panicked, so stay away from the screen and from
+            // every lock.
+            let _ = writeln!(
+                RawSerial::new(),
+                "PANIC WHILE PANICKING: {info}\n{backtrace}"
+            );
+        }
+        // Even raw serial output failed. There is nothing left to try.
+        _ => {}
+    }
+
+    hcf();
+}
@@ -9,66 +9,58 @@ use lazy_static::lazy_static;
 use limine::framebuffer::Framebuffer;
 use spin::Mutex;
 
-use crate::{hcf, trampoline::limine_requests::HHDM_REQUEST};
+use crate::trampoline::{
+    framebuffer::{self, PixelFormat},
+    limine_requests::HHDM_REQUEST,
+};
 
 lazy_static! {
-    pub static ref DEBUG_FRAMEBUFFER: Mutex<FramebufferWriter<'static>> = {
-        if let Some(framebuffer_response) =
-            crate::limine_requests::FRAMEBUFFER_REQUEST.get_response()
-            && let Some(framebuffer) = framebuffer_response.framebuffers().next()
-        {
-            let writer = FramebufferWriter::new(framebuffer);
-            Mutex::new(writer)
-        } else {
-            hcf();
-        }
-    };
+    /// The first framebuffer from Limine, or `None` on machines without one, in which case debug
+    /// output only goes to serial.
+    pub static ref DEBUG_FRAMEBUFFER: Mutex<Option<FramebufferWriter>> = Mutex::new(
+        crate::limine_requests::FRAMEBUFFER_REQUEST
+            .get_response()
+            .and_then(|response| response.framebuffers().next())
+            .map(|framebuffer| FramebufferWriter::new(&framebuffer))
+    );
 }
 
-pub struct FramebufferWriter<'a> {
-    pub fb: Framebuffer<'a>,
-    pub addr_override: Option<&'static mut u8>,
+/// Draws straight into a framebuffer. Everything about it is copied out of Limine's response,
+/// which lives in bootloader memory that gets reclaimed once the kernel is up.
+pub struct FramebufferWriter {
+    addr: &'static mut u8,
+    width: u64,
+    height: u64,
+    pitch: u64,
+    format: PixelFormat,
 }
 
-impl<'a> FramebufferWriter<'a> {
-    pub fn new(framebuffer: Framebuffer<'a>) -> Self {
+impl FramebufferWriter {
+    pub fn new(framebuffer: &Framebuffer) -> Self {
         Self {
-            fb: framebuffer,
-            addr_override: None,
+            // SAFETY: Limine maps the framebuffer in its HHDM, and nothing else draws to it
+            // through this address.
+            addr: unsafe { &mut *framebuffer.addr() },
+            width: framebuffer.width(),
+            height: framebuffer.height(),
+            pitch: framebuffer.pitch(),
+            format: framebuffer::pixel_format(framebuffer),
         }
     }
 
     pub fn write_pixel(&mut self, x: u64, y: u64, r: u8, g: u8, b: u8) {
-        assert!(x < self.fb.width());
-        assert!(y < self.fb.height());
-
-        let mut pixel_value = 0u32;
-
-        pixel_value |=
-            (r as u32 & ((1 << self.fb.red_mask_size()) - 1)) << self.fb.red_mask_shift();
-        pixel_value |=
-            (g as u32 & ((1 << self.fb.green_mask_size()) - 1)) << self.fb.green_mask_shift();
-        pixel_value |=
-            (b as u32 & ((1 << self.fb.blue_mask_size()) - 1)) << self.fb.blue_mask_shift();
+        assert!(x < self.width);
+        assert!(y < self.height);
 
-        let bytes_per_pixel = (self.fb.bpp() / 8) as u64;
+        let pixel_value = self.format.encode(Rgb888::new(r, g, b));
+        let bytes_per_pixel = self.format.bytes_per_pixel() as u64;
 
         // SAFETY: address is properly mapped and aligned.
         // no concurrent writes since the function takes &mut self
         unsafe {
-            let addr = if let Some(addr_override) = &mut self.addr_override {
-                core::ptr::from_mut(*addr_override)
-            } else {
-                self.fb.addr()
-            };
-
-            // self.fb
-            //     .addr()
-            //     .add((y * self.fb.pitch() + x * bytes_per_pixel) as usize)
-            //     .cast::<u32>()
-            //     .write(pixel_value);
             core::ptr::write_volatile(
-                addr.add((y * self.fb.pitch() + x * bytes_per_pixel) as usize)
+                core::ptr::from_mut(self.addr)
+                    .add((y * self.pitch + x * bytes_per_pixel) as usize)
                     .cast::<u32>(),
                 pixel_value,
             );
@@ -78,7 +70,7 @@ impl<'a> FramebufferWriter<'a> {
     /// # SAFETY
     /// The same framebuffer should be located in virtual memory at the new HHDM offset.
     pub unsafe fn override_addr(&mut self, new_hhdm: u64) {
-        let new_addr = self.fb.addr() as u64
+        let new_addr = core::ptr::from_mut(self.addr) as u64
             - HHDM_REQUEST
                 .get_response()
                 .expect("Response should be provided by Limine.")
@@ -86,17 +78,17 @@ impl<'a> FramebufferWriter<'a> {
             + new_hhdm;
 
         // SAFETY: The caller ensures that the new HHDM results in valid memory.
-        self.addr_override = unsafe { Some(&mut *(new_addr as *mut u8)) };
+        self.addr = unsafe { &mut *(new_addr as *mut u8) };
     }
 }
 
-impl OriginDimensions for FramebufferWriter<'_> {
+impl OriginDimensions for FramebufferWriter {
     fn size(&self) -> Size {
-        Size::new(self.fb.width() as u32, self.fb.height() as u32)
+        Size::new(self.width as u32, self.height as u32)
     }
 }
 
-impl DrawTarget for FramebufferWriter<'_> {
+impl DrawTarget for FramebufferWriter {
     // NOTE: Not sure if this would work on every framebuffer. I also don't know how to support
     // more than one color type.
     type Color = Rgb888;
@@ -108,8 +100,8 @@ impl DrawTarget for FramebufferWriter<'_> {
         I: IntoIterator<Item = embedded_graphics::Pixel<Self::Color>>,
     {
         for Pixel(coord, color) in pixels.into_iter() {
-            if coord.x >= self.fb.width() as i32
-                || coord.y >= self.fb.height() as i32
+            if coord.x >= self.width as i32
+                || coord.y >= self.height as i32
                 || coord.x < 0
                 || coord.y < 0
             {
@@ -2,12 +2,24 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use core::fmt;
-use core::fmt::Write;
+use core::{
+    fmt::{self, Write},
+    future,
+    task::{Context, Poll},
+};
 
 use lazy_static::lazy_static;
 use spin::Mutex;
 use uart_16550::SerialPort;
+use x86_64::instructions::interrupts;
+
+use crate::{
+    task::AtomicWaker,
+    trampoline::pic::{self, Irq},
+    util::Queue,
+};
+
+const INPUT_CAPACITY: usize = 256;
 
 lazy_static! {
     static ref SERIAL: Mutex<SerialPort> = {
@@ -20,6 +32,71 @@ lazy_static! {
     };
 }
 
+/// Bytes received over COM1 that nobody has read yet.
+static INPUT: Mutex<Queue<u8, INPUT_CAPACITY>> = Mutex::new(Queue::new());
+static INPUT_WAKER: AtomicWaker = AtomicWaker::new();
+
+/// Starts delivering received bytes through the COM1 interrupt.
+pub fn enable_input() {
+    // Initializing the port enables its receive interrupt.
+    lazy_static::initialize(&SERIAL);
+    pic::unmask(Irq::Com1);
+}
+
+/// Called from the COM1 interrupt handler to move received bytes into the input queue.
+pub fn handle_interrupt() {
+    let mut serial = SERIAL.lock();
+    let mut input = INPUT.lock();
+
+    while let Ok(byte) = serial.try_receive() {
+        input.push(byte);
+    }
+
+    INPUT_WAKER.wake();
+}
+
+/// Whether there are received bytes waiting to be read.
+pub fn has_input() -> bool {
+    interrupts::without_interrupts(|| !INPUT.lock().is_empty())
+}
+
+/// Takes the next received byte, if any.
+pub fn read_byte() -> Option<u8> {
+    // The interrupt handler takes the same lock.
+    interrupts::without_interrupts(|| INPUT.lock().pop())
+}
+
+/// Ready once there are received bytes, waking the task when some arrive otherwise.
+pub fn poll_input(cx: &mut Context) -> Poll<()> {
+    INPUT_WAKER.poll_with(cx, || has_input().then_some(()))
+}
+
+/// Waits for the next received byte.
+pub async fn next_byte() -> u8 {
+    future::poll_fn(|cx| INPUT_WAKER.poll_with(cx, read_byte)).await
+}
+
+/// Releases the serial port lock even if someone is holding it.
+///
+/// # SAFETY
+/// Whoever was holding the lock must never run again, e.g. because we are panicking.
+pub unsafe fn force_unlock() {
+    // SAFETY: Upheld by the caller.
+    unsafe {
+        SERIAL.force_unlock();
+    }
+}
+
+/// [`Write`] adapter for COM1, for code that is generic over where its output goes.
+pub struct SerialWriter;
+
+impl Write for SerialWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        _serial_print(format_args!("{s}"));
+        Ok(())
+    }
+}
+
 #[macro_export]
 macro_rules! serial_print {
     ($($arg:tt)*) => ($crate::debug::serial::_serial_print(format_args!($($arg)*)));
@@ -33,8 +110,11 @@ macro_rules! serial_println {
 
 #[doc(hidden)]
 pub fn _serial_print(args: fmt::Arguments) {
-    SERIAL
-        .lock()
-        .write_fmt(args)
-        .expect("Writing to serial port never returns an error.");
+    // The receive interrupt takes the same lock.
+    interrupts::without_interrupts(|| {
+        SERIAL
+            .lock()
+            .write_fmt(args)
+            .expect("Writing to serial port never returns an error.");
+    });
 }
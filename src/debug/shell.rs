@@ -0,0 +1,389 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! An interactive debug shell on COM1, for poking at a running kernel with `-serial stdio`.
+//!
+//! Lines that don't start with a command name are passed on as [`SerialLine`] messages, which is
+//! how the game receives guesses typed over serial. Page Up and Page Down scroll through the
+//! on-screen debug log.
+
+use alloc::{string::String, sync::Arc, vec::Vec};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    color::{COLOR_SCHEME, Theme},
+    debug::{
+        diagnostics,
+        line_editor::{Input, LineEditor},
+        logger,
+        serial::{self, SerialWriter},
+        text::DEBUG_WRITER,
+    },
+    serial_print, serial_println,
+    trampoline::{
+        BootInfo,
+        clock::{self, Instant},
+        power,
+        ps2::keyboard,
+        smp,
+    },
+    wordle::game::{Game, LetterScore, MAX_GUESSES, Stats},
+};
+
+const PROMPT: &str = "wordle> ";
+/// How far Page Up and Page Down scroll the debug log.
+const SCROLL_LINES: usize = 10;
+
+struct Command {
+    name: &'static str,
+    usage: &'static str,
+    help: &'static str,
+    run: fn(&mut World, &[&str]),
+}
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "help",
+        usage: "",
+        help: "list commands",
+        run: help,
+    },
+    Command {
+        name: "memmap",
+        usage: "",
+        help: "print the memory map from the bootloader",
+        run: |_, _| diagnostics::dump_memory_map(),
+    },
+    Command {
+        name: "pagetables",
+        usage: "",
+        help: "print the active page table mappings",
+        run: |_, _| diagnostics::dump_page_tables(),
+    },
+    Command {
+        name: "heap",
+        usage: "",
+        help: "print kernel heap usage",
+        run: |_, _| diagnostics::dump_heap(),
+    },
+    Command {
+        name: "frames",
+        usage: "",
+        help: "print free physical memory",
+        run: frames,
+    },
+    Command {
+        name: "acpi",
+        usage: "",
+        help: "print the ACPI tables and what was found in them",
+        run: |_, _| diagnostics::dump_acpi(),
+    },
+    Command {
+        name: "cpus",
+        usage: "",
+        help: "list the CPUs and check that each one takes work",
+        run: cpus,
+    },
+    Command {
+        name: "uptime",
+        usage: "",
+        help: "print the time since boot and how it is measured",
+        run: uptime,
+    },
+    Command {
+        name: "time",
+        usage: "LINE",
+        help: "run LINE and print how long it took",
+        run: time,
+    },
+    Command {
+        name: "dmesg",
+        usage: "",
+        help: "print the kernel log",
+        run: dmesg,
+    },
+    Command {
+        name: "guess",
+        usage: "WORD",
+        help: "guess WORD, for words that are also command names",
+        run: |world, args| {
+            world.write_message(SerialLine(args.join(" ")));
+        },
+    },
+    Command {
+        name: "word",
+        usage: "[WORD]",
+        help: "show the guesses so far, or start a new round with WORD as the answer",
+        run: word,
+    },
+    Command {
+        name: "reveal",
+        usage: "",
+        help: "print the answer",
+        run: reveal,
+    },
+    Command {
+        name: "stats",
+        usage: "",
+        help: "print results of the rounds played since boot",
+        run: stats,
+    },
+    Command {
+        name: "theme",
+        usage: "[NAME]",
+        help: "show or change the color theme",
+        run: theme,
+    },
+    Command {
+        name: "shutdown",
+        usage: "",
+        help: "turn the machine off",
+        run: |_, _| power::shutdown(),
+    },
+    Command {
+        name: "reboot",
+        usage: "",
+        help: "reset the machine",
+        run: |_, _| power::reboot(),
+    },
+];
+
+fn help(_: &mut World, _: &[&str]) {
+    for command in COMMANDS {
+        serial_println!(
+            "  {:<10} {:<7} {}",
+            command.name,
+            command.usage,
+            command.help
+        );
+    }
+}
+
+fn frames(world: &mut World, _: &[&str]) {
+    diagnostics::dump_free_frames(&world.resource::<BootInfo>().frame_allocator);
+}
+
+fn cpus(_: &mut World, _: &[&str]) {
+    const TIMEOUT: Duration = Duration::from_millis(100);
+
+    for cpu in smp::cpus() {
+        serial_print!("  CPU {:<3} APIC ID {:<4} ", cpu.index, cpu.lapic_id);
+        if cpu.is_bsp() {
+            serial_println!("boot processor, running the game");
+            continue;
+        }
+
+        let done = Arc::new(AtomicBool::new(false));
+        let start = Instant::now();
+        let sent = smp::run_on(cpu.index, {
+            let done = done.clone();
+            move || done.store(true, Ordering::Release)
+        });
+
+        while sent && !done.load(Ordering::Acquire) && start.elapsed() < TIMEOUT {
+            core::hint::spin_loop();
+        }
+
+        if !sent {
+            serial_println!("offline");
+        } else if done.load(Ordering::Acquire) {
+            serial_println!("ran a task in {:?}", start.elapsed());
+        } else {
+            serial_println!("didn't respond within {TIMEOUT:?}");
+        }
+    }
+}
+
+fn uptime(_: &mut World, _: &[&str]) {
+    let time = clock::since_boot();
+    serial_println!("Up for {}.{:03} s", time.as_secs(), time.subsec_millis());
+    match clock::tsc_frequency() {
+        Some(hz) => serial_println!(
+            "TSC at {hz} Hz, {}invariant",
+            if clock::is_invariant() { "" } else { "not " }
+        ),
+        None => serial_println!("The TSC hasn't been calibrated."),
+    }
+}
+
+fn time(world: &mut World, args: &[&str]) {
+    if args.is_empty() {
+        serial_println!("Usage: time LINE");
+        return;
+    }
+
+    let start = Instant::now();
+    execute(world, args.join(" "));
+    serial_println!("Took {:?}.", start.elapsed());
+}
+
+fn dmesg(_: &mut World, _: &[&str]) {
+    logger::with_ring(|ring| {
+        ring.read(|chunk| serial_print!("{}", String::from_utf8_lossy(chunk)));
+    });
+}
+
+fn word(world: &mut World, args: &[&str]) {
+    let Some(mut game) = world.get_resource_mut::<Game>() else {
+        serial_println!("No game is running.");
+        return;
+    };
+
+    if let Some(answer) = args.first() {
+        match answer.parse() {
+            Ok(answer) => {
+                let hard_mode = game.hard_mode();
+                *game = Game::new(answer, hard_mode);
+                serial_println!("Started a new round.");
+            }
+            Err(error) => serial_println!("Invalid word: {error}."),
+        }
+        return;
+    }
+
+    serial_println!(
+        "{:?}, {} of {} guesses used",
+        game.status(),
+        game.guesses().len(),
+        MAX_GUESSES
+    );
+    for guess in game.guesses() {
+        serial_print!("  ");
+        for (letter, score) in guess.word.letters().iter().zip(guess.scores) {
+            let mark = match score {
+                LetterScore::Correct => "\x1b[32m",
+                LetterScore::Present => "\x1b[33m",
+                LetterScore::Absent => "\x1b[90m",
+            };
+            serial_print!("{mark}{}\x1b[0m", letter.to_ascii_uppercase() as char);
+        }
+        serial_println!();
+    }
+}
+
+fn reveal(world: &mut World, _: &[&str]) {
+    match world.get_resource::<Game>() {
+        Some(game) => serial_println!("The answer is {}.", game.answer()),
+        None => serial_println!("No game is running."),
+    }
+}
+
+fn stats(world: &mut World, _: &[&str]) {
+    let Some(stats) = world.get_resource::<Stats>() else {
+        serial_println!("No game is running.");
+        return;
+    };
+
+    serial_println!(
+        "Played {}, won {}%, streak {} (best {})",
+        stats.played(),
+        stats.win_percentage(),
+        stats.current_streak(),
+        stats.max_streak()
+    );
+    for (guesses, &wins) in stats.distribution().iter().enumerate() {
+        serial_println!("  {}: {wins}", guesses + 1);
+    }
+}
+
+fn theme(_: &mut World, args: &[&str]) {
+    let Some(name) = args.first() else {
+        serial_print!("Current theme: {}. Available:", COLOR_SCHEME.get().name());
+        for theme in Theme::ALL {
+            serial_print!(" {}", theme.name());
+        }
+        serial_println!();
+        return;
+    };
+
+    match name.parse() {
+        Ok(theme) => COLOR_SCHEME.set(theme),
+        Err(()) => serial_println!("Unknown theme `{name}`."),
+    }
+}
+
+/// A line typed over serial or on the keyboard that isn't a shell command, e.g. a guess. Menu items
+/// picked with the mouse arrive as the line that would pick them.
+#[derive(Message, Debug, Clone)]
+pub struct SerialLine(pub String);
+
+fn execute(world: &mut World, line: String) {
+    let mut words = line.split_whitespace();
+    let command = words
+        .next()
+        .and_then(|name| COMMANDS.iter().find(|command| command.name == name));
+
+    match command {
+        Some(command) => {
+            let args: Vec<&str> = words.collect();
+            (command.run)(world, &args);
+        }
+        None => {
+            world.write_message(SerialLine(line));
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Shell {
+    editor: LineEditor,
+    /// Set after a line was handled, so the prompt is printed again after all of its output.
+    needs_prompt: bool,
+}
+
+/// Systems reading [`SerialLine`] should run after this set to see lines in the same update.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShellInput;
+
+/// Feeds bytes received over serial or typed on the keyboard to the line editor and runs every
+/// completed line.
+fn poll(world: &mut World) {
+    while let Some(byte) = serial::read_byte().or_else(keyboard::read_byte) {
+        let input = world
+            .resource_mut::<Shell>()
+            .editor
+            .feed(byte, &mut SerialWriter);
+
+        match input {
+            Some(Input::Line(line)) => {
+                execute(world, line);
+                world.resource_mut::<Shell>().needs_prompt = true;
+            }
+            Some(Input::PageUp) => DEBUG_WRITER.lock().scroll_up(SCROLL_LINES),
+            Some(Input::PageDown) => DEBUG_WRITER.lock().scroll_down(SCROLL_LINES),
+            None => {}
+        }
+    }
+}
+
+fn prompt(mut shell: ResMut<Shell>) {
+    if shell.needs_prompt {
+        shell.editor.redraw(&mut SerialWriter);
+        shell.needs_prompt = false;
+    }
+}
+
+fn greet() {
+    serial_println!("Debug shell ready, type `help` for a list of commands.");
+}
+
+pub struct ShellPlugin;
+
+impl Plugin for ShellPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Shell {
+            editor: LineEditor::new(PROMPT),
+            needs_prompt: true,
+        })
+        .add_message::<SerialLine>()
+        .add_systems(Startup, greet)
+        .add_systems(Update, poll.in_set(ShellInput))
+        .add_systems(PostUpdate, prompt);
+    }
+}
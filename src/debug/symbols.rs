@@ -0,0 +1,86 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Lookup of function names for addresses in the kernel, using the table that `embed_symbols.sh`
+//! writes into the `.ksyms` section after linking.
+
+use core::fmt;
+
+const KSYMS_SIZE: usize = 2 * 1024 * 1024;
+
+/// Zeroed at compile time and filled in by `embed_symbols.sh`. Must only be read through
+/// [`table`] so the compiler can't assume it is still all zeroes.
+#[used]
+#[unsafe(link_section = ".ksyms")]
+static KSYMS: [u8; KSYMS_SIZE] = [0; KSYMS_SIZE];
+
+/// Size of one entry in the table: the start address as a `u64`, then the size and the offset of
+/// the name as `u32`s.
+const ENTRY_SIZE: usize = 16;
+
+/// The sorted entries and the names they point into. Empty if no table was embedded.
+fn table() -> (&'static [[u8; ENTRY_SIZE]], &'static [u8]) {
+    // black_box hides where the pointer came from, so reads can't be constant folded to zero.
+    let ptr = core::hint::black_box(&raw const KSYMS).cast::<u8>();
+    // SAFETY: The pointer comes from a static of exactly this size.
+    let data = unsafe { core::slice::from_raw_parts(ptr, KSYMS_SIZE) };
+
+    let (count, rest) = data
+        .split_first_chunk::<8>()
+        .expect("KSYMS_SIZE is at least 8");
+    let entries_size = (u64::from_le_bytes(*count) as usize)
+        .checked_mul(ENTRY_SIZE)
+        .filter(|&size| size <= rest.len())
+        .unwrap_or(0);
+
+    let (entries, names) = rest.split_at(entries_size);
+    (entries.as_chunks().0, names)
+}
+
+fn entry_start(entry: &[u8; ENTRY_SIZE]) -> u64 {
+    u64::from_le_bytes(entry[0..8].try_into().unwrap())
+}
+
+fn entry_size(entry: &[u8; ENTRY_SIZE]) -> u64 {
+    u32::from_le_bytes(entry[8..12].try_into().unwrap()) as u64
+}
+
+fn entry_name<'a>(entry: &[u8; ENTRY_SIZE], names: &'a [u8]) -> Option<&'a str> {
+    let offset = u32::from_le_bytes(entry[12..16].try_into().unwrap()) as usize;
+    let name = names.get(offset..)?;
+    let end = name.iter().position(|&b| b == 0)?;
+    core::str::from_utf8(&name[..end]).ok()
+}
+
+/// A function that an address was resolved to.
+#[derive(Debug, Clone, Copy)]
+pub struct Symbol {
+    pub name: &'static str,
+    pub offset: u64,
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}+{:#x}", self.name, self.offset)
+    }
+}
+
+/// Finds the function containing `address`. Returns `None` if no symbol table was embedded or the
+/// address is not inside any known function.
+pub fn resolve(address: u64) -> Option<Symbol> {
+    let (entries, names) = table();
+
+    // The table is sorted by address, so the last function starting at or before the address is
+    // the only candidate.
+    let index = entries
+        .partition_point(|entry| entry_start(entry) <= address)
+        .checked_sub(1)?;
+    let entry = &entries[index];
+
+    let start = entry_start(entry);
+    (address < start + entry_size(entry)).then_some(Symbol {
+        name: entry_name(entry, names)?,
+        offset: address - start,
+    })
+}
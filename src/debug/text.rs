@@ -5,63 +5,86 @@
 use core::fmt;
 
 use embedded_graphics::{
-    mono_font::{MonoTextStyle, ascii::FONT_9X18_BOLD},
-    pixelcolor::Rgb888,
-    prelude::Point,
-    text::renderer::TextRenderer,
+    prelude::{DrawTarget, OriginDimensions, Point, Size},
+    primitives::Rectangle,
 };
 use spin::Mutex;
 
 use crate::{
     color::{COLOR_SCHEME, ColorScheme},
-    debug::rendering::DEBUG_FRAMEBUFFER,
+    debug::{
+        console::{Console, ConsoleColor},
+        rendering::DEBUG_FRAMEBUFFER,
+    },
     util::InfallibleResultExt,
 };
-use lazy_static::lazy_static;
 
-lazy_static! {
-    pub static ref DEBUG_WRITER: Mutex<DebugWriter> = Mutex::new(DebugWriter::new());
-}
+const MARGIN: u32 = 20;
+
+pub static DEBUG_WRITER: Mutex<DebugWriter> = Mutex::new(DebugWriter::new());
 
+/// The on-screen debug log. Text scrolls once it reaches the bottom of the screen.
 pub struct DebugWriter {
-    style: MonoTextStyle<'static, Rgb888>,
-    position: Point,
+    console: Console,
 }
 
 impl DebugWriter {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
-            style: MonoTextStyle::new(&FONT_9X18_BOLD, COLOR_SCHEME.error_foreground()),
-            position: Point::new(20, 30),
+            console: Console::new(ConsoleColor::Red),
         }
     }
 
-    pub fn write(&mut self, s: &str) {
-        for c in s.chars() {
-            // New line or line wrap
-            if c == '\n'
-                || self.position.x
-                    >= DEBUG_FRAMEBUFFER.lock().fb.width() as i32
-                        - 20
-                        - self.style.font.character_size.width as i32
-            {
-                self.position.y += self.style.line_height() as i32;
-                self.position.x = 20;
-                continue;
-            }
-
-            let mut tmp = [0; 4];
-
-            self.position = self
-                .style
-                .draw_string(
-                    c.encode_utf8(&mut tmp),
-                    self.position,
-                    embedded_graphics::text::Baseline::Bottom,
-                    &mut *DEBUG_FRAMEBUFFER.lock(),
-                )
-                .infallible();
+    /// Removes all text from the log and the screen.
+    pub fn clear(&mut self) {
+        self.console.clear();
+        self.render();
+    }
+
+    /// Scrolls back through the log. Coming from the bottom, this also takes the screen back from
+    /// whatever was drawn over the log, such as the game.
+    pub fn scroll_up(&mut self, lines: usize) {
+        if !self.console.is_scrolled_back()
+            && let Some(framebuffer) = DEBUG_FRAMEBUFFER.lock().as_mut()
+        {
+            framebuffer.clear(COLOR_SCHEME.background()).infallible();
+            self.console.invalidate();
         }
+
+        self.console.scroll_up(lines);
+        self.render();
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.console.scroll_down(lines);
+        self.render();
+    }
+
+    /// Whether the log is scrolled back, in which case it owns the screen.
+    pub fn is_scrolled_back(&self) -> bool {
+        self.console.is_scrolled_back()
+    }
+
+    pub fn write(&mut self, s: &str) {
+        self.console.write(s);
+        self.render();
+    }
+
+    fn render(&mut self) {
+        let mut framebuffer = DEBUG_FRAMEBUFFER.lock();
+        let Some(framebuffer) = framebuffer.as_mut() else {
+            return;
+        };
+        let size = framebuffer.size();
+        let area = Rectangle::new(
+            Point::new(MARGIN as i32, MARGIN as i32),
+            Size::new(
+                size.width.saturating_sub(2 * MARGIN),
+                size.height.saturating_sub(2 * MARGIN),
+            ),
+        );
+
+        self.console.render(framebuffer, area);
     }
 }
 
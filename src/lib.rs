@@ -1,5 +1,9 @@
 #![no_std]
+#![cfg_attr(test, no_main)]
 #![feature(abi_x86_interrupt)]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::testing::test_runner)]
+#![reexport_test_harness_main = "test_main"]
 
 // SPDX-FileCopyrightText: 2025 SunnyMonster
 //
@@ -7,18 +11,25 @@
 
 extern crate alloc;
 
-use crate::trampoline::BootInfo;
+use crate::trampoline::{BootInfo, cmdline::BootOptions};
 
 use crate::trampoline::{gdt, limine_requests};
 
 pub mod color;
 pub mod debug;
+pub mod task;
+#[cfg(test)]
+mod testing;
 pub mod trampoline;
 pub mod util;
 pub mod wordle;
 
-pub fn kernel_main(boot_info: BootInfo) -> ! {
-    wordle::run(boot_info);
+/// Runs the game on the kernel's own stack, which [`BootInfo`] has been moved onto.
+pub fn kernel_main(mut boot_info: BootInfo, options: BootOptions) -> ! {
+    // Nothing is left on Limine's stack or reads its responses any more.
+    boot_info.frame_allocator.reclaim_bootloader_memory();
+
+    wordle::run(boot_info, options);
 
     hcf();
 }
@@ -28,3 +39,22 @@ pub fn hcf() -> ! {
         x86_64::instructions::hlt();
     }
 }
+
+// SAFETY: Entry point of the test kernel, see the one in main.rs.
+#[cfg(test)]
+#[unsafe(no_mangle)]
+extern "C" fn trampoline_main() -> ! {
+    debug::backtrace::record_boot_stack();
+    let (boot_info, _) = trampoline::init();
+    testing::set_boot_info(boot_info);
+
+    test_main();
+
+    hcf();
+}
+
+#[cfg(test)]
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    testing::test_panic_handler(info);
+}
@@ -8,43 +8,18 @@
 use core::{arch::asm, panic::PanicInfo};
 
 use wordle_kernel::{
-    hcf, kernel_main,
-    trampoline::{
-        BootInfo, gdt, interrupts, limine_requests,
-        memory::{self, HHDM_OFFSET, get_pagetable, map_framebuffers},
-    },
+    debug::{backtrace, panic},
+    kernel_main,
+    trampoline::{self, memory},
 };
-use x86_64::{
-    VirtAddr,
-    structures::paging::{OffsetPageTable, PageSize, Size4KiB},
-};
-
-#[macro_use]
-extern crate wordle_kernel;
+use x86_64::structures::paging::{PageSize, Size4KiB};
 
-/// # Setup order
-/// 1. Exception handling
-/// 2. Basic stack and heap
-/// 3. Reclaim bootloader memory
-/// 4. Initialize bevy, etc
 // SAFETY:  must have a stable, unmangled symbol because it is called by Limine.
 //          the ABI matches the expected System V calling convention.
 #[unsafe(no_mangle)]
 extern "C" fn trampoline_main() -> ! {
-    debug_assert!(limine_requests::BASE_REVISION.is_supported());
-
-    gdt::init();
-    interrupts::init_idt();
-
-    let mut frame_allocator = memory::initialize_paging();
-
-    let page_table = get_pagetable();
-    // SAFETY: get_pagetable returns address from CR3 which must be valid. HHDM_OFFSET is correct
-    // as we mapped our own HHDM before with the call to initialize_paging.
-    let mut offset_page_table =
-        unsafe { OffsetPageTable::new(page_table, VirtAddr::new(HHDM_OFFSET)) };
-
-    let framebuffers = map_framebuffers(&mut offset_page_table, &mut frame_allocator);
+    backtrace::record_boot_stack();
+    let (boot_info, options) = trampoline::init();
 
     // SAFETY: this switches the kernel stack, but then we call kernel_main after, which never
     // returns. Execution effectively starts afresh in kernel_main.
@@ -55,15 +30,10 @@ extern "C" fn trampoline_main() -> ! {
         );
     }
 
-    kernel_main(BootInfo {
-        framebuffers,
-        frame_allocator,
-    });
+    kernel_main(boot_info, options);
 }
 
 #[panic_handler]
 fn rust_panic(info: &PanicInfo) -> ! {
-    eprintln!("{}", info);
-    serial_println!("{}", info);
-    hcf();
+    panic::handle_panic(info);
 }
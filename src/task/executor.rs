@@ -0,0 +1,172 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Polls tasks once something woke them, and halts the CPU while nothing did.
+//!
+//! Waking a task only sets a flag, so wakers can be used from interrupt handlers and other CPUs.
+
+use alloc::{boxed::Box, sync::Arc, task::Wake, vec::Vec};
+use core::{
+    future::Future,
+    mem,
+    pin::{Pin, pin},
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+
+use crate::{
+    task::timer,
+    trampoline::{apic, smp},
+};
+
+/// Tasks that haven't finished yet, including ones spawned while the executor isn't running.
+static TASKS: Mutex<Vec<Task>> = Mutex::new(Vec::new());
+/// Set whenever a task is woken, so that the executor doesn't halt with work to do.
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+struct TaskWaker {
+    woken: AtomicBool,
+}
+
+impl TaskWaker {
+    /// New tasks start out woken so that they are polled once.
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            woken: AtomicBool::new(true),
+        })
+    }
+
+    fn take(&self) -> bool {
+        self.woken.swap(false, Ordering::AcqRel)
+    }
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        notify();
+    }
+}
+
+/// Tells the executor there is work to do, bringing the BSP out of `hlt` if needed.
+pub(crate) fn notify() {
+    PENDING.store(true, Ordering::Release);
+
+    // Interrupts on the BSP end its hlt by themselves.
+    if smp::current().is_some_and(|cpu| !cpu.is_bsp()) {
+        apic::send_ipi(smp::cpus()[0].lapic_id, apic::WAKE_VECTOR);
+    }
+}
+
+struct Task {
+    future: Pin<Box<dyn Future<Output = ()> + Send>>,
+    state: Arc<TaskWaker>,
+    waker: Waker,
+}
+
+impl Task {
+    /// Polls the task if it was woken. Returns whether it finished.
+    fn poll(&mut self) -> bool {
+        self.state.take()
+            && self
+                .future
+                .as_mut()
+                .poll(&mut Context::from_waker(&self.waker))
+                .is_ready()
+    }
+}
+
+/// Runs `future` in the background. It makes progress whenever some CPU is in [`block_on`].
+pub fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+    let state = TaskWaker::new();
+    TASKS.lock().push(Task {
+        future: Box::pin(future),
+        waker: Waker::from(state.clone()),
+        state,
+    });
+    notify();
+}
+
+/// Polls every woken task once.
+fn run_tasks() {
+    // Tasks can spawn more tasks while they are polled, so the lock can't be held meanwhile.
+    let mut tasks = mem::take(&mut *TASKS.lock());
+    tasks.retain_mut(|task| !task.poll());
+
+    let mut queue = TASKS.lock();
+    tasks.append(&mut queue);
+    *queue = tasks;
+}
+
+/// Runs `future` to completion along with the spawned tasks, halting whenever all of them are
+/// waiting. Meant for the BSP, since only it receives device interrupts.
+pub fn block_on<T>(future: impl Future<Output = T>) -> T {
+    let state = TaskWaker::new();
+    let waker = Waker::from(state.clone());
+    let mut future = pin!(future);
+
+    loop {
+        PENDING.store(false, Ordering::Release);
+        timer::wake_expired();
+
+        if state.take()
+            && let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker))
+        {
+            return output;
+        }
+        run_tasks();
+
+        // Check with interrupts off so that a wake up right after the check isn't lost.
+        interrupts::disable();
+        if PENDING.load(Ordering::Acquire) {
+            interrupts::enable();
+        } else {
+            interrupts::enable_and_hlt();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::*;
+    use crate::{task::sleep, trampoline::clock::Instant};
+
+    #[test_case]
+    fn returns_the_output() {
+        assert_eq!(block_on(async { 6 * 7 }), 42);
+    }
+
+    #[test_case]
+    fn spawned_tasks_run() {
+        let ran = Arc::new(AtomicBool::new(false));
+        spawn({
+            let ran = ran.clone();
+            async move { ran.store(true, Ordering::Release) }
+        });
+
+        block_on(async {
+            while !ran.load(Ordering::Acquire) {
+                sleep(Duration::from_millis(1)).await;
+            }
+        });
+    }
+
+    #[test_case]
+    fn sleeps_for_at_least_the_duration() {
+        let start = Instant::now();
+        block_on(sleep(Duration::from_millis(20)));
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(20));
+        assert!(elapsed < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,74 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Cooperative multitasking with `async` functions.
+//!
+//! Tasks run on the BSP in [`block_on`], which halts the CPU whenever none of them can make
+//! progress. Drivers keep an [`AtomicWaker`] that their interrupt handler wakes, so a task waiting
+//! for hardware sleeps until the interrupt arrives instead of polling.
+
+use core::task::{Context, Poll, Waker};
+
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+
+pub mod executor;
+pub mod timer;
+
+pub use executor::{block_on, spawn};
+pub use timer::sleep;
+
+/// Holds the waker of the task waiting on a device, so that its interrupt handler can wake it.
+///
+/// Interrupt handlers only wake by reference, since dropping a waker may free memory and the heap
+/// can't be used from interrupt handlers.
+pub struct AtomicWaker {
+    waker: Mutex<Option<Waker>>,
+}
+
+impl AtomicWaker {
+    pub const fn new() -> Self {
+        Self {
+            waker: Mutex::new(None),
+        }
+    }
+
+    /// Makes [`wake`](Self::wake) wake `waker`, replacing the previous one.
+    pub fn register(&self, waker: &Waker) {
+        // The interrupt handler takes the same lock.
+        interrupts::without_interrupts(|| {
+            let mut current = self.waker.lock();
+            if !current
+                .as_ref()
+                .is_some_and(|current| current.will_wake(waker))
+            {
+                *current = Some(waker.clone());
+            }
+        });
+    }
+
+    /// Wakes the registered task, if any. Safe to call from interrupt handlers.
+    pub fn wake(&self) {
+        if let Some(waker) = &*self.waker.lock() {
+            waker.wake_by_ref();
+        }
+    }
+
+    /// Ready with the result of `f` once it returns `Some`. Registers the task before trying again,
+    /// so that a wake up between the two attempts isn't lost.
+    pub fn poll_with<T>(&self, cx: &mut Context, mut f: impl FnMut() -> Option<T>) -> Poll<T> {
+        if let Some(value) = f() {
+            return Poll::Ready(value);
+        }
+
+        self.register(cx.waker());
+        f().map_or(Poll::Pending, Poll::Ready)
+    }
+}
+
+impl Default for AtomicWaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
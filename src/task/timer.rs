@@ -0,0 +1,90 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Sleeping without holding up other tasks. The PIT's tick tells the executor when a sleeper is
+//! due, so the resolution is one tick.
+
+use alloc::vec::Vec;
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use spin::Mutex;
+
+use crate::{task::executor, trampoline::clock};
+
+/// Sleeping tasks and when they are due, as time since boot.
+static SLEEPERS: Mutex<Vec<(Duration, Waker)>> = Mutex::new(Vec::new());
+/// When the earliest sleeper is due, in nanoseconds since boot. Checked on every tick without
+/// taking the lock.
+static NEXT_DEADLINE: AtomicU64 = AtomicU64::new(u64::MAX);
+
+fn nanos(duration: Duration) -> u64 {
+    duration.as_nanos().try_into().unwrap_or(u64::MAX)
+}
+
+/// Waits for `duration`.
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: clock::since_boot().saturating_add(duration),
+    }
+}
+
+/// Future returned by [`sleep`].
+pub struct Sleep {
+    deadline: Duration,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if clock::since_boot() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        let mut sleepers = SLEEPERS.lock();
+        let waker = cx.waker();
+        if !sleepers
+            .iter()
+            .any(|(deadline, sleeper)| *deadline == self.deadline && sleeper.will_wake(waker))
+        {
+            sleepers.push((self.deadline, waker.clone()));
+        }
+        NEXT_DEADLINE.fetch_min(nanos(self.deadline), Ordering::AcqRel);
+
+        Poll::Pending
+    }
+}
+
+/// Called from the timer interrupt to get the executor going once a sleeper is due.
+pub fn tick() {
+    if nanos(clock::since_boot()) >= NEXT_DEADLINE.load(Ordering::Acquire) {
+        executor::notify();
+    }
+}
+
+/// Wakes the sleepers that are due. Called by the executor, since waking may free memory.
+pub(crate) fn wake_expired() {
+    let now = clock::since_boot();
+    if nanos(now) < NEXT_DEADLINE.load(Ordering::Acquire) {
+        return;
+    }
+
+    let mut sleepers = SLEEPERS.lock();
+    sleepers.retain(|(deadline, waker)| {
+        let expired = *deadline <= now;
+        if expired {
+            waker.wake_by_ref();
+        }
+        !expired
+    });
+
+    let next = sleepers.iter().map(|&(deadline, _)| nanos(deadline)).min();
+    NEXT_DEADLINE.store(next.unwrap_or(u64::MAX), Ordering::Release);
+}
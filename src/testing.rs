@@ -0,0 +1,100 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Harness for `#[test_case]` functions, which run inside the booted kernel under QEMU.
+//!
+//! Results are reported over serial, and QEMU is shut down through the `isa-debug-exit` device
+//! with an exit code saying whether every test passed. See the `test` recipe in the justfile.
+
+use core::{
+    panic::PanicInfo,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use spin::Mutex;
+use x86_64::instructions::port::Port;
+
+use crate::{hcf, serial_print, serial_println, trampoline::BootInfo};
+
+/// I/O port the `isa-debug-exit` device is configured at.
+const ISA_DEBUG_EXIT_PORT: u16 = 0xf4;
+
+/// QEMU exits with `(code << 1) | 1`, so these can't be confused with QEMU's own exit codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum QemuExitCode {
+    Success = 0x10,
+    Failed = 0x11,
+}
+
+pub fn exit_qemu(exit_code: QemuExitCode) -> ! {
+    // SAFETY: The test recipe always attaches isa-debug-exit at this port. Writing to it ends the
+    // virtual machine.
+    unsafe {
+        Port::<u32>::new(ISA_DEBUG_EXIT_PORT).write(exit_code as u32);
+    }
+
+    // Not running under QEMU with the exit device.
+    hcf();
+}
+
+static BOOT_INFO: Mutex<Option<BootInfo>> = Mutex::new(None);
+
+pub fn set_boot_info(boot_info: BootInfo) {
+    *BOOT_INFO.lock() = Some(boot_info);
+}
+
+/// Gives tests access to the framebuffers and frame allocator the kernel booted with.
+pub fn with_boot_info<R>(f: impl FnOnce(&mut BootInfo) -> R) -> R {
+    f(BOOT_INFO
+        .lock()
+        .as_mut()
+        .expect("Boot info is set before tests run."))
+}
+
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+static PASSED: AtomicUsize = AtomicUsize::new(0);
+
+fn print_summary() {
+    serial_println!(
+        "{} of {} tests passed",
+        PASSED.load(Ordering::Relaxed),
+        TOTAL.load(Ordering::Relaxed)
+    );
+}
+
+pub trait Testable {
+    fn run(&self);
+}
+
+impl<T: Fn()> Testable for T {
+    fn run(&self) {
+        serial_print!("{}...\t", core::any::type_name::<T>());
+        self();
+        PASSED.fetch_add(1, Ordering::Relaxed);
+        serial_println!("[ok]");
+    }
+}
+
+pub fn test_runner(tests: &[&dyn Testable]) {
+    serial_println!("Running {} tests", tests.len());
+    TOTAL.store(tests.len(), Ordering::Relaxed);
+    for test in tests {
+        test.run();
+    }
+    print_summary();
+
+    exit_qemu(QemuExitCode::Success);
+}
+
+/// A panic is a failed test. The remaining tests are not run.
+pub fn test_panic_handler(info: &PanicInfo) -> ! {
+    serial_println!("[failed]");
+    serial_println!();
+    serial_println!("{info}");
+    serial_println!();
+    print_summary();
+
+    exit_qemu(QemuExitCode::Failed);
+}
@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Just enough AML to find the `\_S5` object in the DSDT, which holds the values to write to the
+//! PM1 control registers to turn the machine off. There is no AML interpreter, so this only
+//! understands the way firmware usually declares it: `Name (_S5, Package () { a, b, ... })`.
+
+const NAME_OP: u8 = 0x08;
+const ROOT_PREFIX: u8 = b'\\';
+const PACKAGE_OP: u8 = 0x12;
+const ZERO_OP: u8 = 0x00;
+const ONE_OP: u8 = 0x01;
+const BYTE_PREFIX: u8 = 0x0A;
+const WORD_PREFIX: u8 = 0x0B;
+const DWORD_PREFIX: u8 = 0x0C;
+
+/// The `SLP_TYP` values for the PM1a and PM1b control registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SleepType {
+    pub a: u8,
+    pub b: u8,
+}
+
+/// Reads an integer constant, advancing `at` past it.
+fn integer(aml: &[u8], at: &mut usize) -> Option<u8> {
+    let value = match *aml.get(*at)? {
+        ZERO_OP => 0,
+        ONE_OP => 1,
+        BYTE_PREFIX => {
+            *at += 1;
+            *aml.get(*at)?
+        }
+        // Sleep types are only three bits, so the low byte is all that matters.
+        prefix @ (WORD_PREFIX | DWORD_PREFIX) => {
+            let value = *aml.get(*at + 1)?;
+            *at += if prefix == WORD_PREFIX { 2 } else { 4 };
+            value
+        }
+        _ => return None,
+    };
+    *at += 1;
+
+    Some(value)
+}
+
+/// Parses the package following the `_S5_` name that starts at `name`.
+fn package(aml: &[u8], name: usize) -> Option<SleepType> {
+    let mut at = name + 4;
+    if *aml.get(at)? != PACKAGE_OP {
+        return None;
+    }
+    at += 1;
+
+    // The top two bits of the first PkgLength byte count how many bytes follow it.
+    at += 1 + (*aml.get(at)? >> 6) as usize;
+    let elements = *aml.get(at)?;
+    at += 1;
+    if elements == 0 {
+        return None;
+    }
+
+    let a = integer(aml, &mut at)?;
+    let b = if elements > 1 {
+        integer(aml, &mut at)?
+    } else {
+        0
+    };
+
+    Some(SleepType { a, b })
+}
+
+/// Finds `\_S5` in the AML of a DSDT or SSDT, without the table header.
+pub fn find_s5(aml: &[u8]) -> Option<SleepType> {
+    aml.windows(4)
+        .enumerate()
+        .filter(|&(_, window)| window == b"_S5_")
+        .filter(|&(name, _)| match name {
+            0 => false,
+            1 => aml[0] == NAME_OP,
+            _ => {
+                aml[name - 1] == NAME_OP
+                    || (aml[name - 1] == ROOT_PREFIX && aml[name - 2] == NAME_OP)
+            }
+        })
+        .find_map(|(name, _)| package(aml, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn finds_byte_package() {
+        // Name (\_S5, Package (0x04) { 0x05, 0x05, Zero, Zero }) inside a scope, as QEMU has it.
+        let aml = b"\x10\x08\x08\\_S5_\x12\x0a\x04\x0a\x05\x0a\x05\x00\x00";
+        assert_eq!(find_s5(aml), Some(SleepType { a: 5, b: 5 }));
+    }
+
+    #[test_case]
+    fn finds_constants_and_long_lengths() {
+        // A two byte PkgLength, a word and a One.
+        let aml = b"\x08_S5_\x12\x40\x00\x02\x0b\x07\x00\x01";
+        assert_eq!(find_s5(aml), Some(SleepType { a: 7, b: 1 }));
+    }
+
+    #[test_case]
+    fn ignores_references_to_s5() {
+        // A method named _S5_ rather than a Name declaration.
+        let aml = b"\x14_S5_\x12\x04\x01\x01";
+        assert_eq!(find_s5(aml), None);
+    }
+}
@@ -0,0 +1,135 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The Fixed ACPI Description Table, which describes the power management hardware.
+
+use x86_64::PhysAddr;
+
+use crate::trampoline::acpi::{Bytes, GenericAddress, RegisterSpace};
+
+const TMR_VAL_EXT: u32 = 1 << 8;
+const RESET_REG_SUP: u32 = 1 << 10;
+const HW_REDUCED_ACPI: u32 = 1 << 20;
+
+/// IA-PC boot architecture flags.
+const LEGACY_DEVICES: u16 = 1 << 0;
+const HAS_8042: u16 = 1 << 1;
+
+#[derive(Debug, Clone)]
+pub struct Fadt {
+    pub dsdt: PhysAddr,
+    pub sci_interrupt: u16,
+    pub smi_command_port: u32,
+    pub acpi_enable: u8,
+    pub acpi_disable: u8,
+    pub pm1a_event_block: Option<GenericAddress>,
+    pub pm1b_event_block: Option<GenericAddress>,
+    pub pm1a_control_block: Option<GenericAddress>,
+    pub pm1b_control_block: Option<GenericAddress>,
+    pub pm_timer_block: Option<GenericAddress>,
+    /// The PM timer counts with 32 bits instead of 24.
+    pub pm_timer_32bit: bool,
+    /// CMOS register holding the century, if the RTC has one.
+    pub century_register: Option<u8>,
+    pub has_legacy_devices: bool,
+    pub has_8042: bool,
+    pub hardware_reduced: bool,
+    /// Writing `reset_value` here resets the machine.
+    pub reset_register: Option<GenericAddress>,
+    pub reset_value: u8,
+}
+
+/// Prefers the 64 bit `X_` variant of a register block and falls back to the legacy I/O port.
+fn register(table: Bytes, legacy_offset: usize, extended_offset: usize) -> Option<GenericAddress> {
+    table.generic_address(extended_offset).or_else(|| {
+        let port = table.u32(legacy_offset);
+        (port != 0).then_some(GenericAddress {
+            space: RegisterSpace::Io,
+            bit_width: 0,
+            bit_offset: 0,
+            access_size: 0,
+            address: port as u64,
+        })
+    })
+}
+
+impl Fadt {
+    pub(super) fn parse(table: Bytes) -> Self {
+        let flags = table.u32(112);
+        let boot_arch = table.u16(109);
+
+        let dsdt = PhysAddr::new(table.u32(40) as u64);
+        let dsdt = match table.u64(140) {
+            0 => dsdt,
+            x_dsdt => PhysAddr::try_new(x_dsdt).unwrap_or_else(|_| {
+                log::warn!("Ignoring the FADT's invalid X_DSDT {x_dsdt:#x}.");
+                dsdt
+            }),
+        };
+
+        Self {
+            dsdt,
+            sci_interrupt: table.u16(46),
+            smi_command_port: table.u32(48),
+            acpi_enable: table.u8(52),
+            acpi_disable: table.u8(53),
+            pm1a_event_block: register(table, 56, 148),
+            pm1b_event_block: register(table, 60, 160),
+            pm1a_control_block: register(table, 64, 172),
+            pm1b_control_block: register(table, 68, 184),
+            pm_timer_block: register(table, 76, 208),
+            pm_timer_32bit: flags & TMR_VAL_EXT != 0,
+            century_register: Some(table.u8(108)).filter(|&register| register != 0),
+            has_legacy_devices: boot_arch & LEGACY_DEVICES != 0,
+            has_8042: boot_arch & HAS_8042 != 0,
+            hardware_reduced: flags & HW_REDUCED_ACPI != 0,
+            reset_register: table
+                .generic_address(116)
+                .filter(|_| flags & RESET_REG_SUP != 0),
+            reset_value: table.u8(128),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn reads_an_acpi_1_table() {
+        let mut table = [0; 116];
+        table[40..44].copy_from_slice(&0x7FE0_0000u32.to_le_bytes());
+        table[64..68].copy_from_slice(&0x604u32.to_le_bytes());
+        table[109..111].copy_from_slice(&HAS_8042.to_le_bytes());
+
+        let fadt = Fadt::parse(Bytes(&table));
+        assert_eq!(fadt.dsdt, PhysAddr::new(0x7FE0_0000));
+        assert_eq!(
+            fadt.pm1a_control_block.map(|block| block.address),
+            Some(0x604)
+        );
+        assert_eq!(fadt.pm1b_control_block, None);
+        assert!(fadt.has_8042);
+        // The reset register came with ACPI 2.0, and the table ends before it.
+        assert_eq!(fadt.reset_register, None);
+    }
+
+    #[test_case]
+    fn reads_the_reset_register_and_skips_an_invalid_x_dsdt() {
+        let mut table = [0; 276];
+        table[40..44].copy_from_slice(&0x7FE0_0000u32.to_le_bytes());
+        table[112..116].copy_from_slice(&RESET_REG_SUP.to_le_bytes());
+        // Reset by writing 6 to I/O port 0xCF9.
+        table[116] = 1;
+        table[120..128].copy_from_slice(&0xCF9u64.to_le_bytes());
+        table[128] = 6;
+        table[140..148].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let fadt = Fadt::parse(Bytes(&table));
+        assert_eq!(fadt.dsdt, PhysAddr::new(0x7FE0_0000));
+        let reset = fadt.reset_register.unwrap();
+        assert_eq!((reset.space, reset.address), (RegisterSpace::Io, 0xCF9));
+        assert_eq!(fadt.reset_value, 6);
+    }
+}
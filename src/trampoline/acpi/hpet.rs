@@ -0,0 +1,74 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The HPET description table, which says where the High Precision Event Timer's registers are.
+
+use x86_64::PhysAddr;
+
+use crate::trampoline::acpi::{Bytes, RegisterSpace};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Hpet {
+    pub address: PhysAddr,
+    pub hpet_number: u8,
+    /// Smallest tick that periodic timers can use without losing interrupts.
+    pub minimum_tick: u16,
+    pub comparators: u8,
+    pub counter_64bit: bool,
+    pub legacy_replacement: bool,
+    pub pci_vendor_id: u16,
+}
+
+impl Hpet {
+    /// Returns `None` if the registers aren't memory mapped, which the HPET specification requires,
+    /// or the address is invalid.
+    pub(super) fn parse(table: Bytes) -> Option<Self> {
+        let block_id = table.u32(36);
+        let address = table
+            .generic_address(40)
+            .filter(|address| address.space == RegisterSpace::Memory)?;
+
+        Some(Self {
+            address: PhysAddr::try_new(address.address).ok()?,
+            hpet_number: table.u8(52),
+            minimum_tick: table.u16(53),
+            comparators: ((block_id >> 8) & 0x1F) as u8 + 1,
+            counter_64bit: block_id & (1 << 13) != 0,
+            legacy_replacement: block_id & (1 << 15) != 0,
+            pci_vendor_id: (block_id >> 16) as u16,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(space: u8, address: u64) -> [u8; 56] {
+        let mut table = [0; 56];
+        // Three comparators and a 64 bit counter from vendor 0x8086.
+        table[36..40].copy_from_slice(&0x8086_2201u32.to_le_bytes());
+        table[40] = space;
+        table[44..52].copy_from_slice(&address.to_le_bytes());
+        table[53..55].copy_from_slice(&0x80u16.to_le_bytes());
+        table
+    }
+
+    #[test_case]
+    fn reads_the_block_id() {
+        let hpet = Hpet::parse(Bytes(&table(0, 0xFED0_0000))).unwrap();
+        assert_eq!(hpet.address, PhysAddr::new(0xFED0_0000));
+        assert_eq!(hpet.comparators, 3);
+        assert!(hpet.counter_64bit);
+        assert!(!hpet.legacy_replacement);
+        assert_eq!(hpet.pci_vendor_id, 0x8086);
+        assert_eq!(hpet.minimum_tick, 0x80);
+    }
+
+    #[test_case]
+    fn needs_a_valid_memory_address() {
+        assert!(Hpet::parse(Bytes(&table(1, 0xFED0_0000))).is_none());
+        assert!(Hpet::parse(Bytes(&table(0, u64::MAX))).is_none());
+    }
+}
@@ -0,0 +1,282 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The Multiple APIC Description Table, which lists processors and interrupt controllers.
+
+use alloc::vec::Vec;
+
+use x86_64::PhysAddr;
+
+use crate::trampoline::acpi::Bytes;
+
+const ENTRIES_OFFSET: usize = 44;
+const PCAT_COMPAT: u32 = 1 << 0;
+
+const LOCAL_APIC: u8 = 0;
+const IO_APIC: u8 = 1;
+const INTERRUPT_OVERRIDE: u8 = 2;
+const LOCAL_APIC_NMI: u8 = 4;
+const LOCAL_APIC_ADDRESS_OVERRIDE: u8 = 5;
+const LOCAL_X2APIC: u8 = 9;
+const LOCAL_X2APIC_NMI: u8 = 10;
+
+const CPU_ENABLED: u32 = 1 << 0;
+const CPU_ONLINE_CAPABLE: u32 = 1 << 1;
+
+/// A processor, described by its local APIC.
+#[derive(Debug, Clone, Copy)]
+pub struct Cpu {
+    pub processor_uid: u32,
+    pub apic_id: u32,
+    pub enabled: bool,
+    /// A disabled processor that can be brought online later.
+    pub online_capable: bool,
+}
+
+impl Cpu {
+    pub fn usable(&self) -> bool {
+        self.enabled || self.online_capable
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IoApic {
+    pub id: u8,
+    pub address: PhysAddr,
+    /// The first global system interrupt this IO APIC handles.
+    pub gsi_base: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polarity {
+    /// Whatever the bus normally uses, active high for ISA.
+    BusDefault,
+    ActiveHigh,
+    ActiveLow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// Whatever the bus normally uses, edge for ISA.
+    BusDefault,
+    Edge,
+    Level,
+}
+
+/// Decodes the MPS INTI flags used by overrides and NMI entries.
+fn inti_flags(flags: u16) -> (Polarity, Trigger) {
+    let polarity = match flags & 0b11 {
+        0b01 => Polarity::ActiveHigh,
+        0b11 => Polarity::ActiveLow,
+        _ => Polarity::BusDefault,
+    };
+    let trigger = match (flags >> 2) & 0b11 {
+        0b01 => Trigger::Edge,
+        0b11 => Trigger::Level,
+        _ => Trigger::BusDefault,
+    };
+
+    (polarity, trigger)
+}
+
+/// An ISA interrupt that is wired to a different global system interrupt than its number.
+#[derive(Debug, Clone, Copy)]
+pub struct InterruptOverride {
+    pub irq: u8,
+    pub gsi: u32,
+    pub polarity: Polarity,
+    pub trigger: Trigger,
+}
+
+/// A local APIC input that is connected to NMI.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalApicNmi {
+    /// `None` means every processor.
+    pub processor_uid: Option<u32>,
+    pub lint: u8,
+    pub polarity: Polarity,
+    pub trigger: Trigger,
+}
+
+#[derive(Debug, Clone)]
+pub struct Madt {
+    pub local_apic_address: PhysAddr,
+    /// The machine also has a pair of 8259 PICs, which must be masked to use the APICs.
+    pub has_8259: bool,
+    pub cpus: Vec<Cpu>,
+    pub io_apics: Vec<IoApic>,
+    pub overrides: Vec<InterruptOverride>,
+    pub nmis: Vec<LocalApicNmi>,
+}
+
+impl Madt {
+    pub(super) fn parse(table: Bytes) -> Self {
+        let mut madt = Self {
+            local_apic_address: PhysAddr::new(table.u32(36) as u64),
+            has_8259: table.u32(40) & PCAT_COMPAT != 0,
+            cpus: Vec::new(),
+            io_apics: Vec::new(),
+            overrides: Vec::new(),
+            nmis: Vec::new(),
+        };
+
+        let mut offset = ENTRIES_OFFSET;
+        while offset + 2 <= table.0.len() {
+            let entry_type = table.u8(offset);
+            let length = table.u8(offset + 1) as usize;
+            if length < 2 {
+                log::warn!("Malformed MADT entry at offset {offset}.");
+                break;
+            }
+            let entry = Bytes(&table.0[offset..(offset + length).min(table.0.len())]);
+
+            match entry_type {
+                LOCAL_APIC => madt.cpus.push(Cpu {
+                    processor_uid: entry.u8(2) as u32,
+                    apic_id: entry.u8(3) as u32,
+                    enabled: entry.u32(4) & CPU_ENABLED != 0,
+                    online_capable: entry.u32(4) & CPU_ONLINE_CAPABLE != 0,
+                }),
+                LOCAL_X2APIC => madt.cpus.push(Cpu {
+                    processor_uid: entry.u32(12),
+                    apic_id: entry.u32(4),
+                    enabled: entry.u32(8) & CPU_ENABLED != 0,
+                    online_capable: entry.u32(8) & CPU_ONLINE_CAPABLE != 0,
+                }),
+                IO_APIC => madt.io_apics.push(IoApic {
+                    id: entry.u8(2),
+                    address: PhysAddr::new(entry.u32(4) as u64),
+                    gsi_base: entry.u32(8),
+                }),
+                INTERRUPT_OVERRIDE => {
+                    let (polarity, trigger) = inti_flags(entry.u16(8));
+                    madt.overrides.push(InterruptOverride {
+                        irq: entry.u8(3),
+                        gsi: entry.u32(4),
+                        polarity,
+                        trigger,
+                    });
+                }
+                LOCAL_APIC_NMI => {
+                    let (polarity, trigger) = inti_flags(entry.u16(3));
+                    madt.nmis.push(LocalApicNmi {
+                        processor_uid: Some(entry.u8(2) as u32).filter(|&uid| uid != 0xFF),
+                        lint: entry.u8(5),
+                        polarity,
+                        trigger,
+                    });
+                }
+                LOCAL_X2APIC_NMI => {
+                    let (polarity, trigger) = inti_flags(entry.u16(2));
+                    madt.nmis.push(LocalApicNmi {
+                        processor_uid: Some(entry.u32(4)).filter(|&uid| uid != u32::MAX),
+                        lint: entry.u8(8),
+                        polarity,
+                        trigger,
+                    });
+                }
+                LOCAL_APIC_ADDRESS_OVERRIDE => match PhysAddr::try_new(entry.u64(4)) {
+                    Ok(address) => madt.local_apic_address = address,
+                    Err(_) => log::warn!(
+                        "Ignoring the MADT's invalid local APIC address {:#x}.",
+                        entry.u64(4)
+                    ),
+                },
+                _ => {}
+            }
+
+            offset += length;
+        }
+
+        madt
+    }
+
+    /// The global system interrupt an ISA IRQ arrives on, taking overrides into account.
+    pub fn isa_irq(&self, irq: u8) -> InterruptOverride {
+        self.overrides
+            .iter()
+            .find(|o| o.irq == irq)
+            .copied()
+            .unwrap_or(InterruptOverride {
+                irq,
+                gsi: irq as u32,
+                polarity: Polarity::BusDefault,
+                trigger: Trigger::BusDefault,
+            })
+    }
+
+    /// The IO APIC that handles `gsi`, if any.
+    pub fn io_apic_for(&self, gsi: u32) -> Option<&IoApic> {
+        self.io_apics
+            .iter()
+            .filter(|io_apic| io_apic.gsi_base <= gsi)
+            .max_by_key(|io_apic| io_apic.gsi_base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn table(entries: &[&[u8]]) -> Vec<u8> {
+        let mut table = vec![0; ENTRIES_OFFSET];
+        table[36..40].copy_from_slice(&0xFEE0_0000u32.to_le_bytes());
+        table[40..44].copy_from_slice(&PCAT_COMPAT.to_le_bytes());
+        for entry in entries {
+            table.extend_from_slice(entry);
+        }
+        table
+    }
+
+    #[test_case]
+    fn reads_processors_and_interrupt_controllers() {
+        let table = table(&[
+            // Processor 0 with APIC ID 0, enabled.
+            &[LOCAL_APIC, 8, 0, 0, 1, 0, 0, 0],
+            // Processor 1 with APIC ID 1, disabled.
+            &[LOCAL_APIC, 8, 1, 1, 0, 0, 0, 0],
+            // IO APIC 2 at 0xFEC00000 for GSIs from 0.
+            &[IO_APIC, 12, 2, 0, 0, 0, 0xC0, 0xFE, 0, 0, 0, 0],
+            // IRQ 0 arrives on GSI 2.
+            &[INTERRUPT_OVERRIDE, 10, 0, 0, 2, 0, 0, 0, 0, 0],
+            // LINT1 of every processor, active high and edge triggered.
+            &[LOCAL_APIC_NMI, 6, 0xFF, 0b0101, 0, 1],
+        ]);
+
+        let madt = Madt::parse(Bytes(&table));
+        assert_eq!(madt.local_apic_address, PhysAddr::new(0xFEE0_0000));
+        assert!(madt.has_8259);
+        assert_eq!(madt.cpus.iter().filter(|cpu| cpu.usable()).count(), 1);
+        assert_eq!(
+            madt.io_apic_for(9).map(|io_apic| io_apic.address),
+            Some(PhysAddr::new(0xFEC0_0000))
+        );
+        assert_eq!(madt.isa_irq(0).gsi, 2);
+        assert_eq!(madt.isa_irq(1).gsi, 1);
+        let nmi = madt.nmis[0];
+        assert_eq!(nmi.processor_uid, None);
+        assert_eq!(
+            (nmi.lint, nmi.polarity, nmi.trigger),
+            (1, Polarity::ActiveHigh, Trigger::Edge)
+        );
+    }
+
+    #[test_case]
+    fn ignores_an_invalid_local_apic_address_override() {
+        let mut entry = [0; 12];
+        entry[..2].copy_from_slice(&[LOCAL_APIC_ADDRESS_OVERRIDE, 12]);
+        entry[4..].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let madt = Madt::parse(Bytes(&table(&[&entry])));
+        assert_eq!(madt.local_apic_address, PhysAddr::new(0xFEE0_0000));
+    }
+
+    #[test_case]
+    fn stops_at_a_malformed_entry() {
+        let table = table(&[&[LOCAL_APIC, 0], &[LOCAL_APIC, 8, 0, 0, 1, 0, 0, 0]]);
+        assert!(Madt::parse(Bytes(&table)).cpus.is_empty());
+    }
+}
@@ -0,0 +1,425 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Discovery of the ACPI tables that describe the machine: processors and interrupt controllers
+//! (MADT), power management registers (FADT), the HPET and the sleep type for turning the machine
+//! off (DSDT).
+//!
+//! Everything is copied out of the firmware's tables while booting, because ACPI reclaimable
+//! memory is handed to the frame allocator once [`init`] is done.
+
+pub mod aml;
+pub mod fadt;
+pub mod hpet;
+pub mod madt;
+
+use alloc::vec::Vec;
+use core::{fmt, str};
+
+use spin::Once;
+use x86_64::{
+    PhysAddr, VirtAddr,
+    structures::paging::{OffsetPageTable, PageSize, PageTableFlags, Size4KiB, Translate},
+};
+
+use crate::trampoline::{
+    acpi::{aml::SleepType, fadt::Fadt, hpet::Hpet, madt::Madt},
+    limine_requests::RSDP_REQUEST,
+    memory::{
+        HHDM_OFFSET,
+        allocators::ProperFrameAllocator,
+        error::{MemoryError, MemoryRegion},
+        map_physical,
+    },
+};
+
+const SDT_HEADER_SIZE: usize = 36;
+const RSDP_V1_SIZE: usize = 20;
+const RSDP_V2_SIZE: usize = 36;
+
+#[derive(Debug)]
+pub enum AcpiError {
+    /// Limine didn't find an RSDP, e.g. because the machine has no ACPI.
+    NoRsdp,
+    /// The RSDP's signature or checksum is wrong.
+    InvalidRsdp,
+    /// The table that should be at an address has a different signature.
+    UnexpectedSignature { expected: [u8; 4], found: [u8; 4] },
+    /// A table's bytes don't add up to zero.
+    BadChecksum([u8; 4]),
+    /// A table outside our HHDM couldn't be mapped.
+    Map(MemoryError),
+}
+
+impl From<MemoryError> for AcpiError {
+    fn from(error: MemoryError) -> Self {
+        Self::Map(error)
+    }
+}
+
+impl fmt::Display for AcpiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoRsdp => f.write_str("the bootloader found no RSDP"),
+            Self::InvalidRsdp => f.write_str("the RSDP is invalid"),
+            Self::UnexpectedSignature { expected, found } => write!(
+                f,
+                "expected a {} table but found {}",
+                signature_str(expected),
+                signature_str(found)
+            ),
+            Self::BadChecksum(signature) => {
+                write!(
+                    f,
+                    "the {} table has a bad checksum",
+                    signature_str(signature)
+                )
+            }
+            Self::Map(error) => write!(f, "could not map a table: {error}"),
+        }
+    }
+}
+
+/// Prints a table signature, which should be four ASCII letters.
+pub fn signature_str(signature: &[u8; 4]) -> &str {
+    str::from_utf8(signature).unwrap_or("????")
+}
+
+/// Where an ACPI register lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterSpace {
+    Memory,
+    Io,
+    PciConfig,
+    Other(u8),
+}
+
+/// ACPI's Generic Address Structure, which describes a register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenericAddress {
+    pub space: RegisterSpace,
+    pub bit_width: u8,
+    pub bit_offset: u8,
+    pub access_size: u8,
+    pub address: u64,
+}
+
+/// The raw bytes of a table. Fields past the end read as zero, which is how ACPI treats fields
+/// that an older revision of a table doesn't have.
+#[derive(Clone, Copy)]
+pub(crate) struct Bytes<'a>(pub &'a [u8]);
+
+impl Bytes<'_> {
+    pub fn array<const N: usize>(&self, offset: usize) -> [u8; N] {
+        let mut array = [0; N];
+        if let Some(bytes) = self.0.get(offset..) {
+            let len = bytes.len().min(N);
+            array[..len].copy_from_slice(&bytes[..len]);
+        }
+        array
+    }
+
+    pub fn u8(&self, offset: usize) -> u8 {
+        self.0.get(offset).copied().unwrap_or(0)
+    }
+
+    pub fn u16(&self, offset: usize) -> u16 {
+        u16::from_le_bytes(self.array(offset))
+    }
+
+    pub fn u32(&self, offset: usize) -> u32 {
+        u32::from_le_bytes(self.array(offset))
+    }
+
+    pub fn u64(&self, offset: usize) -> u64 {
+        u64::from_le_bytes(self.array(offset))
+    }
+
+    /// Reads a Generic Address Structure, which is `None` if the address is zero.
+    pub fn generic_address(&self, offset: usize) -> Option<GenericAddress> {
+        let address = self.u64(offset + 4);
+        if address == 0 {
+            return None;
+        }
+
+        Some(GenericAddress {
+            space: match self.u8(offset) {
+                0 => RegisterSpace::Memory,
+                1 => RegisterSpace::Io,
+                2 => RegisterSpace::PciConfig,
+                other => RegisterSpace::Other(other),
+            },
+            bit_width: self.u8(offset + 1),
+            bit_offset: self.u8(offset + 2),
+            access_size: self.u8(offset + 3),
+            address,
+        })
+    }
+}
+
+fn checksum_ok(bytes: &[u8]) -> bool {
+    bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) == 0
+}
+
+/// A table listed in the RSDT or XSDT.
+#[derive(Debug, Clone, Copy)]
+pub struct TableEntry {
+    pub signature: [u8; 4],
+    pub address: PhysAddr,
+    pub length: u32,
+    pub revision: u8,
+    pub oem_id: [u8; 6],
+}
+
+/// What the firmware told us about the machine.
+#[derive(Debug)]
+pub struct Acpi {
+    pub revision: u8,
+    pub oem_id: [u8; 6],
+    /// Every table with a valid checksum, in the order the root table lists them.
+    pub tables: Vec<TableEntry>,
+    pub madt: Option<Madt>,
+    pub fadt: Option<Fadt>,
+    pub hpet: Option<Hpet>,
+    /// What to write to the PM1 control registers to enter S5, i.e. to turn the machine off.
+    pub s5: Option<SleepType>,
+}
+
+static ACPI: Once<Acpi> = Once::new();
+
+/// The ACPI information found at boot, if the machine has ACPI.
+pub fn get() -> Option<&'static Acpi> {
+    ACPI.get()
+}
+
+/// Reads physical memory through the HHDM, or through a new mapping where the HHDM doesn't reach.
+struct PhysReader<'a, 'b> {
+    offset_page_table: &'a mut OffsetPageTable<'b>,
+    frame_allocator: &'a mut ProperFrameAllocator,
+}
+
+impl PhysReader<'_, '_> {
+    /// The returned slice is only valid while booting, see the module documentation.
+    fn read(&mut self, phys: PhysAddr, size: usize) -> Result<&'static [u8], AcpiError> {
+        let hhdm = VirtAddr::new(phys.as_u64() + HHDM_OFFSET);
+        let first_page = hhdm.align_down(Size4KiB::SIZE);
+        let in_hhdm = (first_page.as_u64()..hhdm.as_u64() + size as u64)
+            .step_by(Size4KiB::SIZE as usize)
+            .all(|page| {
+                self.offset_page_table
+                    .translate_addr(VirtAddr::new(page))
+                    .is_some()
+            });
+
+        let virt = if in_hhdm {
+            hhdm
+        } else {
+            // Tables are only read once, so these mappings are never torn down.
+            // SAFETY: ACPI tables are plain memory that nothing else writes to.
+            unsafe {
+                map_physical(
+                    self.offset_page_table,
+                    self.frame_allocator,
+                    phys,
+                    size as u64,
+                    MemoryRegion::Dynamic("ACPI table"),
+                    PageTableFlags::NO_EXECUTE,
+                )?
+            }
+        };
+
+        // SAFETY: The range was checked or made to be mapped above.
+        Ok(unsafe { core::slice::from_raw_parts(virt.as_ptr(), size) })
+    }
+
+    /// Reads a whole table with a standard header and checks its checksum.
+    fn table(&mut self, phys: PhysAddr) -> Result<Bytes<'static>, AcpiError> {
+        let header = Bytes(self.read(phys, SDT_HEADER_SIZE)?);
+        let signature = header.array(0);
+        let length = (header.u32(4) as usize).max(SDT_HEADER_SIZE);
+
+        let table = self.read(phys, length)?;
+        if !checksum_ok(table) {
+            return Err(AcpiError::BadChecksum(signature));
+        }
+
+        Ok(Bytes(table))
+    }
+
+    fn expect_table(
+        &mut self,
+        phys: PhysAddr,
+        expected: &[u8; 4],
+    ) -> Result<Bytes<'static>, AcpiError> {
+        let table = self.table(phys)?;
+        let found = table.array(0);
+        if found != *expected {
+            return Err(AcpiError::UnexpectedSignature {
+                expected: *expected,
+                found,
+            });
+        }
+
+        Ok(table)
+    }
+}
+
+fn discover(reader: &mut PhysReader) -> Result<Acpi, AcpiError> {
+    let rsdp_address = RSDP_REQUEST
+        .get_response()
+        .ok_or(AcpiError::NoRsdp)?
+        .address();
+    let rsdp_address = PhysAddr::new(rsdp_address as u64);
+
+    let rsdp = Bytes(reader.read(rsdp_address, RSDP_V1_SIZE)?);
+    if rsdp.array::<8>(0) != *b"RSD PTR " || !checksum_ok(&rsdp.0[..RSDP_V1_SIZE]) {
+        return Err(AcpiError::InvalidRsdp);
+    }
+
+    let revision = rsdp.u8(15);
+    let oem_id = rsdp.array(9);
+
+    // ACPI 2.0 and later have an XSDT with 64 bit pointers, older firmware only an RSDT.
+    let root = if revision >= 2 {
+        let rsdp = Bytes(reader.read(rsdp_address, RSDP_V2_SIZE)?);
+        if !checksum_ok(rsdp.0) {
+            return Err(AcpiError::InvalidRsdp);
+        }
+        match rsdp.u64(24) {
+            0 => None,
+            xsdt => PhysAddr::try_new(xsdt)
+                .inspect_err(|_| log::warn!("Ignoring the XSDT at invalid address {xsdt:#x}."))
+                .ok(),
+        }
+    } else {
+        None
+    };
+
+    let (root, pointer_size) = match root {
+        Some(xsdt) => (reader.expect_table(xsdt, b"XSDT")?, 8),
+        None => (
+            reader.expect_table(PhysAddr::new(rsdp.u32(16) as u64), b"RSDT")?,
+            4,
+        ),
+    };
+
+    let mut acpi = Acpi {
+        revision,
+        oem_id,
+        tables: Vec::new(),
+        madt: None,
+        fadt: None,
+        hpet: None,
+        s5: None,
+    };
+
+    for offset in (SDT_HEADER_SIZE..root.0.len()).step_by(pointer_size) {
+        let address = match pointer_size {
+            8 => root.u64(offset),
+            _ => root.u32(offset) as u64,
+        };
+
+        let Ok(address) = PhysAddr::try_new(address) else {
+            log::warn!("Skipping ACPI table at invalid address {address:#x}.");
+            continue;
+        };
+
+        let table = match reader.table(address) {
+            Ok(table) => table,
+            Err(error) => {
+                log::warn!("Skipping ACPI table at {:#x}: {error}.", address.as_u64());
+                continue;
+            }
+        };
+
+        let signature = table.array(0);
+        acpi.tables.push(TableEntry {
+            signature,
+            address,
+            length: table.0.len() as u32,
+            revision: table.u8(8),
+            oem_id: table.array(10),
+        });
+
+        match &signature {
+            b"APIC" => acpi.madt = Some(Madt::parse(table)),
+            b"FACP" => acpi.fadt = Some(Fadt::parse(table)),
+            b"HPET" => acpi.hpet = Hpet::parse(table),
+            _ => {}
+        }
+    }
+
+    if let Some(fadt) = &acpi.fadt {
+        match reader.expect_table(fadt.dsdt, b"DSDT") {
+            Ok(dsdt) => acpi.s5 = aml::find_s5(&dsdt.0[SDT_HEADER_SIZE..]),
+            Err(error) => log::warn!("Can't read the DSDT: {error}."),
+        }
+    }
+
+    Ok(acpi)
+}
+
+/// Finds and parses the ACPI tables. Machines without usable ACPI tables keep working without
+/// them, so failures are only logged.
+///
+/// The frame allocator holds ACPI reclaimable memory back until this has run, see
+/// [`ProperFrameAllocator::reclaim_acpi_memory`].
+pub fn init(offset_page_table: &mut OffsetPageTable, frame_allocator: &mut ProperFrameAllocator) {
+    let mut reader = PhysReader {
+        offset_page_table,
+        frame_allocator,
+    };
+
+    match discover(&mut reader) {
+        Ok(acpi) => {
+            log::info!(
+                "ACPI {} from {}: {} tables",
+                acpi.revision,
+                str::from_utf8(&acpi.oem_id).unwrap_or("?").trim_end(),
+                acpi.tables.len()
+            );
+            if let Some(madt) = &acpi.madt {
+                log::info!(
+                    "MADT: {} CPUs, {} IO APICs, {} interrupt overrides",
+                    madt.cpus.iter().filter(|cpu| cpu.usable()).count(),
+                    madt.io_apics.len(),
+                    madt.overrides.len()
+                );
+            }
+            ACPI.call_once(|| acpi);
+        }
+        Err(error) => log::warn!("No ACPI: {error}."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn reads_little_endian_fields_and_zero_past_the_end() {
+        let bytes = Bytes(&[0x34, 0x12, 0xAA]);
+        assert_eq!(bytes.u16(0), 0x1234);
+        assert_eq!(bytes.u32(1), 0xAA12);
+        assert_eq!(bytes.u8(3), 0);
+        assert_eq!(bytes.u64(8), 0);
+    }
+
+    #[test_case]
+    fn reads_generic_addresses() {
+        // Byte wide I/O port 0x64.
+        let bytes = Bytes(&[1, 8, 0, 1, 0x64, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            bytes.generic_address(0),
+            Some(GenericAddress {
+                space: RegisterSpace::Io,
+                bit_width: 8,
+                bit_offset: 0,
+                access_size: 1,
+                address: 0x64,
+            })
+        );
+        assert_eq!(Bytes(&[1, 8, 0, 1]).generic_address(0), None);
+    }
+}
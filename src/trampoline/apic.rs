@@ -0,0 +1,156 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Each CPU's local APIC, used to wake up idle processors with inter-processor interrupts. Device
+//! interrupts still go through the 8259 PICs to the boot processor.
+//!
+//! Only the memory mapped xAPIC interface is supported. Every CPU sees its own local APIC at the
+//! same address, so a single mapping serves all of them.
+
+use core::ptr;
+
+use spin::Once;
+use x86_64::{
+    PhysAddr, VirtAddr,
+    instructions::interrupts,
+    registers::model_specific::Msr,
+    structures::paging::{OffsetPageTable, PageTableFlags},
+};
+
+use crate::trampoline::{
+    acpi,
+    memory::{allocators::ProperFrameAllocator, error::MemoryRegion, map_physical},
+};
+
+/// Sent to a processor to make it check for new work.
+pub const WAKE_VECTOR: u8 = 0xF0;
+/// Delivered instead of an interrupt that went away before it could be handled. Needs no EOI.
+pub const SPURIOUS_VECTOR: u8 = 0xFF;
+
+const IA32_APIC_BASE: u32 = 0x1B;
+const X2APIC_ENABLE: u64 = 1 << 10;
+const APIC_BASE_MASK: u64 = 0xF_FFFF_F000;
+
+const ID: usize = 0x020;
+const END_OF_INTERRUPT: usize = 0x0B0;
+const SPURIOUS_INTERRUPT: usize = 0x0F0;
+const INTERRUPT_COMMAND_LOW: usize = 0x300;
+const INTERRUPT_COMMAND_HIGH: usize = 0x310;
+const REGISTERS_SIZE: u64 = 0x400;
+
+const APIC_SOFTWARE_ENABLE: u32 = 1 << 8;
+const DELIVERY_MODE_NMI: u32 = 0b100 << 8;
+const DELIVERY_PENDING: u32 = 1 << 12;
+const LEVEL_ASSERT: u32 = 1 << 14;
+const ALL_EXCLUDING_SELF: u32 = 0b11 << 18;
+
+/// How often to check whether the previous IPI went out before sending an NMI anyway.
+const NMI_DELIVERY_ATTEMPTS: usize = 100_000;
+
+static REGISTERS: Once<VirtAddr> = Once::new();
+
+fn register(offset: usize) -> Option<*mut u32> {
+    REGISTERS
+        .get()
+        .map(|base| (*base + offset as u64).as_mut_ptr())
+}
+
+fn read(offset: usize) -> u32 {
+    // SAFETY: The registers were mapped by init and are only accessed 32 bits at a time.
+    register(offset).map_or(0, |register| unsafe { ptr::read_volatile(register) })
+}
+
+fn write(offset: usize, value: u32) {
+    if let Some(register) = register(offset) {
+        // SAFETY: As in read.
+        unsafe { ptr::write_volatile(register, value) };
+    }
+}
+
+/// Whether IPIs can be sent, i.e. [`init`] found and mapped the local APIC.
+pub fn is_available() -> bool {
+    REGISTERS.get().is_some()
+}
+
+/// Maps the local APIC's registers and enables the BSP's. Without them processors can't be woken
+/// up, so they have to poll for work instead.
+pub fn init(offset_page_table: &mut OffsetPageTable, frame_allocator: &mut ProperFrameAllocator) {
+    // SAFETY: IA32_APIC_BASE exists on every x86_64 CPU.
+    let base = unsafe { Msr::new(IA32_APIC_BASE).read() };
+    if base & X2APIC_ENABLE != 0 {
+        log::warn!("The local APIC is in x2APIC mode, which isn't supported.");
+        return;
+    }
+
+    let phys = acpi::get()
+        .and_then(|acpi| acpi.madt.as_ref())
+        .map_or(PhysAddr::new(base & APIC_BASE_MASK), |madt| {
+            madt.local_apic_address
+        });
+
+    // SAFETY: The local APIC's registers are MMIO that nothing else maps.
+    let mapped = unsafe {
+        map_physical(
+            offset_page_table,
+            frame_allocator,
+            phys,
+            REGISTERS_SIZE,
+            MemoryRegion::Dynamic("local APIC"),
+            PageTableFlags::WRITABLE | PageTableFlags::NO_CACHE | PageTableFlags::NO_EXECUTE,
+        )
+    };
+
+    match mapped {
+        Ok(registers) => {
+            REGISTERS.call_once(|| registers);
+            enable();
+        }
+        Err(error) => log::warn!("Can't map the local APIC: {error}."),
+    }
+}
+
+/// The current CPU's APIC ID.
+pub fn id() -> u32 {
+    read(ID) >> 24
+}
+
+/// Lets the current CPU's local APIC accept interrupts.
+pub fn enable() {
+    write(
+        SPURIOUS_INTERRUPT,
+        APIC_SOFTWARE_ENABLE | SPURIOUS_VECTOR as u32,
+    );
+}
+
+pub fn end_of_interrupt() {
+    write(END_OF_INTERRUPT, 0);
+}
+
+/// Sends interrupt `vector` to the CPU with APIC ID `apic_id`.
+pub fn send_ipi(apic_id: u32, vector: u8) {
+    // Both halves of the command have to be written without another IPI being sent in between.
+    interrupts::without_interrupts(|| {
+        while read(INTERRUPT_COMMAND_LOW) & DELIVERY_PENDING != 0 {
+            core::hint::spin_loop();
+        }
+        write(INTERRUPT_COMMAND_HIGH, apic_id << 24);
+        write(INTERRUPT_COMMAND_LOW, LEVEL_ASSERT | vector as u32);
+    });
+}
+
+/// Sends an NMI to every CPU but the current one. Only waits so long for the previous IPI to go
+/// out, since this is used while panicking.
+pub fn send_nmi_to_others() {
+    for _ in 0..NMI_DELIVERY_ATTEMPTS {
+        if read(INTERRUPT_COMMAND_LOW) & DELIVERY_PENDING == 0 {
+            break;
+        }
+        core::hint::spin_loop();
+    }
+    // The destination shorthand leaves the high half unused.
+    write(
+        INTERRUPT_COMMAND_LOW,
+        ALL_EXCLUDING_SELF | LEVEL_ASSERT | DELIVERY_MODE_NMI,
+    );
+}
@@ -0,0 +1,322 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Time since boot, counted by the timestamp counter.
+//!
+//! The TSC's frequency isn't something the CPU reports reliably, so it is measured while booting
+//! against the HPET, or the PIT on machines without one. Until then every [`Duration`] reads as
+//! zero.
+
+use core::{
+    arch::x86_64::{__cpuid, _rdtsc},
+    ops::{Add, Sub},
+    ptr,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use spin::Once;
+use x86_64::{
+    VirtAddr,
+    instructions::port::Port,
+    structures::paging::{OffsetPageTable, PageTableFlags},
+};
+
+use crate::trampoline::{
+    acpi::{self, hpet::Hpet},
+    memory::{
+        allocators::ProperFrameAllocator,
+        error::{MemoryError, MemoryRegion},
+        map_physical, unmap_physical,
+    },
+};
+
+/// How long the TSC is measured for. Longer is more accurate but slows down booting.
+const CALIBRATION_MS: u64 = 10;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+const FEMTOS_PER_SEC: u64 = 1_000_000_000_000_000;
+
+const HPET_CAPABILITIES: usize = 0x000;
+const HPET_CONFIG: usize = 0x010;
+const HPET_MAIN_COUNTER: usize = 0x0F0;
+const HPET_REGISTERS_SIZE: u64 = 0x400;
+const HPET_ENABLE: u64 = 1 << 0;
+
+const PIT_FREQUENCY: u64 = 1_193_182;
+const PIT_CHANNEL_2: u16 = 0x42;
+const PIT_COMMAND: u16 = 0x43;
+/// Channel 2, low then high byte, mode 0 (interrupt on terminal count).
+const PIT_CHANNEL_2_ONE_SHOT: u8 = 0b1011_0000;
+/// Controls the gate of PIT channel 2 and reports its output.
+const NMI_STATUS_CONTROL: u16 = 0x61;
+const PIT_2_GATE: u8 = 1 << 0;
+const SPEAKER_ENABLE: u8 = 1 << 1;
+const PIT_2_OUTPUT: u8 = 1 << 5;
+
+static BOOT_TSC: AtomicU64 = AtomicU64::new(0);
+static TSC_HZ: Once<u64> = Once::new();
+static INVARIANT: AtomicBool = AtomicBool::new(false);
+
+fn rdtsc() -> u64 {
+    // SAFETY: rdtsc is available on every x86_64 CPU.
+    unsafe { _rdtsc() }
+}
+
+/// Converts TSC ticks to time, or zero before calibration.
+fn ticks_to_duration(ticks: u64) -> Duration {
+    match TSC_HZ.get() {
+        Some(&hz) => Duration::new(
+            ticks / hz,
+            ((ticks % hz) as u128 * NANOS_PER_SEC as u128 / hz as u128) as u32,
+        ),
+        None => Duration::ZERO,
+    }
+}
+
+fn duration_to_ticks(duration: Duration) -> Option<u64> {
+    let hz = *TSC_HZ.get()?;
+    let ticks = duration.as_nanos() * hz as u128 / NANOS_PER_SEC as u128;
+    ticks.try_into().ok()
+}
+
+/// A point in time, like `std::time::Instant`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(u64);
+
+impl Instant {
+    pub fn now() -> Self {
+        Self(rdtsc())
+    }
+
+    /// The time from `earlier` to `self`, or zero if `earlier` is later.
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        ticks_to_duration(self.0.saturating_sub(earlier.0))
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self)
+    }
+
+    /// `None` if the result would overflow or the clock isn't calibrated yet.
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_add(duration_to_ticks(duration)?).map(Self)
+    }
+
+    /// `None` if the result would underflow or the clock isn't calibrated yet.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_sub(duration_to_ticks(duration)?).map(Self)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, duration: Duration) -> Instant {
+        self.checked_add(duration)
+            .expect("Overflow when adding a duration to an instant.")
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, duration: Duration) -> Instant {
+        self.checked_sub(duration)
+            .expect("Overflow when subtracting a duration from an instant.")
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+/// Time since [`start`] was called early during boot.
+pub fn since_boot() -> Duration {
+    Instant::now().duration_since(Instant(BOOT_TSC.load(Ordering::Relaxed)))
+}
+
+/// The measured TSC frequency in Hz, once calibrated.
+pub fn tsc_frequency() -> Option<u64> {
+    TSC_HZ.get().copied()
+}
+
+/// Whether the TSC ticks at a constant rate regardless of power states. If it doesn't, time
+/// runs slow or fast whenever the CPU changes frequency.
+pub fn is_invariant() -> bool {
+    INVARIANT.load(Ordering::Relaxed)
+}
+
+fn tsc_is_invariant() -> bool {
+    let max_extended = __cpuid(0x8000_0000).eax;
+    max_extended >= 0x8000_0007 && __cpuid(0x8000_0007).edx & (1 << 8) != 0
+}
+
+/// Counts TSC ticks while the HPET's main counter runs for [`CALIBRATION_MS`].
+///
+/// # SAFETY
+/// `registers` must be the HPET's mapped register block.
+unsafe fn measure_with_hpet(registers: VirtAddr, hpet: &Hpet) -> u64 {
+    let register = |offset: usize| registers.as_mut_ptr::<u64>().wrapping_byte_add(offset);
+    // SAFETY: The caller guarantees these are the HPET's registers.
+    let read = |offset| unsafe { ptr::read_volatile(register(offset)) };
+
+    let period_fs = read(HPET_CAPABILITIES) >> 32;
+    let config = read(HPET_CONFIG);
+    if config & HPET_ENABLE == 0 {
+        // SAFETY: Starting the main counter doesn't affect anything else.
+        unsafe { ptr::write_volatile(register(HPET_CONFIG), config | HPET_ENABLE) };
+    }
+
+    // A 32 bit counter can wrap while we wait, so only its low half is used.
+    let mask = if hpet.counter_64bit {
+        u64::MAX
+    } else {
+        u32::MAX as u64
+    };
+    let counter = || read(HPET_MAIN_COUNTER) & mask;
+    let target = CALIBRATION_MS * (FEMTOS_PER_SEC / 1000) / period_fs;
+
+    let hpet_start = counter();
+    let tsc_start = rdtsc();
+    let mut hpet_ticks;
+    loop {
+        hpet_ticks = counter().wrapping_sub(hpet_start) & mask;
+        if hpet_ticks >= target {
+            break;
+        }
+        core::hint::spin_loop();
+    }
+    let tsc_ticks = rdtsc() - tsc_start;
+
+    (tsc_ticks as u128 * FEMTOS_PER_SEC as u128 / (hpet_ticks as u128 * period_fs as u128)) as u64
+}
+
+fn calibrate_with_hpet(
+    offset_page_table: &mut OffsetPageTable,
+    frame_allocator: &mut ProperFrameAllocator,
+    hpet: &Hpet,
+) -> Result<u64, MemoryError> {
+    // SAFETY: The HPET's registers are MMIO that nothing else uses yet.
+    let registers = unsafe {
+        map_physical(
+            offset_page_table,
+            frame_allocator,
+            hpet.address,
+            HPET_REGISTERS_SIZE,
+            MemoryRegion::Dynamic("HPET"),
+            PageTableFlags::WRITABLE | PageTableFlags::NO_CACHE | PageTableFlags::NO_EXECUTE,
+        )?
+    };
+
+    // SAFETY: Just mapped above.
+    let hz = unsafe { measure_with_hpet(registers, hpet) };
+
+    // SAFETY: Nothing keeps a reference to the registers.
+    unsafe { unmap_physical(offset_page_table, registers)? };
+
+    Ok(hz)
+}
+
+/// Counts TSC ticks while PIT channel 2 counts down from [`CALIBRATION_MS`], which every PC has
+/// even if it has no HPET.
+fn calibrate_with_pit() -> u64 {
+    let count = PIT_FREQUENCY * CALIBRATION_MS / 1000;
+    let mut control = Port::<u8>::new(NMI_STATUS_CONTROL);
+    let mut command = Port::<u8>::new(PIT_COMMAND);
+    let mut channel = Port::<u8>::new(PIT_CHANNEL_2);
+
+    // SAFETY: Channel 2 only drives the PC speaker, which is kept off.
+    unsafe {
+        let original = control.read();
+        control.write((original & !SPEAKER_ENABLE) | PIT_2_GATE);
+
+        command.write(PIT_CHANNEL_2_ONE_SHOT);
+        channel.write(count as u8);
+        channel.write((count >> 8) as u8);
+
+        let start = rdtsc();
+        while control.read() & PIT_2_OUTPUT == 0 {
+            core::hint::spin_loop();
+        }
+        let ticks = rdtsc() - start;
+
+        control.write(original);
+
+        ticks * PIT_FREQUENCY / count
+    }
+}
+
+/// Marks the point in time that [`since_boot`] counts from. Should be the first thing the kernel
+/// does.
+pub fn start() {
+    BOOT_TSC.store(rdtsc(), Ordering::Relaxed);
+}
+
+/// Measures the TSC frequency and makes Bevy's clock use it. Must be called after
+/// [`acpi::init`] and with interrupts disabled, so that nothing stretches the measurement.
+pub fn init(offset_page_table: &mut OffsetPageTable, frame_allocator: &mut ProperFrameAllocator) {
+    INVARIANT.store(tsc_is_invariant(), Ordering::Relaxed);
+    if !is_invariant() {
+        log::warn!("The TSC isn't invariant, time may drift as the CPU changes frequency.");
+    }
+
+    let hpet = acpi::get().and_then(|acpi| acpi.hpet.as_ref());
+    let (hz, source) =
+        match hpet.map(|hpet| calibrate_with_hpet(offset_page_table, frame_allocator, hpet)) {
+            Some(Ok(hz)) => (hz, "HPET"),
+            Some(Err(error)) => {
+                log::warn!("Can't use the HPET: {error}.");
+                (calibrate_with_pit(), "PIT")
+            }
+            None => (calibrate_with_pit(), "PIT"),
+        };
+
+    TSC_HZ.call_once(|| hz);
+    log::info!(
+        "TSC runs at {}.{:03} MHz, measured against the {source}.",
+        hz / 1_000_000,
+        hz / 1000 % 1000
+    );
+
+    // SAFETY: since_boot is monotonic and valid for the whole life of the kernel.
+    unsafe { bevy::platform::time::Instant::set_elapsed(since_boot) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn time_moves_forward() {
+        let start = Instant::now();
+        let mut previous = start;
+        for _ in 0..1000 {
+            let now = Instant::now();
+            assert!(now >= previous);
+            previous = now;
+        }
+        assert!(previous.duration_since(start) < Duration::from_millis(100));
+        assert_eq!(start.duration_since(previous), Duration::ZERO);
+    }
+
+    #[test_case]
+    fn durations_round_trip() {
+        let start = Instant::now();
+        let later = start + Duration::from_millis(1500);
+        let difference = later - start;
+        assert!(difference.abs_diff(Duration::from_millis(1500)) < Duration::from_micros(1));
+        assert_eq!(later - Duration::from_millis(1500), start);
+    }
+
+    #[test_case]
+    fn frequency_is_plausible() {
+        let hz = tsc_frequency().expect("Calibrated while booting.");
+        assert!((100_000_000..20_000_000_000).contains(&hz));
+    }
+}
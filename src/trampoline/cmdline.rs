@@ -0,0 +1,171 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Boot options passed on the kernel command line.
+//!
+//! The command line is a whitespace separated list of `key=value` options, set with `cmdline:` in
+//! `limine.conf`. Boolean options may also be given as a bare `key`. For example:
+//!
+//! ```text
+//! log=info,wordle_kernel::trampoline=debug theme=latte hard seed=1234 mode=unlimited display=1
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, str::FromStr};
+
+use bevy::ecs::resource::Resource;
+
+use crate::{
+    color::Theme,
+    debug::logger,
+    trampoline::{limine_requests::EXECUTABLE_CMDLINE_REQUEST, rtc},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameMode {
+    /// One word per day, picked from the seed.
+    #[default]
+    Daily,
+    /// A new word every round.
+    Unlimited,
+}
+
+impl FromStr for GameMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "daily" => Ok(Self::Daily),
+            "unlimited" => Ok(Self::Unlimited),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An option that could not be understood. The rest of the command line is still applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CmdlineError {
+    UnknownOption(String),
+    InvalidValue { key: String, value: String },
+    MissingValue(String),
+}
+
+impl fmt::Display for CmdlineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownOption(key) => write!(f, "unknown option `{key}`"),
+            Self::InvalidValue { key, value } => write!(f, "invalid value `{value}` for `{key}`"),
+            Self::MissingValue(key) => write!(f, "option `{key}` needs a value"),
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct BootOptions {
+    /// Filter for the kernel logger, see [`logger`] for the format.
+    pub log_filter: String,
+    pub theme: Theme,
+    pub hard_mode: bool,
+    /// Seed for picking words. `None` means one is derived at runtime.
+    pub seed: Option<u64>,
+    pub mode: GameMode,
+    /// Index of the framebuffer the game is drawn on.
+    pub display: usize,
+}
+
+impl Default for BootOptions {
+    fn default() -> Self {
+        Self {
+            log_filter: logger::DEFAULT_FILTER.to_string(),
+            theme: Theme::default(),
+            hard_mode: false,
+            seed: None,
+            mode: GameMode::default(),
+            display: 0,
+        }
+    }
+}
+
+fn parse_value<T: FromStr>(key: &str, value: Option<&str>) -> Result<T, CmdlineError> {
+    let value = value.ok_or_else(|| CmdlineError::MissingValue(key.to_string()))?;
+
+    value.parse().map_err(|_| CmdlineError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Parses `0x` prefixed hexadecimal as well as decimal seeds.
+fn parse_seed(value: Option<&str>) -> Result<u64, CmdlineError> {
+    let hex = value.and_then(|value| value.strip_prefix("0x"));
+
+    match hex {
+        Some(hex) => u64::from_str_radix(hex, 16).map_err(|_| CmdlineError::InvalidValue {
+            key: "seed".to_string(),
+            value: value.unwrap_or_default().to_string(),
+        }),
+        None => parse_value("seed", value),
+    }
+}
+
+impl BootOptions {
+    /// Parses a command line, returning the options along with every option that was ignored.
+    pub fn parse(cmdline: &str) -> (Self, Vec<CmdlineError>) {
+        let mut options = Self::default();
+        let mut errors = Vec::new();
+
+        for option in cmdline.split_whitespace() {
+            let (key, value) = match option.split_once('=') {
+                Some((key, value)) => (key, Some(value)),
+                None => (option, None),
+            };
+
+            let result = match key {
+                "log" => {
+                    parse_value::<String>(key, value).map(|filter| options.log_filter = filter)
+                }
+                "theme" => parse_value(key, value).map(|theme| options.theme = theme),
+                "hard" => value
+                    .map_or(Ok(true), |_| parse_value(key, value))
+                    .map(|hard| options.hard_mode = hard),
+                "seed" => parse_seed(value).map(|seed| options.seed = Some(seed)),
+                "mode" => parse_value(key, value).map(|mode| options.mode = mode),
+                "display" => parse_value(key, value).map(|display| options.display = display),
+                _ => Err(CmdlineError::UnknownOption(key.to_string())),
+            };
+
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        }
+
+        (options, errors)
+    }
+
+    /// Reads the options from the command line Limine passed to the kernel.
+    pub fn from_limine() -> (Self, Vec<CmdlineError>) {
+        let cmdline = EXECUTABLE_CMDLINE_REQUEST
+            .get_response()
+            .and_then(|response| response.cmdline().to_str().ok())
+            .unwrap_or_default();
+
+        Self::parse(cmdline)
+    }
+
+    /// The seed to pick the answer of round `round` with. Daily mode has one word per day unless a
+    /// seed is given, unlimited mode a new one every round.
+    pub fn answer_seed(&self, round: u64) -> u64 {
+        match self.mode {
+            GameMode::Daily => self.seed.unwrap_or_else(|| rtc::today().days_since_epoch()),
+            // SAFETY: rdtsc is available on every x86_64 CPU.
+            GameMode::Unlimited => self.seed.map_or_else(
+                || unsafe { core::arch::x86_64::_rdtsc() },
+                |seed| seed.wrapping_add(round),
+            ),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Just enough ELF64 parsing to read the kernel's own program headers.
+
+use core::fmt;
+
+const ELF_MAGIC: [u8; 4] = *b"\x7fELF";
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+
+pub const PT_LOAD: u32 = 1;
+
+pub const PF_X: u32 = 1;
+pub const PF_W: u32 = 2;
+pub const PF_R: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfError {
+    TooSmall,
+    BadMagic,
+    Not64BitLittleEndian,
+    ProgramHeadersOutOfBounds,
+}
+
+impl fmt::Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::TooSmall => "file is smaller than an ELF header",
+            Self::BadMagic => "file does not start with the ELF magic",
+            Self::Not64BitLittleEndian => "file is not a 64-bit little endian ELF",
+            Self::ProgramHeadersOutOfBounds => "program header table is out of bounds",
+        })
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Elf64Header {
+    ident: [u8; 16],
+    file_type: u16,
+    machine: u16,
+    version: u32,
+    entry: u64,
+    phoff: u64,
+    shoff: u64,
+    flags: u32,
+    ehsize: u16,
+    phentsize: u16,
+    phnum: u16,
+    shentsize: u16,
+    shnum: u16,
+    shstrndx: u16,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ProgramHeader {
+    pub p_type: u32,
+    pub p_flags: u32,
+    pub p_offset: u64,
+    pub p_vaddr: u64,
+    pub p_paddr: u64,
+    pub p_filesz: u64,
+    pub p_memsz: u64,
+    pub p_align: u64,
+}
+
+impl ProgramHeader {
+    pub fn is_load(&self) -> bool {
+        self.p_type == PT_LOAD
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.p_flags & PF_W != 0
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.p_flags & PF_X != 0
+    }
+}
+
+/// A borrowed, minimally validated ELF64 file.
+pub struct ElfFile<'a> {
+    data: &'a [u8],
+    header: Elf64Header,
+}
+
+impl<'a> ElfFile<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, ElfError> {
+        if data.len() < size_of::<Elf64Header>() {
+            return Err(ElfError::TooSmall);
+        }
+
+        // SAFETY: The length was checked above and the header is plain old data, so any bit
+        // pattern is valid. read_unaligned is used since the file has no alignment guarantees.
+        let header = unsafe { data.as_ptr().cast::<Elf64Header>().read_unaligned() };
+
+        if header.ident[..4] != ELF_MAGIC {
+            return Err(ElfError::BadMagic);
+        }
+
+        if header.ident[4] != ELFCLASS64 || header.ident[5] != ELFDATA2LSB {
+            return Err(ElfError::Not64BitLittleEndian);
+        }
+
+        let table_end = (header.phnum as u64)
+            .checked_mul(header.phentsize as u64)
+            .and_then(|size| size.checked_add(header.phoff));
+
+        if header.phentsize as usize != size_of::<ProgramHeader>()
+            || table_end.is_none_or(|end| end > data.len() as u64)
+        {
+            return Err(ElfError::ProgramHeadersOutOfBounds);
+        }
+
+        Ok(Self { data, header })
+    }
+
+    pub fn program_headers(&self) -> impl Iterator<Item = ProgramHeader> + '_ {
+        (0..self.header.phnum as usize).map(|i| {
+            let offset = self.header.phoff as usize + i * size_of::<ProgramHeader>();
+            // SAFETY: The bounds of the program header table were checked in parse.
+            unsafe {
+                self.data
+                    .as_ptr()
+                    .add(offset)
+                    .cast::<ProgramHeader>()
+                    .read_unaligned()
+            }
+        })
+    }
+}
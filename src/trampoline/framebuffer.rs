@@ -2,137 +2,137 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use core::convert::Infallible;
-
-use embedded_graphics::{
-    Pixel,
-    pixelcolor::Rgb888,
-    prelude::{DrawTarget, OriginDimensions, RgbColor, Size},
-};
-use x86_64::structures::paging::{Page, Size2MiB};
-
-use crate::util::page_from_addr;
-
-pub struct Framebuffer {
-    back_buf_addr: &'static mut u8,
-    front_addr: &'static mut u8,
-    width: u64,
-    height: u64,
-    pitch: u64,
-    bpp: u16,
-    red_mask_size: u8,
-    red_mask_shift: u8,
-    green_mask_size: u8,
-    green_mask_shift: u8,
-    blue_mask_size: u8,
-    blue_mask_shift: u8,
-    length: u64,
+pub use wordle_graphics::{Framebuffer, FramebufferError, PixelFormat};
+
+use crate::trampoline::smp;
+
+/// Copies `back` into `front`, with every online CPU taking a part. Meant for
+/// [`Framebuffer::flush_with`].
+pub fn copy_in_parallel(front: &mut [u8], back: &[u8]) {
+    let cpus = smp::cpus().iter().filter(|cpu| cpu.is_online()).count();
+    // Whole cache lines, so that no two CPUs write to the same one.
+    let chunk = back
+        .len()
+        .div_ceil(cpus.max(1))
+        .next_multiple_of(64)
+        .max(64);
+
+    smp::for_each(
+        front.chunks_mut(chunk).zip(back.chunks(chunk)),
+        |(front, back)| front.copy_from_slice(back),
+    );
 }
 
-impl Framebuffer {
-    pub const FRAMEBUFFER_BASE: Page<Size2MiB> = page_from_addr(0x2222_2220_0000);
-
-    pub fn write_pixel(&mut self, x: u64, y: u64, mut r: u8, mut g: u8, mut b: u8) {
-        assert!(x < self.width);
-        assert!(y < self.height);
-
-        let mut pixel_value = 0u32;
-
-        r = ((r as u16 * ((1u16 << self.red_mask_size) - 1) + 127) / 255) as u8;
-        g = ((g as u16 * ((1u16 << self.green_mask_size) - 1) + 127) / 255) as u8;
-        b = ((b as u16 * ((1u16 << self.blue_mask_size) - 1) + 127) / 255) as u8;
-
-        pixel_value |= (r as u32 & ((1 << self.red_mask_size) - 1)) << self.red_mask_shift;
-        pixel_value |= (g as u32 & ((1 << self.green_mask_size) - 1)) << self.green_mask_shift;
-        pixel_value |= (b as u32 & ((1 << self.blue_mask_size) - 1)) << self.blue_mask_shift;
-
-        let bytes_per_pixel = (self.bpp / 8) as u64;
+/// # SAFETY
+/// Both the back_buf_addr and front_addr must point to valid memory which is readable and
+/// writable for the length of the framebuffer, and is not used by anything else from now on.
+/// The two buffers must also not overlap.
+pub unsafe fn from_limine_framebuffer(
+    framebuffer: &limine::framebuffer::Framebuffer,
+    back_buf_addr: *mut u8,
+    front_addr: *mut u8,
+) -> Result<Framebuffer<'static>, FramebufferError> {
+    let length = (framebuffer.pitch() * framebuffer.height()) as usize;
+
+    // SAFETY: The caller guarantees both buffers are valid, exclusive and disjoint.
+    let (back, front) = unsafe {
+        (
+            core::slice::from_raw_parts_mut(back_buf_addr, length),
+            core::slice::from_raw_parts_mut(front_addr, length),
+        )
+    };
+
+    Framebuffer::new(
+        back,
+        front,
+        framebuffer.width() as usize,
+        framebuffer.height() as usize,
+        framebuffer.pitch() as usize,
+        pixel_format(framebuffer),
+    )
+}
 
-        // SAFETY: address is properly mapped and aligned.
-        // no concurrent writes since the function takes &mut self
-        unsafe {
-            core::ptr::write_volatile(
-                core::ptr::from_mut(self.back_buf_addr)
-                    .add((y * self.pitch + x * bytes_per_pixel) as usize)
-                    .cast::<u32>(),
-                pixel_value,
-            );
-        }
+/// How Limine says `framebuffer` packs its pixels.
+pub fn pixel_format(framebuffer: &limine::framebuffer::Framebuffer) -> PixelFormat {
+    PixelFormat {
+        bpp: framebuffer.bpp(),
+        red_mask_size: framebuffer.red_mask_size(),
+        red_mask_shift: framebuffer.red_mask_shift(),
+        green_mask_size: framebuffer.green_mask_size(),
+        green_mask_shift: framebuffer.green_mask_shift(),
+        blue_mask_size: framebuffer.blue_mask_size(),
+        blue_mask_shift: framebuffer.blue_mask_shift(),
     }
+}
 
-    // TODO: Potentially test out techniques like dirty rectangles etc if this is not fast enough.
-    pub fn flush(&mut self) {
-        // SAFETY: Both buffers are made sure to be valid and properly aligned by the caller of the
-        // constructor. They are also non-overlapping.
-        unsafe {
-            core::ptr::copy_nonoverlapping(
-                core::ptr::from_mut(self.back_buf_addr),
-                core::ptr::from_mut(self.front_addr),
-                self.length as usize,
-            );
-        }
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::{
+        Drawable,
+        pixelcolor::Rgb888,
+        prelude::{DrawTarget, OriginDimensions, Point, Primitive, RgbColor, Size},
+        primitives::{PrimitiveStyle, Rectangle},
+    };
+
+    use super::*;
+    use crate::testing::with_boot_info;
+
+    /// Runs `f` on the first framebuffer. Machines without a display have nothing to test.
+    fn with_framebuffer(f: impl FnOnce(&mut Framebuffer<'static>)) {
+        with_boot_info(|boot_info| {
+            if let Some(framebuffer) = boot_info.framebuffers.first_mut() {
+                f(framebuffer);
+            }
+        });
     }
 
-    /// # SAFETY
-    /// Both the back_buf_addr and front_addr must point to valid memory which is readable and
-    /// writable for the length of the framebuffer. They also must be properly aligned.
-    /// The two buffers must also not overlap.
-    pub unsafe fn from_limine_framebuffer(
-        framebuffer: &limine::framebuffer::Framebuffer,
-        back_buf_addr: &'static mut u8,
-        front_addr: &'static mut u8,
-    ) -> Self {
-        Self {
-            back_buf_addr,
-            front_addr,
-            width: framebuffer.width(),
-            height: framebuffer.height(),
-            pitch: framebuffer.pitch(),
-            bpp: framebuffer.bpp(),
-            red_mask_size: framebuffer.red_mask_size(),
-            red_mask_shift: framebuffer.red_mask_shift(),
-            green_mask_size: framebuffer.green_mask_size(),
-            green_mask_shift: framebuffer.green_mask_shift(),
-            blue_mask_size: framebuffer.blue_mask_size(),
-            blue_mask_shift: framebuffer.blue_mask_shift(),
-            length: framebuffer.pitch() * framebuffer.height(),
-        }
+    #[test_case]
+    fn write_pixel_round_trips() {
+        with_framebuffer(|framebuffer| {
+            for color in [Rgb888::WHITE, Rgb888::RED, Rgb888::BLACK] {
+                framebuffer.write_pixel(0, 0, color);
+                assert_eq!(framebuffer.pixel(0, 0), color);
+            }
+        });
     }
-}
 
-impl OriginDimensions for Framebuffer {
-    fn size(&self) -> Size {
-        Size::new(self.width as u32, self.height as u32)
+    #[test_case]
+    fn drawing_clips_to_bounds() {
+        with_framebuffer(|framebuffer| {
+            let size = framebuffer.size();
+
+            framebuffer.clear(Rgb888::BLACK).unwrap();
+            Rectangle::new(Point::new(-10, -10), size + Size::new(20, 20))
+                .into_styled(PrimitiveStyle::with_fill(Rgb888::WHITE))
+                .draw(framebuffer)
+                .unwrap();
+
+            let (right, bottom) = (framebuffer.width() - 1, framebuffer.height() - 1);
+            assert_eq!(framebuffer.pixel(0, 0), Rgb888::WHITE);
+            assert_eq!(framebuffer.pixel(right, bottom), Rgb888::WHITE);
+        });
     }
-}
 
-impl DrawTarget for Framebuffer {
-    type Color = Rgb888;
-
-    type Error = Infallible;
-
-    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
-    where
-        I: IntoIterator<Item = embedded_graphics::Pixel<Self::Color>>,
-    {
-        for Pixel(coord, color) in pixels {
-            if coord.x >= self.width as i32
-                || coord.y >= self.height as i32
-                || coord.x < 0
-                || coord.y < 0
-            {
-                continue;
-            }
+    #[test_case]
+    fn flush_copies_back_buffer() {
+        with_framebuffer(|framebuffer| {
+            framebuffer.clear(Rgb888::BLACK).unwrap();
+            framebuffer.write_pixel(3, 2, Rgb888::WHITE);
+
+            framebuffer.flush();
+            assert!(framebuffer.front() == framebuffer.back());
+        });
+    }
 
-            self.write_pixel(
-                coord.x as u64,
-                coord.y as u64,
-                color.r(),
-                color.g(),
-                color.b(),
-            );
-        }
+    #[test_case]
+    fn parallel_flush_copies_back_buffer() {
+        with_framebuffer(|framebuffer| {
+            framebuffer.clear(Rgb888::BLACK).unwrap();
+            let (right, bottom) = (framebuffer.width() - 1, framebuffer.height() - 1);
+            framebuffer.write_pixel(right, bottom, Rgb888::WHITE);
 
-        Ok(())
+            framebuffer.flush_with(copy_in_parallel);
+            assert!(framebuffer.front() == framebuffer.back());
+        });
     }
 }
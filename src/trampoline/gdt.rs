@@ -2,6 +2,9 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use alloc::boxed::Box;
+use core::{cell::UnsafeCell, ops::Range};
+
 use lazy_static::lazy_static;
 use x86_64::{
     VirtAddr,
@@ -9,33 +12,114 @@ use x86_64::{
     registers::segmentation::{CS, Segment},
     structures::{
         gdt::{Descriptor, GlobalDescriptorTable, SegmentSelector},
+        paging::OffsetPageTable,
         tss::TaskStateSegment,
     },
 };
 
+use crate::trampoline::{
+    memory::{
+        allocate_stack,
+        allocators::ProperFrameAllocator,
+        error::{MemoryError, MemoryRegion},
+    },
+    smp,
+};
+
 pub const DOUBLE_FAULT_IST_INDEX: u16 = 0;
+pub const NMI_IST_INDEX: u16 = 1;
+pub const MACHINE_CHECK_IST_INDEX: u16 = 2;
 
-lazy_static! {
-    static ref TSS: TaskStateSegment = {
-        let mut tss = TaskStateSegment::new();
-        tss.interrupt_stack_table[DOUBLE_FAULT_IST_INDEX as usize] = {
-            const STACK_SIZE: usize = 4096 * 5;
-            static mut STACK: [u8; STACK_SIZE] = [0; STACK_SIZE];
+const IST_STACK_COUNT: usize = 3;
+const IST_STACK_SIZE: u64 = 4096 * 5;
+const IST_STACK_NAMES: [&str; IST_STACK_COUNT] =
+    ["double fault stack", "NMI stack", "machine check stack"];
+
+/// A stack for one IST entry of the boot TSS, which is only used until the BSP has stacks from the
+/// frame allocator. Nothing but the CPU ever touches it.
+#[repr(C, align(16))]
+struct BootStack(UnsafeCell<[u8; IST_STACK_SIZE as usize]>);
+
+// SAFETY: The stack is never accessed through the static, only used by the CPU as a stack.
+unsafe impl Sync for BootStack {}
+
+/// Separate for each entry, since an NMI or machine check can arrive while handling a double
+/// fault, and the other way around.
+///
+/// These can't wait for [`IstStacks::allocate`]: that needs our page tables and frame allocator,
+/// and a fault while setting those up must still land on a good stack instead of triple faulting.
+/// They are also what the BSP keeps if allocating its own stacks fails.
+static BOOT_STACKS: [BootStack; IST_STACK_COUNT] =
+    [const { BootStack(UnsafeCell::new([0; IST_STACK_SIZE as usize])) }; IST_STACK_COUNT];
+
+fn boot_stack(index: usize) -> Range<u64> {
+    let start = BOOT_STACKS[index].0.get() as u64;
+    start..start + IST_STACK_SIZE
+}
 
-            let stack_start = VirtAddr::from_ptr(&raw const STACK);
+fn boot_stacks() -> impl Iterator<Item = Range<u64>> {
+    (0..IST_STACK_COUNT).map(boot_stack)
+}
+
+/// One CPU's interrupt stacks, in the order of its TSS's interrupt stack table.
+#[derive(Debug, Clone)]
+pub struct IstStacks([Range<u64>; IST_STACK_COUNT]);
+
+impl IstStacks {
+    /// Allocates a guarded stack for each IST entry.
+    pub fn allocate(
+        offset_page_table: &mut OffsetPageTable,
+        frame_allocator: &mut ProperFrameAllocator,
+    ) -> Result<Self, MemoryError> {
+        let mut stacks = [const { 0..0 }; IST_STACK_COUNT];
+        for (stack, name) in stacks.iter_mut().zip(IST_STACK_NAMES) {
+            *stack = allocate_stack(
+                offset_page_table,
+                frame_allocator,
+                IST_STACK_SIZE,
+                MemoryRegion::Dynamic(name),
+            )?;
+        }
 
-            stack_start + STACK_SIZE as u64 // stack_end
-        };
+        Ok(Self(stacks))
+    }
+
+    /// The static stacks the BSP boots with.
+    pub fn boot() -> Self {
+        Self(core::array::from_fn(boot_stack))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Range<u64>> {
+        self.0.iter()
+    }
+
+    fn tss(&self) -> TaskStateSegment {
+        let mut tss = TaskStateSegment::new();
+        // The TSS is packed, so its entries can only be assigned, not borrowed.
+        let mut table = tss.interrupt_stack_table;
+        for (entry, stack) in table.iter_mut().zip(self.iter()) {
+            *entry = VirtAddr::new(stack.end);
+        }
+        tss.interrupt_stack_table = table;
         tss
-    };
+    }
+}
 
+lazy_static! {
+    static ref TSS: TaskStateSegment = IstStacks::boot().tss();
     static ref GDT: (GlobalDescriptorTable, Selectors) = {
         let mut gdt = GlobalDescriptorTable::new();
 
         let code_selector = gdt.append(Descriptor::kernel_code_segment());
         let tss_selector = gdt.append(Descriptor::tss_segment(&TSS));
 
-        (gdt, Selectors { code_selector, tss_selector })
+        (
+            gdt,
+            Selectors {
+                code_selector,
+                tss_selector,
+            },
+        )
     };
 }
 
@@ -44,12 +128,84 @@ struct Selectors {
     tss_selector: SegmentSelector,
 }
 
+impl Selectors {
+    /// # Safety
+    /// The GDT the selectors point into must be loaded.
+    unsafe fn load(&self) {
+        // SAFETY: The caller guarantees the selectors are valid.
+        unsafe {
+            CS::set_reg(self.code_selector);
+            load_tss(self.tss_selector);
+        }
+    }
+}
+
+/// Loads the boot GDT and TSS on the boot processor. Its interrupt stacks are static ones until
+/// [`init_cpu`] replaces them.
 pub fn init() {
     GDT.0.load();
 
-    // SAFETY: Selectors are initialized in lazy_static and should be valid.
+    // SAFETY: GDT was just loaded.
     unsafe {
-        CS::set_reg(GDT.1.code_selector);
-        load_tss(GDT.1.tss_selector);
+        GDT.1.load();
+    }
+}
+
+/// Loads a new GDT and a TSS using `stacks` on the current CPU. Each CPU needs its own TSS since
+/// loading one marks it busy. Both are leaked because the CPU uses them for as long as it runs.
+pub fn init_cpu(stacks: &IstStacks) {
+    let tss: &'static TaskStateSegment = Box::leak(Box::new(stacks.tss()));
+
+    let gdt = Box::leak(Box::new(GlobalDescriptorTable::new()));
+    let selectors = Selectors {
+        code_selector: gdt.append(Descriptor::kernel_code_segment()),
+        tss_selector: gdt.append(Descriptor::tss_segment(tss)),
+    };
+    let gdt: &'static GlobalDescriptorTable = gdt;
+    gdt.load();
+
+    // SAFETY: GDT was just loaded.
+    unsafe {
+        selectors.load();
+    }
+}
+
+/// Address ranges of every interrupt stack in every CPU's TSS, including the boot one.
+pub fn ist_stacks() -> impl Iterator<Item = Range<u64>> {
+    boot_stacks().chain(
+        smp::cpus()
+            .iter()
+            .flat_map(|cpu| cpu.ist_stacks.iter().cloned()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn boot_stacks_are_separate() {
+        let stacks = IstStacks::boot();
+        for (i, a) in stacks.iter().enumerate() {
+            for b in stacks.iter().skip(i + 1) {
+                assert!(a.end <= b.start || b.end <= a.start);
+            }
+        }
+    }
+
+    #[test_case]
+    fn cpus_left_the_boot_stacks() {
+        for cpu in smp::cpus() {
+            assert!(
+                cpu.ist_stacks
+                    .iter()
+                    .all(|stack| boot_stacks().all(|boot| *stack != boot))
+            );
+            assert!(
+                cpu.ist_stacks
+                    .iter()
+                    .all(|stack| stack.end - stack.start == IST_STACK_SIZE)
+            );
+        }
     }
 }
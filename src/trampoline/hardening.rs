@@ -0,0 +1,140 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use alloc::vec::Vec;
+use core::arch::x86_64::__cpuid_count;
+
+use x86_64::{
+    registers::control::{Cr4, Cr4Flags},
+    structures::paging::{
+        Mapper, OffsetPageTable, Page, PageSize, Size1GiB, Size2MiB, Size4KiB, mapper::UnmapError,
+    },
+};
+
+use crate::trampoline::memory::{
+    HHDM_OFFSET,
+    address_space::KERNEL_ADDRESS_SPACE,
+    walk::{self, Mapping},
+};
+
+/// Protection features that the CPU may or may not support.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuProtection {
+    pub smep: bool,
+    pub smap: bool,
+    pub umip: bool,
+}
+
+impl CpuProtection {
+    pub fn detect() -> Self {
+        let max_leaf = __cpuid_count(0, 0).eax;
+        if max_leaf < 7 {
+            return Self::default();
+        }
+
+        let features = __cpuid_count(7, 0);
+        Self {
+            smep: features.ebx & (1 << 7) != 0,
+            smap: features.ebx & (1 << 20) != 0,
+            umip: features.ecx & (1 << 2) != 0,
+        }
+    }
+}
+
+/// Turns on every control register protection the CPU supports. Control registers are per CPU,
+/// so every processor calls this for itself.
+pub fn protect_cpu() -> CpuProtection {
+    let protection = CpuProtection::detect();
+
+    let mut cr4 = Cr4Flags::empty();
+    if protection.smep {
+        cr4 |= Cr4Flags::SUPERVISOR_MODE_EXECUTION_PROTECTION;
+    }
+    if protection.smap {
+        cr4 |= Cr4Flags::SUPERVISOR_MODE_ACCESS_PREVENTION;
+    }
+    if protection.umip {
+        cr4 |= Cr4Flags::USER_MODE_INSTRUCTION_PREVENTION;
+    }
+
+    // SAFETY: The kernel has no user accessible pages, so SMEP and SMAP never trigger, and UMIP
+    // only restricts instructions in ring 3, which we never enter.
+    unsafe {
+        Cr4::update(|flags| flags.insert(cr4));
+    }
+
+    protection
+}
+
+/// Protects the boot processor with [`protect_cpu`] and removes any lower half mapping that wasn't
+/// handed out by the kernel address space. Write protection and NX are already enabled while
+/// setting up paging.
+///
+/// Must be called after the kernel is running on its own page tables and every deliberate lower
+/// half region (stack, heap, back buffers) has been recorded in
+/// [`KERNEL_ADDRESS_SPACE`](crate::trampoline::memory::address_space::KERNEL_ADDRESS_SPACE).
+pub fn harden(offset_page_table: &mut OffsetPageTable) {
+    let protection = protect_cpu();
+    log::info!("CPU protection: {protection:?}");
+
+    let strays = stray_lower_half_mappings(offset_page_table);
+    for mapping in &strays {
+        log::warn!(
+            "Unmapping stray lower half mapping {:?} -> {:?} ({:#x} bytes)",
+            mapping.virt,
+            mapping.phys,
+            mapping.size
+        );
+        unmap(offset_page_table, mapping)
+            .unwrap_or_else(|e| panic!("Failed to unmap {:?}: {e:?}", mapping.virt));
+    }
+
+    let remaining = stray_lower_half_mappings(offset_page_table);
+    assert!(
+        remaining.is_empty(),
+        "Lower half still contains unexpected mappings: {remaining:#?}"
+    );
+}
+
+fn stray_lower_half_mappings(offset_page_table: &mut OffsetPageTable) -> Vec<Mapping> {
+    let address_space = KERNEL_ADDRESS_SPACE.lock();
+    let mut strays = Vec::new();
+
+    // SAFETY: our HHDM is active and covers all page tables, which are allocated from usable
+    // memory.
+    unsafe {
+        walk::for_each_mapping(
+            offset_page_table.level_4_table(),
+            HHDM_OFFSET,
+            walk::LOWER_HALF,
+            |mapping| {
+                if address_space.region_containing(mapping.virt).is_none() {
+                    strays.push(mapping);
+                }
+            },
+        );
+    }
+
+    strays
+}
+
+fn unmap(offset_page_table: &mut OffsetPageTable, mapping: &Mapping) -> Result<(), UnmapError> {
+    match mapping.size {
+        Size4KiB::SIZE => offset_page_table
+            .unmap(Page::<Size4KiB>::containing_address(mapping.virt))?
+            .1
+            .flush(),
+        Size2MiB::SIZE => offset_page_table
+            .unmap(Page::<Size2MiB>::containing_address(mapping.virt))?
+            .1
+            .flush(),
+        Size1GiB::SIZE => offset_page_table
+            .unmap(Page::<Size1GiB>::containing_address(mapping.virt))?
+            .1
+            .flush(),
+        _ => unreachable!("Page table walk only yields 4KiB, 2MiB and 1GiB pages."),
+    }
+
+    Ok(())
+}
@@ -2,48 +2,283 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use core::fmt;
+
 use x86_64::{
-    registers::control::Cr2,
-    structures::idt::{InterruptDescriptorTable, InterruptStackFrame, PageFaultErrorCode},
+    registers::{
+        control::{Cr0, Cr2, Cr3, Cr4},
+        model_specific::Efer,
+    },
+    structures::{
+        idt::{
+            DescriptorTable, InterruptDescriptorTable, InterruptStackFrame,
+            InterruptStackFrameValue, PageFaultErrorCode, SelectorErrorCode,
+        },
+        paging::{PageSize, Size4KiB},
+    },
 };
 
-use crate::{eprintln, gdt};
+use crate::{
+    debug::{panic, serial},
+    eprintln, gdt, serial_println,
+    trampoline::{
+        apic,
+        pic::{self, Irq},
+        pit,
+        ps2::{keyboard, mouse},
+        smp,
+    },
+};
 
 use lazy_static::lazy_static;
+use spin::Mutex;
 
 lazy_static! {
     static ref IDT: InterruptDescriptorTable = {
         let mut idt = InterruptDescriptorTable::new();
 
+        idt.divide_error.set_handler_fn(divide_error_handler);
+        idt.debug.set_handler_fn(debug_handler);
         idt.breakpoint.set_handler_fn(breakpoint_handler);
+        idt.overflow.set_handler_fn(overflow_handler);
+        idt.bound_range_exceeded.set_handler_fn(bound_range_exceeded_handler);
+        idt.invalid_opcode.set_handler_fn(invalid_opcode_handler);
+        idt.device_not_available.set_handler_fn(device_not_available_handler);
 
-        // SAFETY: Stack index is set as a constant that is used in initializing the TSS, so it
-        // must be valid.
+        // SAFETY: Every CPU's TSS has a stack for each of these indices. NMIs and machine checks
+        // can arrive at any point, even while the current stack is unusable, and a double fault
+        // is what a kernel stack overflow turns into. Page faults stay on the current stack,
+        // since an IST stack would be reused, and overwritten, by a nested one.
         unsafe {
             idt.double_fault
                 .set_handler_fn(double_fault_handler)
                 .set_stack_index(gdt::DOUBLE_FAULT_IST_INDEX);
+            idt.non_maskable_interrupt
+                .set_handler_fn(nmi_handler)
+                .set_stack_index(gdt::NMI_IST_INDEX);
+            idt.machine_check
+                .set_handler_fn(machine_check_handler)
+                .set_stack_index(gdt::MACHINE_CHECK_IST_INDEX);
         }
 
         idt.page_fault.set_handler_fn(page_fault_handler);
 
+        idt.invalid_tss.set_handler_fn(invalid_tss_handler);
+        idt.segment_not_present.set_handler_fn(segment_not_present_handler);
+        idt.stack_segment_fault.set_handler_fn(stack_segment_fault_handler);
+        idt.general_protection_fault.set_handler_fn(general_protection_fault_handler);
+        idt.x87_floating_point.set_handler_fn(x87_floating_point_handler);
+        idt.alignment_check.set_handler_fn(alignment_check_handler);
+        idt.simd_floating_point.set_handler_fn(simd_floating_point_handler);
+        idt.virtualization.set_handler_fn(virtualization_handler);
+        idt.cp_protection_exception.set_handler_fn(cp_protection_handler);
+        idt.hv_injection_exception.set_handler_fn(hv_injection_handler);
+        idt.vmm_communication_exception.set_handler_fn(vmm_communication_handler);
+        idt.security_exception.set_handler_fn(security_exception_handler);
+
+        idt[Irq::Timer.vector()].set_handler_fn(timer_handler);
+        idt[Irq::Keyboard.vector()].set_handler_fn(keyboard_handler);
+        idt[Irq::Com1.vector()].set_handler_fn(com1_handler);
+        idt[Irq::Mouse.vector()].set_handler_fn(mouse_handler);
+        idt[apic::WAKE_VECTOR].set_handler_fn(wake_handler);
+        idt[apic::SPURIOUS_VECTOR].set_handler_fn(spurious_handler);
+
         idt
     };
 }
 
+/// Loads the IDT on the current CPU. All CPUs share it, since handlers don't depend on which CPU
+/// they run on.
 pub fn init_idt() {
     IDT.load();
 }
 
+/// Details of the exception that caused the current panic, kept for the crash screen.
+#[derive(Clone, Copy)]
+pub struct ExceptionInfo {
+    pub name: &'static str,
+    pub frame: InterruptStackFrameValue,
+    pub error_code: Option<u64>,
+}
+
+impl fmt::Display for ExceptionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Exception: {}", self.name)?;
+        if let Some(error_code) = self.error_code {
+            writeln!(f, "Error code: {error_code:#x}")?;
+        }
+        writeln!(
+            f,
+            "RIP:    {:#018x}",
+            self.frame.instruction_pointer.as_u64()
+        )?;
+        writeln!(f, "CS:     {:?}", self.frame.code_segment)?;
+        writeln!(f, "RFLAGS: {:?}", self.frame.cpu_flags)?;
+        writeln!(f, "RSP:    {:#018x}", self.frame.stack_pointer.as_u64())?;
+        write!(f, "SS:     {:?}", self.frame.stack_segment)
+    }
+}
+
+/// The exception each CPU is reporting, indexed by [`smp::current_index`].
+static CURRENT_EXCEPTION: [Mutex<Option<ExceptionInfo>>; smp::MAX_CPUS] =
+    [const { Mutex::new(None) }; smp::MAX_CPUS];
+
+/// The exception the current CPU is reporting, if its panic came from one.
+pub fn current_exception() -> Option<ExceptionInfo> {
+    CURRENT_EXCEPTION[smp::current_index()]
+        .try_lock()
+        .and_then(|exception| *exception)
+}
+
+/// Snapshot of the control registers, printed alongside every fatal exception.
+pub struct ControlRegisters;
+
+impl fmt::Display for ControlRegisters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (cr3, cr3_flags) = Cr3::read();
+
+        writeln!(f, "CR0:  {:?}", Cr0::read())?;
+        writeln!(f, "CR2:  {:?}", Cr2::read())?;
+        writeln!(f, "CR3:  {:?} {:?}", cr3.start_address(), cr3_flags)?;
+        writeln!(f, "CR4:  {:?}", Cr4::read())?;
+        write!(f, "EFER: {:?}", Efer::read())
+    }
+}
+
+/// Decoded form of the error code pushed by exceptions that refer to a segment selector.
+struct Selector(u64);
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 0 {
+            return f.write_str("not selector related");
+        }
+
+        let code = SelectorErrorCode::new_truncate(self.0);
+        let table = match code.descriptor_table() {
+            DescriptorTable::Gdt => "GDT",
+            DescriptorTable::Idt => "IDT",
+            DescriptorTable::Ldt => "LDT",
+        };
+
+        write!(f, "{table} index {}", code.index())?;
+        if code.external() {
+            f.write_str(", external event")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reports an exception that the kernel cannot recover from.
+fn fault(
+    name: &'static str,
+    error_code: Option<u64>,
+    detail: fmt::Arguments,
+    stack_frame: &InterruptStackFrame,
+) -> ! {
+    // try_lock since a fault while recording a previous fault must not deadlock.
+    if let Some(mut exception) = CURRENT_EXCEPTION[smp::current_index()].try_lock() {
+        *exception = Some(ExceptionInfo {
+            name,
+            frame: **stack_frame,
+            error_code,
+        });
+    }
+
+    panic!(
+        "EXCEPTION: {name} at RIP {:#x}{detail}",
+        stack_frame.instruction_pointer.as_u64(),
+    );
+}
+
+macro_rules! fatal_handler {
+    ($handler:ident, $name:literal) => {
+        extern "x86-interrupt" fn $handler(stack_frame: InterruptStackFrame) {
+            fault($name, None, format_args!(""), &stack_frame);
+        }
+    };
+    ($handler:ident, $name:literal, error_code) => {
+        extern "x86-interrupt" fn $handler(stack_frame: InterruptStackFrame, error_code: u64) {
+            fault(
+                $name,
+                Some(error_code),
+                format_args!(" (error code {error_code:#x})"),
+                &stack_frame,
+            );
+        }
+    };
+    ($handler:ident, $name:literal, selector) => {
+        extern "x86-interrupt" fn $handler(stack_frame: InterruptStackFrame, error_code: u64) {
+            fault(
+                $name,
+                Some(error_code),
+                format_args!(" ({})", Selector(error_code)),
+                &stack_frame,
+            );
+        }
+    };
+}
+
+fatal_handler!(divide_error_handler, "DIVIDE ERROR");
+fatal_handler!(overflow_handler, "OVERFLOW");
+fatal_handler!(bound_range_exceeded_handler, "BOUND RANGE EXCEEDED");
+fatal_handler!(invalid_opcode_handler, "INVALID OPCODE");
+fatal_handler!(device_not_available_handler, "DEVICE NOT AVAILABLE");
+fatal_handler!(invalid_tss_handler, "INVALID TSS", selector);
+fatal_handler!(segment_not_present_handler, "SEGMENT NOT PRESENT", selector);
+fatal_handler!(stack_segment_fault_handler, "STACK SEGMENT FAULT", selector);
+fatal_handler!(
+    general_protection_fault_handler,
+    "GENERAL PROTECTION FAULT",
+    selector
+);
+fatal_handler!(x87_floating_point_handler, "x87 FLOATING POINT");
+fatal_handler!(alignment_check_handler, "ALIGNMENT CHECK", error_code);
+fatal_handler!(simd_floating_point_handler, "SIMD FLOATING POINT");
+fatal_handler!(virtualization_handler, "VIRTUALIZATION");
+fatal_handler!(cp_protection_handler, "CONTROL PROTECTION", error_code);
+fatal_handler!(hv_injection_handler, "HYPERVISOR INJECTION");
+fatal_handler!(vmm_communication_handler, "VMM COMMUNICATION", error_code);
+fatal_handler!(security_exception_handler, "SECURITY EXCEPTION", error_code);
+
 extern "x86-interrupt" fn breakpoint_handler(stack_frame: InterruptStackFrame) {
     eprintln!("EXCEPTION: BREAKPOINT\n{:#?}", stack_frame);
+    serial_println!("EXCEPTION: BREAKPOINT\n{:#?}", stack_frame);
+}
+
+extern "x86-interrupt" fn debug_handler(stack_frame: InterruptStackFrame) {
+    eprintln!("EXCEPTION: DEBUG\n{:#?}", stack_frame);
+    serial_println!("EXCEPTION: DEBUG\n{:#?}", stack_frame);
 }
 
 extern "x86-interrupt" fn double_fault_handler(
     stack_frame: InterruptStackFrame,
-    _error_code: u64,
+    error_code: u64,
 ) -> ! {
-    panic!("EXCEPTION: DOUBLE FAULT\n{:#?}", stack_frame);
+    // Overflowing a stack into its guard page raises a page fault that can't be pushed onto that
+    // same stack, so it ends up here with the address in CR2.
+    let overflow = if Cr2::read().is_ok_and(|addr| hits_stack_guard(addr.as_u64())) {
+        " (stack overflow)"
+    } else {
+        ""
+    };
+
+    fault(
+        "DOUBLE FAULT",
+        Some(error_code),
+        format_args!("{overflow}"),
+        &stack_frame,
+    );
+}
+
+/// Whether `addr` lies in the unmapped page below one of the kernel's stacks.
+fn hits_stack_guard(addr: u64) -> bool {
+    smp::cpus()
+        .iter()
+        .map(|cpu| cpu.stack.clone())
+        .chain(gdt::ist_stacks())
+        .any(|stack| (stack.start.saturating_sub(Size4KiB::SIZE)..stack.start).contains(&addr))
 }
 
 extern "x86-interrupt" fn page_fault_handler(
@@ -52,8 +287,54 @@ extern "x86-interrupt" fn page_fault_handler(
 ) {
     let addr = Cr2::read();
 
-    panic!(
-        "EXCEPTION: PAGE FAULT at {addr:?} code {:?}\n{:#?}",
-        error_code, stack_frame
+    fault(
+        "PAGE FAULT",
+        Some(error_code.bits()),
+        format_args!(" accessing {addr:?} code {error_code:?}"),
+        &stack_frame,
+    );
+}
+
+extern "x86-interrupt" fn nmi_handler(stack_frame: InterruptStackFrame) {
+    if panic::handle_nmi() {
+        return;
+    }
+
+    fault(
+        "NON-MASKABLE INTERRUPT",
+        None,
+        format_args!(""),
+        &stack_frame,
     );
 }
+
+extern "x86-interrupt" fn machine_check_handler(stack_frame: InterruptStackFrame) -> ! {
+    fault("MACHINE CHECK", None, format_args!(""), &stack_frame);
+}
+
+extern "x86-interrupt" fn timer_handler(_stack_frame: InterruptStackFrame) {
+    pit::handle_interrupt();
+    pic::end_of_interrupt(Irq::Timer);
+}
+
+extern "x86-interrupt" fn keyboard_handler(_stack_frame: InterruptStackFrame) {
+    keyboard::handle_interrupt();
+    pic::end_of_interrupt(Irq::Keyboard);
+}
+
+extern "x86-interrupt" fn com1_handler(_stack_frame: InterruptStackFrame) {
+    serial::handle_interrupt();
+    pic::end_of_interrupt(Irq::Com1);
+}
+
+extern "x86-interrupt" fn mouse_handler(_stack_frame: InterruptStackFrame) {
+    mouse::handle_interrupt();
+    pic::end_of_interrupt(Irq::Mouse);
+}
+
+/// Only there to bring a CPU out of `hlt`, which is all the IPI is for.
+extern "x86-interrupt" fn wake_handler(_stack_frame: InterruptStackFrame) {
+    apic::end_of_interrupt();
+}
+
+extern "x86-interrupt" fn spurious_handler(_stack_frame: InterruptStackFrame) {}
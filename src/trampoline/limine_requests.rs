@@ -5,8 +5,9 @@
 use limine::{
     BaseRevision,
     request::{
-        ExecutableAddressRequest, FramebufferRequest, HhdmRequest, MemoryMapRequest,
-        RequestsEndMarker, RequestsStartMarker,
+        ExecutableAddressRequest, ExecutableCmdlineRequest, ExecutableFileRequest,
+        FramebufferRequest, HhdmRequest, MemoryMapRequest, MpRequest, RequestsEndMarker,
+        RequestsStartMarker, RsdpRequest,
     },
 };
 
@@ -36,6 +37,22 @@ pub static HHDM_REQUEST: HhdmRequest = HhdmRequest::new();
 #[unsafe(link_section = ".requests")]
 pub static EXECUTABLE_ADDRESS_REQUEST: ExecutableAddressRequest = ExecutableAddressRequest::new();
 
+#[used]
+#[unsafe(link_section = ".requests")]
+pub static EXECUTABLE_FILE_REQUEST: ExecutableFileRequest = ExecutableFileRequest::new();
+
+#[used]
+#[unsafe(link_section = ".requests")]
+pub static EXECUTABLE_CMDLINE_REQUEST: ExecutableCmdlineRequest = ExecutableCmdlineRequest::new();
+
+#[used]
+#[unsafe(link_section = ".requests")]
+pub static RSDP_REQUEST: RsdpRequest = RsdpRequest::new();
+
+#[used]
+#[unsafe(link_section = ".requests")]
+pub static MP_REQUEST: MpRequest = MpRequest::new();
+
 #[used]
 #[unsafe(link_section = ".requests_end_marker")]
 pub static _REQUESTS_END_MARKER: RequestsEndMarker = RequestsEndMarker::new();
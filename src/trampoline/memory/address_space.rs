@@ -0,0 +1,392 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Hands out non-overlapping ranges of kernel virtual memory so that nothing has to pick a magic
+//! address by hand.
+//!
+//! Every region is followed by an unmapped guard gap of at least one page, so running off the end
+//! of one region page faults instead of silently corrupting its neighbour.
+
+use alloc::vec::Vec;
+use spin::Mutex;
+use x86_64::{
+    PhysAddr, VirtAddr,
+    structures::paging::{
+        FrameAllocator, FrameDeallocator, Mapper, Page, PageSize, PageTableFlags, PhysFrame,
+        Size4KiB, mapper::MapToError,
+    },
+};
+
+use crate::trampoline::memory::error::{MapRangeError, MemoryError, MemoryRegion};
+
+/// Start of the window that dynamic kernel allocations are placed in.
+pub const DYNAMIC_WINDOW_START: u64 = 0x1000_0000_0000;
+/// End (exclusive) of the window that dynamic kernel allocations are placed in.
+pub const DYNAMIC_WINDOW_END: u64 = 0x4000_0000_0000;
+
+pub static KERNEL_ADDRESS_SPACE: Mutex<AddressSpace> =
+    Mutex::new(AddressSpace::new(DYNAMIC_WINDOW_START, DYNAMIC_WINDOW_END));
+
+/// A range of kernel virtual memory handed out by an [`AddressSpace`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VirtRegion {
+    pub start: VirtAddr,
+    pub size: u64,
+    pub purpose: MemoryRegion,
+}
+
+impl VirtRegion {
+    pub fn end(&self) -> VirtAddr {
+        self.start + self.size
+    }
+
+    pub fn contains(&self, addr: VirtAddr) -> bool {
+        addr >= self.start && addr < self.end()
+    }
+
+    /// Iterates over the pages of size `S` that make up this region.
+    pub fn pages<S: PageSize>(&self) -> impl Iterator<Item = Page<S>> {
+        let start = Page::<S>::containing_address(self.start);
+        (0..self.size.div_ceil(S::SIZE)).map(move |i| start + i)
+    }
+}
+
+/// Bookkeeping for which parts of a window of virtual memory are in use.
+///
+/// This only records the layout. Use [`map_fresh`], [`map_to_phys`] and [`unmap`] to actually
+/// change the page tables.
+pub struct AddressSpace {
+    window_start: u64,
+    window_end: u64,
+    /// Sorted by start address.
+    regions: Vec<VirtRegion>,
+}
+
+impl AddressSpace {
+    pub const fn new(window_start: u64, window_end: u64) -> Self {
+        Self {
+            window_start,
+            window_end,
+            regions: Vec::new(),
+        }
+    }
+
+    pub fn regions(&self) -> &[VirtRegion] {
+        &self.regions
+    }
+
+    /// Finds the region containing `addr`, if any.
+    pub fn region_containing(&self, addr: VirtAddr) -> Option<&VirtRegion> {
+        self.regions.iter().find(|region| region.contains(addr))
+    }
+
+    /// Reserves `size` bytes (rounded up to `align`) of virtual memory aligned to `align`.
+    ///
+    /// `align` must be a power of two and at least 4KiB.
+    pub fn allocate(
+        &mut self,
+        size: u64,
+        align: u64,
+        purpose: MemoryRegion,
+    ) -> Result<VirtRegion, MemoryError> {
+        assert!(align.is_power_of_two() && align >= Size4KiB::SIZE);
+
+        let size = size.next_multiple_of(align);
+        let guard = align;
+
+        let mut candidate = self.window_start.next_multiple_of(align);
+        let mut index = self.regions.len();
+
+        for (i, region) in self.regions.iter().enumerate() {
+            let region_start = region.start.as_u64();
+            let region_end = region.end().as_u64();
+
+            if region_end <= candidate {
+                continue;
+            }
+
+            if candidate + size + guard <= region_start {
+                index = i;
+                break;
+            }
+
+            candidate = (region_end + guard).next_multiple_of(align);
+        }
+
+        if candidate + size > self.window_end {
+            return Err(MemoryError::AddressSpaceExhausted(purpose));
+        }
+
+        let region = VirtRegion {
+            start: VirtAddr::new(candidate),
+            size,
+            purpose,
+        };
+        self.regions.insert(index, region);
+
+        Ok(region)
+    }
+
+    /// Records a region at a fixed address, e.g. one that was mapped before the heap existed.
+    pub fn reserve_fixed(
+        &mut self,
+        start: VirtAddr,
+        size: u64,
+        purpose: MemoryRegion,
+    ) -> Result<VirtRegion, MemoryError> {
+        let region = VirtRegion {
+            start,
+            size,
+            purpose,
+        };
+
+        if self
+            .regions
+            .iter()
+            .any(|other| other.start < region.end() && region.start < other.end())
+        {
+            return Err(MemoryError::Overlap(purpose));
+        }
+
+        let index = self
+            .regions
+            .partition_point(|other| other.start < region.start);
+        self.regions.insert(index, region);
+
+        Ok(region)
+    }
+
+    /// Forgets the region starting at `start` so its addresses can be handed out again.
+    ///
+    /// The region must already have been unmapped.
+    pub fn release(&mut self, start: VirtAddr) -> Option<VirtRegion> {
+        let index = self
+            .regions
+            .iter()
+            .position(|region| region.start == start)?;
+        Some(self.regions.remove(index))
+    }
+}
+
+/// Backs every page of `region` with a newly allocated frame.
+///
+/// # SAFETY
+/// `region` must not already be mapped, and must have come from an [`AddressSpace`] so that it
+/// does not alias any other mapping.
+pub unsafe fn map_fresh<S, M, A>(
+    mapper: &mut M,
+    frame_allocator: &mut A,
+    region: &VirtRegion,
+    flags: PageTableFlags,
+) -> Result<(), MemoryError>
+where
+    S: PageSize,
+    M: Mapper<S>,
+    A: FrameAllocator<S> + FrameAllocator<Size4KiB>,
+    MapRangeError: From<MapToError<S>>,
+{
+    for page in region.pages::<S>() {
+        let frame = FrameAllocator::<S>::allocate_frame(frame_allocator)
+            .ok_or(MemoryError::OutOfFrames(region.purpose))?;
+
+        // SAFETY: The caller guarantees that the region is unused.
+        unsafe {
+            mapper
+                .map_to(page, frame, flags, frame_allocator)
+                .map_err(|e| MemoryError::Map(region.purpose, e.into()))?
+                .flush();
+        }
+    }
+
+    Ok(())
+}
+
+/// Maps `region` onto the physically contiguous memory starting at `phys`, e.g. for MMIO.
+///
+/// # SAFETY
+/// Same as [`map_fresh`]. Additionally, the caller must make sure that accessing the physical
+/// memory through this mapping does not break any invariants.
+pub unsafe fn map_to_phys<S, M, A>(
+    mapper: &mut M,
+    frame_allocator: &mut A,
+    region: &VirtRegion,
+    phys: PhysAddr,
+    flags: PageTableFlags,
+) -> Result<(), MemoryError>
+where
+    S: PageSize,
+    M: Mapper<S>,
+    A: FrameAllocator<Size4KiB>,
+    MapRangeError: From<MapToError<S>>,
+{
+    let first_frame = PhysFrame::<S>::from_start_address(phys)
+        .map_err(|e| MemoryError::Map(region.purpose, MapRangeError::AddressNotAligned(e)))?;
+
+    for (i, page) in region.pages::<S>().enumerate() {
+        // SAFETY: The caller guarantees that the region is unused and the memory is safe to map.
+        unsafe {
+            mapper
+                .map_to(page, first_frame + i as u64, flags, frame_allocator)
+                .map_err(|e| MemoryError::Map(region.purpose, e.into()))?
+                .flush();
+        }
+    }
+
+    Ok(())
+}
+
+/// Unmaps every page of `region`, handing the frames back to `frame_deallocator` if one is given.
+///
+/// # SAFETY
+/// Nothing may access the region after it is unmapped. If a deallocator is given, the frames
+/// must not be mapped anywhere else.
+pub unsafe fn unmap<S, M, D>(
+    mapper: &mut M,
+    mut frame_deallocator: Option<&mut D>,
+    region: &VirtRegion,
+) -> Result<(), MemoryError>
+where
+    S: PageSize,
+    M: Mapper<S>,
+    D: FrameDeallocator<S>,
+{
+    for page in region.pages::<S>() {
+        let (frame, flush) = mapper
+            .unmap(page)
+            .map_err(|e| MemoryError::Unmap(region.purpose, e))?;
+        flush.flush();
+
+        if let Some(deallocator) = frame_deallocator.as_deref_mut() {
+            // SAFETY: The caller guarantees that the frame is not used anywhere else.
+            unsafe {
+                deallocator.deallocate_frame(frame);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use x86_64::{
+        VirtAddr,
+        structures::paging::{
+            OffsetPageTable, PageSize, PageTableFlags, Size2MiB, Size4KiB, Translate,
+        },
+    };
+
+    use super::*;
+    use crate::{
+        testing::with_boot_info,
+        trampoline::memory::{HHDM_OFFSET, error::MemoryRegion, get_pagetable},
+    };
+
+    const PURPOSE: MemoryRegion = MemoryRegion::Dynamic("test");
+
+    #[test_case]
+    fn allocations_are_aligned_and_disjoint() {
+        let mut space = AddressSpace::new(0x1000_0000_0000, 0x1000_4000_0000);
+
+        let a = space.allocate(1, Size4KiB::SIZE, PURPOSE).unwrap();
+        let b = space
+            .allocate(Size2MiB::SIZE, Size2MiB::SIZE, PURPOSE)
+            .unwrap();
+        let c = space
+            .allocate(3 * Size4KiB::SIZE, Size4KiB::SIZE, PURPOSE)
+            .unwrap();
+
+        assert_eq!(a.size, Size4KiB::SIZE);
+        assert!(b.start.is_aligned(Size2MiB::SIZE));
+        for (x, y) in [(a, b), (a, c), (b, c)] {
+            assert!(x.end() <= y.start || y.end() <= x.start);
+        }
+    }
+
+    #[test_case]
+    fn released_space_is_reused() {
+        let mut space = AddressSpace::new(0x1000_0000_0000, 0x1000_0010_0000);
+
+        let a = space
+            .allocate(Size4KiB::SIZE, Size4KiB::SIZE, PURPOSE)
+            .unwrap();
+        assert_eq!(space.release(a.start), Some(a));
+        assert_eq!(
+            space
+                .allocate(Size4KiB::SIZE, Size4KiB::SIZE, PURPOSE)
+                .unwrap(),
+            a
+        );
+    }
+
+    #[test_case]
+    fn exhausted_window_is_an_error() {
+        let mut space = AddressSpace::new(0x1000_0000_0000, 0x1000_0000_2000);
+
+        assert!(
+            space
+                .allocate(Size4KiB::SIZE, Size4KiB::SIZE, PURPOSE)
+                .is_ok()
+        );
+        assert!(matches!(
+            space.allocate(Size4KiB::SIZE, Size4KiB::SIZE, PURPOSE),
+            Err(MemoryError::AddressSpaceExhausted(PURPOSE))
+        ));
+    }
+
+    #[test_case]
+    fn fixed_regions_must_not_overlap() {
+        let mut space = AddressSpace::new(0x1000_0000_0000, 0x2000_0000_0000);
+        let start = VirtAddr::new(0x1000_0000_0000);
+
+        assert!(space.reserve_fixed(start, Size4KiB::SIZE, PURPOSE).is_ok());
+        assert!(matches!(
+            space.reserve_fixed(start + 0x800u64, Size4KiB::SIZE, PURPOSE),
+            Err(MemoryError::Overlap(PURPOSE))
+        ));
+    }
+
+    #[test_case]
+    fn map_write_and_unmap() {
+        // SAFETY: CR3 points to our own tables, which our HHDM covers.
+        let mut mapper =
+            unsafe { OffsetPageTable::new(get_pagetable(), VirtAddr::new(HHDM_OFFSET)) };
+        let region = KERNEL_ADDRESS_SPACE
+            .lock()
+            .allocate(2 * Size4KiB::SIZE, Size4KiB::SIZE, PURPOSE)
+            .unwrap();
+
+        with_boot_info(|boot_info| {
+            // SAFETY: The region was just allocated, so it is unmapped.
+            unsafe {
+                map_fresh::<Size4KiB, _, _>(
+                    &mut mapper,
+                    &mut boot_info.frame_allocator,
+                    &region,
+                    PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::NO_EXECUTE,
+                )
+                .unwrap();
+            }
+
+            let ptr = region.start.as_mut_ptr::<u64>();
+            // SAFETY: The region was mapped writable above.
+            unsafe {
+                ptr.write_volatile(0xdead_beef);
+                ptr.add(511).write_volatile(42);
+                assert_eq!(ptr.read_volatile(), 0xdead_beef);
+                assert_eq!(ptr.add(511).read_volatile(), 42);
+            }
+            assert!(mapper.translate_addr(region.start).is_some());
+
+            // SAFETY: Nothing uses the region anymore and its frames were allocated for it alone.
+            unsafe {
+                unmap::<Size4KiB, _, _>(&mut mapper, Some(&mut boot_info.frame_allocator), &region)
+                    .unwrap();
+            }
+        });
+
+        assert!(mapper.translate_addr(region.start).is_none());
+        KERNEL_ADDRESS_SPACE.lock().release(region.start);
+    }
+}
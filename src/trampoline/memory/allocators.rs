@@ -7,7 +7,9 @@ use alloc::vec::Vec;
 use limine::memory_map::EntryType;
 use x86_64::{
     PhysAddr,
-    structures::paging::{FrameAllocator, PageSize, PhysFrame, Size4KiB, frame::PhysFrameRange},
+    structures::paging::{
+        FrameAllocator, FrameDeallocator, PageSize, PhysFrame, Size4KiB, frame::PhysFrameRange,
+    },
 };
 
 use crate::trampoline::{
@@ -88,6 +90,10 @@ unsafe impl FrameAllocator<Size4KiB> for EarlyFrameAllocator {
 
 pub struct ProperFrameAllocator {
     availables: Vec<PhysFrameRange>,
+    /// Bootloader reclaimable memory, held back until [`Self::reclaim_bootloader_memory`].
+    bootloader: Vec<PhysFrameRange>,
+    /// ACPI reclaimable memory, held back until [`Self::reclaim_acpi_memory`].
+    acpi: Vec<PhysFrameRange>,
 }
 
 /// # SAFETY
@@ -118,6 +124,8 @@ impl ProperFrameAllocator {
 
     pub fn new(early_frame_allocator: EarlyFrameAllocator) -> Self {
         let mut availables = vec![];
+        let mut bootloader = vec![];
+        let mut acpi = vec![];
 
         for entry in MEMMAP_REQUEST
             .get_response()
@@ -125,10 +133,17 @@ impl ProperFrameAllocator {
             .entries()
         {
             match entry.entry_type {
-                // Early frame allocator does not allocate from reclaimable memory
-                EntryType::BOOTLOADER_RECLAIMABLE | EntryType::ACPI_RECLAIMABLE => {
+                // Still holds the ACPI tables, which haven't been parsed yet.
+                EntryType::ACPI_RECLAIMABLE => {
                     // SAFETY: memmap entries by Limine should be aligned.
-                    Self::push_range(&mut availables, unsafe {
+                    Self::push_range(&mut acpi, unsafe {
+                        address_range_unchecked(entry.base, entry.length)
+                    });
+                }
+                // Still in use by Limine's responses and the parked application processors.
+                EntryType::BOOTLOADER_RECLAIMABLE => {
+                    // SAFETY: memmap entries by Limine should be aligned.
+                    Self::push_range(&mut bootloader, unsafe {
                         address_range_unchecked(entry.base, entry.length)
                     });
                 }
@@ -165,7 +180,38 @@ impl ProperFrameAllocator {
             }
         }
 
-        Self { availables }
+        Self {
+            availables,
+            bootloader,
+            acpi,
+        }
+    }
+
+    /// Starts handing out the memory Limine used, which holds its responses and the stacks the
+    /// application processors start on. Must only be called once every processor has left its
+    /// Limine stack.
+    pub fn reclaim_bootloader_memory(&mut self) {
+        for range in core::mem::take(&mut self.bootloader) {
+            Self::push_range(&mut self.availables, range);
+        }
+    }
+
+    /// Never hands out the memory Limine used, for when something still runs in it.
+    pub fn keep_bootloader_memory(&mut self) {
+        self.bootloader.clear();
+    }
+
+    /// Starts handing out the memory the firmware's ACPI tables are in. Must only be called once
+    /// the tables have been copied by [`acpi::init`](crate::trampoline::acpi::init).
+    pub fn reclaim_acpi_memory(&mut self) {
+        for range in core::mem::take(&mut self.acpi) {
+            Self::push_range(&mut self.availables, range);
+        }
+    }
+
+    /// The physical ranges that are still free to allocate from, in no particular order.
+    pub fn free_ranges(&self) -> &[PhysFrameRange] {
+        &self.availables
     }
 }
 
@@ -202,8 +248,8 @@ unsafe impl<S: PageSize> FrameAllocator<S> for ProperFrameAllocator {
         }
 
         // This will put the new range at the back, which means it can't merge with other ranges if
-        // any ranges are then freed. However, in this project, we don't need to free frames ever.
-        // So that's fine.
+        // any ranges are then freed. Frames are only freed rarely (when a dynamic region is
+        // unmapped), so the fragmentation doesn't matter much.
         if let Some(additional_range) = additional_range {
             self.availables.push(additional_range);
         }
@@ -214,3 +260,63 @@ unsafe impl<S: PageSize> FrameAllocator<S> for ProperFrameAllocator {
         })
     }
 }
+
+impl<S: PageSize> FrameDeallocator<S> for ProperFrameAllocator {
+    unsafe fn deallocate_frame(&mut self, frame: PhysFrame<S>) {
+        // SAFETY: A frame of size S is always aligned to S, so also to 4KiB.
+        let range = unsafe { address_range_unchecked(frame.start_address().as_u64(), S::SIZE) };
+        Self::push_range(&mut self.availables, range);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, vec::Vec};
+
+    use x86_64::structures::paging::{FrameAllocator, FrameDeallocator, PhysFrame, Size4KiB};
+
+    use crate::{testing::with_boot_info, trampoline::memory::heap_usage};
+
+    #[test_case]
+    fn boxed_value() {
+        let value = Box::new(41);
+        assert_eq!(*value + 1, 42);
+    }
+
+    #[test_case]
+    fn large_vec() {
+        let n = 1000;
+        let vec: Vec<u64> = (0..n).collect();
+        assert_eq!(vec.iter().sum::<u64>(), (n - 1) * n / 2);
+    }
+
+    #[test_case]
+    fn freed_memory_is_reused() {
+        let (used_before, size) = heap_usage();
+
+        // Allocates far more than the heap holds in total, which only works if frees are reused.
+        for i in 0..size {
+            let value = Box::new(i);
+            assert_eq!(*value, i);
+        }
+
+        assert_eq!(heap_usage().0, used_before);
+    }
+
+    #[test_case]
+    fn frames_are_distinct_and_reusable() {
+        with_boot_info(|boot_info| {
+            let allocator = &mut boot_info.frame_allocator;
+
+            let a: PhysFrame<Size4KiB> = allocator.allocate_frame().expect("Frame available.");
+            let b: PhysFrame<Size4KiB> = allocator.allocate_frame().expect("Frame available.");
+            assert_ne!(a, b);
+
+            // SAFETY: Neither frame was ever mapped.
+            unsafe {
+                allocator.deallocate_frame(a);
+                allocator.deallocate_frame(b);
+            }
+        });
+    }
+}
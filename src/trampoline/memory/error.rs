@@ -0,0 +1,189 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use core::fmt;
+
+use x86_64::structures::paging::{
+    PageSize, Size1GiB, Size2MiB, Size4KiB,
+    mapper::{MapToError, UnmapError},
+    page::AddressNotAligned,
+};
+
+use crate::trampoline::elf::ElfError;
+
+/// The part of the address space that was being set up when something went wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryRegion {
+    PageTable,
+    Hhdm,
+    KernelText,
+    KernelRodata,
+    KernelData,
+    KernelImage,
+    Stack,
+    Heap,
+    BackBuffer,
+    /// A region allocated at runtime through the kernel address space, named by its owner.
+    Dynamic(&'static str),
+}
+
+impl fmt::Display for MemoryRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::PageTable => "top level page table",
+            Self::Hhdm => "higher half direct map",
+            Self::KernelText => "kernel .text",
+            Self::KernelRodata => "kernel .rodata",
+            Self::KernelData => "kernel .data",
+            Self::KernelImage => "kernel executable",
+            Self::Stack => "kernel stack",
+            Self::Heap => "kernel heap",
+            Self::BackBuffer => "framebuffer back buffer",
+            Self::Dynamic(name) => name,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum MapRangeError {
+    AddressNotAligned(AddressNotAligned),
+    MapToError4KiB(MapToError<Size4KiB>),
+    MapToError2MiB(MapToError<Size2MiB>),
+    MapToError1GiB(MapToError<Size1GiB>),
+}
+
+impl MapRangeError {
+    pub fn is_out_of_memory(&self) -> bool {
+        matches!(
+            self,
+            Self::MapToError4KiB(MapToError::FrameAllocationFailed)
+                | Self::MapToError2MiB(MapToError::FrameAllocationFailed)
+                | Self::MapToError1GiB(MapToError::FrameAllocationFailed)
+        )
+    }
+
+    pub fn is_already_mapped(&self) -> bool {
+        matches!(
+            self,
+            Self::MapToError4KiB(MapToError::ParentEntryHugePage)
+                | Self::MapToError4KiB(MapToError::PageAlreadyMapped(_))
+                | Self::MapToError2MiB(MapToError::ParentEntryHugePage)
+                | Self::MapToError2MiB(MapToError::PageAlreadyMapped(_))
+                | Self::MapToError1GiB(MapToError::ParentEntryHugePage)
+                | Self::MapToError1GiB(MapToError::PageAlreadyMapped(_))
+        )
+    }
+}
+
+impl From<MapToError<Size4KiB>> for MapRangeError {
+    fn from(value: MapToError<Size4KiB>) -> Self {
+        Self::MapToError4KiB(value)
+    }
+}
+
+impl From<MapToError<Size2MiB>> for MapRangeError {
+    fn from(value: MapToError<Size2MiB>) -> Self {
+        Self::MapToError2MiB(value)
+    }
+}
+
+impl From<MapToError<Size1GiB>> for MapRangeError {
+    fn from(value: MapToError<Size1GiB>) -> Self {
+        Self::MapToError1GiB(value)
+    }
+}
+
+fn fmt_map_to_error<S: PageSize>(error: &MapToError<S>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match error {
+        MapToError::FrameAllocationFailed => {
+            f.write_str("out of frames while allocating a page table")
+        }
+        MapToError::ParentEntryHugePage => f.write_str("parent entry is already a huge page"),
+        MapToError::PageAlreadyMapped(frame) => write!(
+            f,
+            "page is already mapped to {:#x}",
+            frame.start_address().as_u64()
+        ),
+    }
+}
+
+impl fmt::Display for MapRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddressNotAligned(e) => write!(f, "{e}"),
+            Self::MapToError4KiB(e) => fmt_map_to_error(e, f),
+            Self::MapToError2MiB(e) => fmt_map_to_error(e, f),
+            Self::MapToError1GiB(e) => fmt_map_to_error(e, f),
+        }
+    }
+}
+
+/// Kernel-wide error for anything that goes wrong while setting up or changing memory mappings.
+#[derive(Debug)]
+pub enum MemoryError {
+    /// No physical frame was left to back `region`.
+    OutOfFrames(MemoryRegion),
+    /// Mapping `region` into the page tables failed.
+    Map(MemoryRegion, MapRangeError),
+    /// Unmapping `region` from the page tables failed.
+    Unmap(MemoryRegion, UnmapError),
+    /// There is no free virtual address range large enough for `region`.
+    AddressSpaceExhausted(MemoryRegion),
+    /// `region` overlaps with a region that has already been reserved.
+    Overlap(MemoryRegion),
+    /// `region` would have been mapped both writable and executable.
+    WritableAndExecutable(MemoryRegion),
+    /// Part of `region` should have been mapped but isn't.
+    NotMapped(MemoryRegion),
+    /// The kernel's own ELF file could not be parsed.
+    InvalidKernelImage(ElfError),
+}
+
+impl MemoryError {
+    pub fn region(&self) -> MemoryRegion {
+        match self {
+            Self::OutOfFrames(region)
+            | Self::Map(region, _)
+            | Self::Unmap(region, _)
+            | Self::AddressSpaceExhausted(region)
+            | Self::Overlap(region)
+            | Self::WritableAndExecutable(region)
+            | Self::NotMapped(region) => *region,
+            Self::InvalidKernelImage(_) => MemoryRegion::KernelImage,
+        }
+    }
+
+    pub fn is_out_of_memory(&self) -> bool {
+        match self {
+            Self::OutOfFrames(_) => true,
+            Self::Map(_, e) => e.is_out_of_memory(),
+            _ => false,
+        }
+    }
+
+    /// Returns a closure usable with [`Result::map_err`] that tags a [`MapRangeError`] with the
+    /// region being mapped.
+    pub fn mapping(region: MemoryRegion) -> impl FnOnce(MapRangeError) -> Self {
+        move |e| Self::Map(region, e)
+    }
+}
+
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfFrames(region) => write!(f, "out of physical memory while mapping {region}"),
+            Self::Map(region, e) => write!(f, "failed to map {region}: {e}"),
+            Self::Unmap(region, e) => write!(f, "failed to unmap {region}: {e:?}"),
+            Self::AddressSpaceExhausted(region) => {
+                write!(f, "no free virtual address range for {region}")
+            }
+            Self::Overlap(region) => write!(f, "{region} overlaps an existing region"),
+            Self::WritableAndExecutable(region) => {
+                write!(f, "{region} is both writable and executable")
+            }
+            Self::NotMapped(region) => write!(f, "{region} is not mapped"),
+            Self::InvalidKernelImage(e) => write!(f, "invalid kernel executable: {e}"),
+        }
+    }
+}
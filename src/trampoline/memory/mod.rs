@@ -2,20 +2,26 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod address_space;
 pub mod allocators;
-
-use core::panic;
+pub mod error;
+pub mod walk;
 
 use alloc::vec;
 use alloc::vec::Vec;
+use core::ops::Range;
 use limine::memory_map::EntryType;
 use linked_list_allocator::LockedHeap;
+use spin::Once;
 use x86_64::{
     PhysAddr, VirtAddr,
-    registers::control::{Cr3, Cr3Flags},
+    registers::{
+        control::{Cr0, Cr0Flags, Cr3, Cr3Flags},
+        model_specific::{Efer, EferFlags},
+    },
     structures::paging::{
         FrameAllocator, Mapper, OffsetPageTable, Page, PageSize, PageTable, PageTableFlags,
-        PhysFrame, Size1GiB, Size2MiB, Size4KiB, mapper::MapToError, page::AddressNotAligned,
+        PhysFrame, Size1GiB, Size2MiB, Size4KiB, Translate, mapper::TranslateResult,
     },
 };
 
@@ -23,26 +29,18 @@ use crate::{
     debug::rendering::DEBUG_FRAMEBUFFER,
     limine_requests::{EXECUTABLE_ADDRESS_REQUEST, HHDM_REQUEST, MEMMAP_REQUEST},
     trampoline::{
-        framebuffer::Framebuffer,
-        limine_requests::FRAMEBUFFER_REQUEST,
-        memory::allocators::{EarlyFrameAllocator, ProperFrameAllocator, init_allocator},
+        elf::{ElfFile, ProgramHeader},
+        framebuffer::{self, Framebuffer},
+        limine_requests::{EXECUTABLE_FILE_REQUEST, FRAMEBUFFER_REQUEST},
+        memory::{
+            address_space::{KERNEL_ADDRESS_SPACE, VirtRegion},
+            allocators::{EarlyFrameAllocator, ProperFrameAllocator, init_allocator},
+            error::{MapRangeError, MemoryError, MemoryRegion},
+        },
     },
     util::page_from_addr,
 };
 
-// Linker symbols placed at the boundaries of kernel sections
-// SAFETY: linker symbols should be present as specified in the linker script.
-unsafe extern "C" {
-    static __text_start: u8;
-    static __text_end: u8;
-    static __rodata_start: u8;
-    static __rodata_end: u8;
-    static __data_start: u8;
-    static __data_end: u8;
-    static __got_start: u8;
-    static __got_end: u8;
-}
-
 pub const HHDM_OFFSET: u64 = 0xffff_8000_0000_0000;
 
 pub const STACK_BASE: Page<Size4KiB> = page_from_addr(0x4888_8888_0000);
@@ -53,7 +51,77 @@ pub const HEAP_PAGES: u64 = 25; // 100KiB
 #[global_allocator]
 static ALLOCATOR: LockedHeap = LockedHeap::empty();
 
-pub fn initialize_paging() -> ProperFrameAllocator {
+/// Bytes of the kernel heap that are in use, and its total size.
+pub fn heap_usage() -> (usize, usize) {
+    let heap = ALLOCATOR.lock();
+    (heap.used(), heap.size())
+}
+
+/// A region of physical memory from Limine's memory map.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryMapEntry {
+    pub base: u64,
+    pub length: u64,
+    pub entry_type: EntryType,
+}
+
+static MEMORY_MAP: Once<Vec<MemoryMapEntry>> = Once::new();
+
+/// Copies Limine's memory map onto the heap, where it survives bootloader memory being reclaimed.
+pub fn save_memory_map() {
+    MEMORY_MAP.call_once(|| memory_map().into_iter().flatten().collect());
+}
+
+/// The memory map as Limine handed it to us, or `None` if it didn't. Reads the copy once
+/// [`save_memory_map`] has made one, and Limine's response before that.
+pub fn memory_map() -> Option<impl Iterator<Item = MemoryMapEntry>> {
+    let saved = MEMORY_MAP.get();
+    let limine = match saved {
+        Some(_) => None,
+        None => Some(MEMMAP_REQUEST.get_response()?.entries()),
+    };
+
+    Some(
+        saved
+            .into_iter()
+            .flatten()
+            .copied()
+            .chain(limine.into_iter().flatten().map(|entry| MemoryMapEntry {
+                base: entry.base,
+                length: entry.length,
+                entry_type: entry.entry_type,
+            })),
+    )
+}
+
+/// Physical memory as reported by Limine, in bytes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryStats {
+    pub usable: u64,
+    pub reclaimable: u64,
+    pub entries: usize,
+}
+
+impl MemoryStats {
+    pub fn from_memmap() -> Self {
+        let mut stats = Self::default();
+
+        for entry in memory_map().into_iter().flatten() {
+            stats.entries += 1;
+            match entry.entry_type {
+                EntryType::USABLE => stats.usable += entry.length,
+                EntryType::BOOTLOADER_RECLAIMABLE | EntryType::ACPI_RECLAIMABLE => {
+                    stats.reclaimable += entry.length
+                }
+                _ => {}
+            }
+        }
+
+        stats
+    }
+}
+
+pub fn initialize_paging() -> Result<ProperFrameAllocator, MemoryError> {
     let hhdm_offset = HHDM_REQUEST
         .get_response()
         .expect("Response should be provided by Limine.")
@@ -63,7 +131,7 @@ pub fn initialize_paging() -> ProperFrameAllocator {
     let mut frame_allocator = EarlyFrameAllocator::new();
     let page_table_addr = frame_allocator
         .allocate_frame()
-        .unwrap_or_else(|| panic!("Out of memory when allocating frame for page table."))
+        .ok_or(MemoryError::OutOfFrames(MemoryRegion::PageTable))?
         .start_address()
         .as_u64()
         + hhdm_offset;
@@ -83,16 +151,12 @@ pub fn initialize_paging() -> ProperFrameAllocator {
     let mut offset_page_table =
         unsafe { OffsetPageTable::new(&mut *page_table_ptr, VirtAddr::new(hhdm_offset)) };
 
-    map_hhdm(&mut offset_page_table, &mut frame_allocator);
-
-    // SAFETY: We have just mapped the HHDM in the line above.
-    unsafe {
-        DEBUG_FRAMEBUFFER.lock().override_addr(HHDM_OFFSET);
-    }
+    map_hhdm(&mut offset_page_table, &mut frame_allocator)?;
+    map_kernel(&mut offset_page_table, &mut frame_allocator, hhdm_offset)?;
+    map_stack(&mut offset_page_table, &mut frame_allocator)?;
+    map_heap(&mut offset_page_table, &mut frame_allocator)?;
 
-    map_kernel(&mut offset_page_table, &mut frame_allocator);
-    map_stack(&mut offset_page_table, &mut frame_allocator);
-    map_heap(&mut offset_page_table, &mut frame_allocator);
+    enable_protection_bits();
 
     // SAFETY: after switching to our own paging we will also be switching stack and calling a new
     // entry point function. This means we won't rely on any references that still used the old
@@ -105,23 +169,50 @@ pub fn initialize_paging() -> ProperFrameAllocator {
         );
     }
 
+    if let Some(framebuffer) = DEBUG_FRAMEBUFFER.lock().as_mut() {
+        // SAFETY: Our HHDM is active now that we have switched page tables. This is done only
+        // after the switch so that a failed mapping above can still be reported through Limine's
+        // HHDM.
+        unsafe {
+            framebuffer.override_addr(HHDM_OFFSET);
+        }
+    }
+
+    // SAFETY: CR3 now points to the table we just built and our HHDM is active.
+    let offset_page_table =
+        unsafe { OffsetPageTable::new(get_pagetable(), VirtAddr::new(HHDM_OFFSET)) };
+    verify_kernel_wx(&offset_page_table)?;
+
     // SAFETY: Allocator is initialized after page tables are setup.
     unsafe {
         init_allocator();
     }
 
-    ProperFrameAllocator::new(frame_allocator)
+    // The stack and heap had to be mapped before there was a heap to keep track of them, so they
+    // are recorded now to keep dynamic allocations away from them.
+    let mut address_space = KERNEL_ADDRESS_SPACE.lock();
+    address_space.reserve_fixed(
+        STACK_BASE.start_address(),
+        STACK_PAGES * Size4KiB::SIZE,
+        MemoryRegion::Stack,
+    )?;
+    address_space.reserve_fixed(
+        HEAP_BASE.start_address(),
+        HEAP_PAGES * Size4KiB::SIZE,
+        MemoryRegion::Heap,
+    )?;
+    drop(address_space);
+
+    Ok(ProperFrameAllocator::new(frame_allocator))
 }
 
 pub fn map_framebuffers(
     offset_page_table: &mut OffsetPageTable,
     frame_allocator: &mut ProperFrameAllocator,
-) -> Vec<Framebuffer> {
+) -> Result<Vec<Framebuffer<'static>>, MemoryError> {
     let mut framebuffers = vec![];
 
     if let Some(response) = FRAMEBUFFER_REQUEST.get_response() {
-        let mut back_buffer_page = Framebuffer::FRAMEBUFFER_BASE;
-
         for framebuffer in response.framebuffers() {
             let framebuffer_size = framebuffer.pitch() * framebuffer.height();
 
@@ -132,50 +223,160 @@ pub fn map_framebuffers(
                     .offset()
                 + HHDM_OFFSET;
 
-            // SAFETY: We will map the memory later in this loop (before the framebuffer vector is
-            // ever used) so the memory will be valid.
+            let back_buffer = KERNEL_ADDRESS_SPACE.lock().allocate(
+                framebuffer_size,
+                Size2MiB::SIZE,
+                MemoryRegion::BackBuffer,
+            )?;
+
+            // SAFETY: The region was just handed out by the kernel address space so nothing else
+            // is mapped there.
+            unsafe {
+                address_space::map_fresh::<Size2MiB, _, _>(
+                    offset_page_table,
+                    frame_allocator,
+                    &back_buffer,
+                    PageTableFlags::PRESENT
+                        | PageTableFlags::HUGE_PAGE
+                        | PageTableFlags::WRITABLE
+                        | PageTableFlags::NO_EXECUTE,
+                )?;
+            }
+
+            // SAFETY: The back buffer was mapped above and the front buffer is covered by our
+            // HHDM. Nothing else uses either of them.
             let new_framebuffer = unsafe {
-                Framebuffer::from_limine_framebuffer(
+                framebuffer::from_limine_framebuffer(
                     &framebuffer,
-                    &mut *(back_buffer_page.start_address().as_u64() as *mut u8),
-                    &mut *(new_framebuffer_virt as *mut u8),
+                    back_buffer.start.as_mut_ptr(),
+                    new_framebuffer_virt as *mut u8,
                 )
             };
 
-            // Allocate back buffer
-            let num_frames = framebuffer_size.div_ceil(Size2MiB::SIZE);
-
-            for _ in 0..num_frames {
-                let frame: PhysFrame<Size2MiB> = frame_allocator
-                    .allocate_frame()
-                    .unwrap_or_else(|| panic!("Out of memory when allocating back buffer!"));
-                // SAFETY: Back buffers are only mapped once.
-                unsafe {
-                    offset_page_table
-                        .map_to(
-                            back_buffer_page,
-                            frame,
-                            PageTableFlags::PRESENT
-                                | PageTableFlags::HUGE_PAGE
-                                | PageTableFlags::WRITABLE
-                                | PageTableFlags::NO_EXECUTE,
-                            frame_allocator,
-                        )
-                        .unwrap_or_else(|e| panic!("Failed to map back buffer! {e:#?}"))
-                        .flush();
+            let new_framebuffer = match new_framebuffer {
+                Ok(new_framebuffer) => new_framebuffer,
+                Err(e) => {
+                    log::warn!("Skipping framebuffer: {e}.");
+
+                    // SAFETY: Nothing was given the back buffer, and its frames were allocated
+                    // for it alone.
+                    unsafe {
+                        address_space::unmap::<Size2MiB, _, _>(
+                            offset_page_table,
+                            Some(&mut *frame_allocator),
+                            &back_buffer,
+                        )?;
+                    }
+                    KERNEL_ADDRESS_SPACE.lock().release(back_buffer.start);
+                    continue;
                 }
-                // Page increments by increments of its page size.
-                back_buffer_page += 1;
-            }
-
-            // Guard page + alignment
-            back_buffer_page += 1;
+            };
 
             framebuffers.push(new_framebuffer);
         }
     }
 
-    framebuffers
+    Ok(framebuffers)
+}
+
+/// Maps `size` bytes of physical memory starting at `phys` into the dynamic window, for memory
+/// that our HHDM doesn't cover such as MMIO registers or firmware tables in reserved memory.
+/// Returns the virtual address that `phys` ends up at.
+///
+/// # SAFETY
+/// Same as [`address_space::map_to_phys`].
+pub unsafe fn map_physical(
+    offset_page_table: &mut OffsetPageTable,
+    frame_allocator: &mut ProperFrameAllocator,
+    phys: PhysAddr,
+    size: u64,
+    purpose: MemoryRegion,
+    flags: PageTableFlags,
+) -> Result<VirtAddr, MemoryError> {
+    let start = phys.align_down(Size4KiB::SIZE);
+    let offset = phys - start;
+
+    let region = KERNEL_ADDRESS_SPACE
+        .lock()
+        .allocate(offset + size, Size4KiB::SIZE, purpose)?;
+
+    // SAFETY: The region was just handed out by the kernel address space, and the caller
+    // guarantees that the physical memory is safe to map.
+    unsafe {
+        address_space::map_to_phys::<Size4KiB, _, _>(
+            offset_page_table,
+            frame_allocator,
+            &region,
+            start,
+            flags | PageTableFlags::PRESENT,
+        )
+    }
+    .inspect_err(|_| {
+        KERNEL_ADDRESS_SPACE.lock().release(region.start);
+    })?;
+
+    Ok(region.start + offset)
+}
+
+/// Maps a fresh stack of `size` bytes into the dynamic window, with an unmapped guard page below it
+/// so that an overflow faults instead of running into the next region. Returns the bounds of the
+/// usable stack.
+pub fn allocate_stack(
+    offset_page_table: &mut OffsetPageTable,
+    frame_allocator: &mut ProperFrameAllocator,
+    size: u64,
+    purpose: MemoryRegion,
+) -> Result<Range<u64>, MemoryError> {
+    let region =
+        KERNEL_ADDRESS_SPACE
+            .lock()
+            .allocate(size + Size4KiB::SIZE, Size4KiB::SIZE, purpose)?;
+    let stack = VirtRegion {
+        start: region.start + Size4KiB::SIZE,
+        size,
+        purpose,
+    };
+
+    // SAFETY: The region was just handed out by the kernel address space.
+    unsafe {
+        address_space::map_fresh::<Size4KiB, _, _>(
+            offset_page_table,
+            frame_allocator,
+            &stack,
+            PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::NO_EXECUTE,
+        )
+    }
+    .inspect_err(|_| {
+        KERNEL_ADDRESS_SPACE.lock().release(region.start);
+    })?;
+
+    Ok(stack.start.as_u64()..stack.end().as_u64())
+}
+
+/// Undoes [`map_physical`] for the mapping containing `virt`. The physical memory is left alone.
+///
+/// # SAFETY
+/// Nothing may access the mapping afterwards.
+pub unsafe fn unmap_physical(
+    offset_page_table: &mut OffsetPageTable,
+    virt: VirtAddr,
+) -> Result<(), MemoryError> {
+    let Some(region) = KERNEL_ADDRESS_SPACE.lock().region_containing(virt).copied() else {
+        return Ok(());
+    };
+
+    // SAFETY: The caller guarantees the mapping is no longer used, and no deallocator is given so
+    // the physical memory isn't touched.
+    unsafe {
+        address_space::unmap::<Size4KiB, _, ProperFrameAllocator>(
+            offset_page_table,
+            None,
+            &region,
+        )?;
+    }
+    KERNEL_ADDRESS_SPACE.lock().release(region.start);
+
+    Ok(())
 }
 
 pub fn get_pagetable<'a>() -> &'a mut PageTable {
@@ -185,7 +386,10 @@ pub fn get_pagetable<'a>() -> &'a mut PageTable {
     unsafe { &mut *(page_table_addr as *mut PageTable) }
 }
 
-fn map_hhdm(offset_page_table: &mut OffsetPageTable, frame_allocator: &mut EarlyFrameAllocator) {
+fn map_hhdm(
+    offset_page_table: &mut OffsetPageTable,
+    frame_allocator: &mut EarlyFrameAllocator,
+) -> Result<(), MemoryError> {
     for entry in MEMMAP_REQUEST
         .get_response()
         .expect("Response should be provided by Limine.")
@@ -210,162 +414,197 @@ fn map_hhdm(offset_page_table: &mut OffsetPageTable, frame_allocator: &mut Early
                 flags,
             )
         }
-        .unwrap_or_else(|e| panic!("Failed to map HHDM! {e:#?}"));
+        .map_err(MemoryError::mapping(MemoryRegion::Hhdm))?;
     }
+
+    Ok(())
 }
 
-fn map_kernel(offset_page_table: &mut OffsetPageTable, frame_allocator: &mut EarlyFrameAllocator) {
-    let mut phys = EXECUTABLE_ADDRESS_REQUEST
+/// Returns the kernel's own ELF file as loaded by Limine, accessed through the HHDM at
+/// `hhdm_offset`.
+pub fn kernel_elf(hhdm_offset: u64) -> Result<ElfFile<'static>, MemoryError> {
+    let file = EXECUTABLE_FILE_REQUEST
         .get_response()
         .expect("Response should be provided by Limine.")
-        .physical_base();
+        .file();
 
-    let text_length = (&raw const __text_end) as u64 - (&raw const __text_start) as u64;
-    // SAFETY: this mapping should map the kernel to exactly where it expects itself.
-    unsafe {
-        map_range(
-            offset_page_table,
-            frame_allocator,
-            PhysAddr::new(phys),
-            VirtAddr::from_ptr(&raw const __text_start),
-            text_length,
-            PageTableFlags::PRESENT,
-        )
-    }
-    .unwrap_or_else(|e| panic!("Failed to map kernel .text! {e:#?}"));
-    // Kernel executable is guaranteed to be physically contiguous according to Limine.
-    phys += text_length;
+    let limine_hhdm_offset = HHDM_REQUEST
+        .get_response()
+        .expect("Response should be provided by Limine.")
+        .offset();
+    let addr = file.addr() as u64 - limine_hhdm_offset + hhdm_offset;
 
-    let rodata_length = (&raw const __rodata_end) as u64 - (&raw const __rodata_start) as u64;
-    // SAFETY: this mapping should map the kernel to exactly where it expects itself.
-    unsafe {
-        map_range(
-            offset_page_table,
-            frame_allocator,
-            PhysAddr::new(phys),
-            VirtAddr::from_ptr(&raw const __rodata_start),
-            rodata_length,
-            PageTableFlags::PRESENT | PageTableFlags::NO_EXECUTE,
-        )
-    }
-    .unwrap_or_else(|e| panic!("Failed to map kernel .rodata! {e:#?}"));
-    phys += rodata_length;
+    // SAFETY: Limine loads the whole file into EXECUTABLE_AND_MODULES memory, which is never
+    // reclaimed and is covered by both Limine's and our HHDM. The caller passes the offset of
+    // whichever HHDM is currently active.
+    let data = unsafe { core::slice::from_raw_parts(addr as *const u8, file.size() as usize) };
 
-    let data_length = (&raw const __data_end) as u64 - (&raw const __data_start) as u64;
-    // SAFETY: this mapping should map the kernel to exactly where it expects itself.
-    unsafe {
-        map_range(
-            offset_page_table,
-            frame_allocator,
-            PhysAddr::new(phys),
-            VirtAddr::from_ptr(&raw const __data_start),
-            data_length,
-            PageTableFlags::PRESENT | PageTableFlags::NO_EXECUTE | PageTableFlags::WRITABLE,
-        )
-    }
-    .unwrap_or_else(|e| panic!("Failed to map kernel .data! {e:#?}"));
-    phys += data_length;
+    ElfFile::parse(data).map_err(MemoryError::InvalidKernelImage)
+}
 
-    let got_length = (&raw const __got_end) as u64 - (&raw const __got_start) as u64;
-    // SAFETY: this mapping should map the kernel to exactly where it expects itself.
-    unsafe {
-        map_range(
-            offset_page_table,
-            frame_allocator,
-            PhysAddr::new(phys),
-            VirtAddr::from_ptr(&raw const __got_start),
-            got_length,
-            PageTableFlags::PRESENT | PageTableFlags::NO_EXECUTE,
-        )
+fn segment_region(segment: &ProgramHeader) -> MemoryRegion {
+    if segment.is_executable() {
+        MemoryRegion::KernelText
+    } else if segment.is_writable() {
+        MemoryRegion::KernelData
+    } else {
+        MemoryRegion::KernelRodata
     }
-    .unwrap_or_else(|e| panic!("Failed to map kernel .got! {e:#?}"));
 }
 
-fn map_stack(offset_page_table: &mut OffsetPageTable, frame_allocator: &mut EarlyFrameAllocator) {
-    // i represents number of pages
-    for i in 0u64..STACK_PAGES {
-        let frame = frame_allocator
-            .allocate_frame()
-            .unwrap_or_else(|| panic!("Out of memory while allocating frames for stack."));
-
-        // SAFETY: Stack is only mapped once.
-        let result = unsafe {
-            offset_page_table.map_to(
-                STACK_BASE + i,
-                frame,
-                PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::NO_EXECUTE,
+/// Maps every `PT_LOAD` segment of the kernel with the permissions from its program header.
+fn map_kernel(
+    offset_page_table: &mut OffsetPageTable,
+    frame_allocator: &mut EarlyFrameAllocator,
+    hhdm_offset: u64,
+) -> Result<(), MemoryError> {
+    let executable_address = EXECUTABLE_ADDRESS_REQUEST
+        .get_response()
+        .expect("Response should be provided by Limine.");
+
+    for segment in kernel_elf(hhdm_offset)?
+        .program_headers()
+        .filter(ProgramHeader::is_load)
+    {
+        let region = segment_region(&segment);
+
+        if segment.is_writable() && segment.is_executable() {
+            return Err(MemoryError::WritableAndExecutable(region));
+        }
+
+        let mut flags = PageTableFlags::PRESENT;
+        if segment.is_writable() {
+            flags |= PageTableFlags::WRITABLE;
+        }
+        if !segment.is_executable() {
+            flags |= PageTableFlags::NO_EXECUTE;
+        }
+
+        // .bss is included since memsz covers it. The linker script page aligns every segment so
+        // rounding out to page boundaries never makes two segments share a page.
+        let virt_start = VirtAddr::new(segment.p_vaddr).align_down(Size4KiB::SIZE);
+        let virt_end = VirtAddr::new(segment.p_vaddr + segment.p_memsz).align_up(Size4KiB::SIZE);
+
+        // Kernel executable is guaranteed to be physically contiguous according to Limine.
+        let phys = PhysAddr::new(
+            executable_address.physical_base()
+                + (virt_start.as_u64() - executable_address.virtual_base()),
+        );
+
+        // SAFETY: this mapping should map the kernel to exactly where it expects itself.
+        unsafe {
+            map_range(
+                offset_page_table,
                 frame_allocator,
+                phys,
+                virt_start,
+                virt_end - virt_start,
+                flags,
             )
-        };
+        }
+        .map_err(MemoryError::mapping(region))?;
+    }
+
+    Ok(())
+}
 
-        if let Err(MapToError::FrameAllocationFailed) = result {
-            panic!("Out of memory while allocating frame for page table.");
+/// Walks the active page tables over the whole kernel image and fails if any page is both
+/// writable and executable.
+fn verify_kernel_wx(offset_page_table: &OffsetPageTable) -> Result<(), MemoryError> {
+    for segment in kernel_elf(HHDM_OFFSET)?
+        .program_headers()
+        .filter(ProgramHeader::is_load)
+    {
+        let region = segment_region(&segment);
+        let start = VirtAddr::new(segment.p_vaddr).align_down(Size4KiB::SIZE);
+        let end = VirtAddr::new(segment.p_vaddr + segment.p_memsz);
+
+        let mut addr = start;
+        while addr < end {
+            match offset_page_table.translate(addr) {
+                TranslateResult::Mapped { flags, .. } => {
+                    if flags.contains(PageTableFlags::WRITABLE)
+                        && !flags.contains(PageTableFlags::NO_EXECUTE)
+                    {
+                        return Err(MemoryError::WritableAndExecutable(region));
+                    }
+                }
+                _ => return Err(MemoryError::NotMapped(region)),
+            }
+
+            addr += Size4KiB::SIZE;
         }
+    }
 
-        result
-            .expect("Stack region should not overlap with any other already allocated regions.")
-            .ignore();
+    Ok(())
+}
+
+/// Turns on NX and write protection for the current CPU, which every CPU needs before it can use
+/// the kernel's page tables.
+pub fn enable_protection_bits() {
+    // SAFETY: The kernel never writes to read-only pages or executes non-executable ones, so
+    // turning on enforcement doesn't change the behaviour of correct code.
+    unsafe {
+        Efer::update(|flags| flags.insert(EferFlags::NO_EXECUTE_ENABLE));
+        Cr0::update(|flags| flags.insert(Cr0Flags::WRITE_PROTECT));
     }
 }
 
-fn map_heap(offset_page_table: &mut OffsetPageTable, frame_allocator: &mut EarlyFrameAllocator) {
+/// Backs `pages` pages starting at `base` with freshly allocated frames.
+fn map_fresh_pages(
+    offset_page_table: &mut OffsetPageTable,
+    frame_allocator: &mut EarlyFrameAllocator,
+    base: Page<Size4KiB>,
+    pages: u64,
+    region: MemoryRegion,
+) -> Result<(), MemoryError> {
     // i represents number of pages
-    for i in 0u64..HEAP_PAGES {
+    for i in 0u64..pages {
         let frame = frame_allocator
             .allocate_frame()
-            .unwrap_or_else(|| panic!("Out of memory while allocating frames for heap."));
-
-        // SAFETY: Heap is only mapped once.
-        let result = unsafe {
-            offset_page_table.map_to(
-                HEAP_BASE + i,
-                frame,
-                PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::NO_EXECUTE,
-                frame_allocator,
-            )
-        };
+            .ok_or(MemoryError::OutOfFrames(region))?;
 
-        if let Err(MapToError::FrameAllocationFailed) = result {
-            panic!("Out of memory while allocating frame for page table.");
+        // SAFETY: The caller maps each region only once.
+        unsafe {
+            offset_page_table
+                .map_to(
+                    base + i,
+                    frame,
+                    PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::NO_EXECUTE,
+                    frame_allocator,
+                )
+                .map_err(|e| MemoryError::Map(region, MapRangeError::MapToError4KiB(e)))?
+                .ignore();
         }
-
-        result
-            .expect("Heap region should not overlap with any other already allocated regions.")
-            .ignore();
     }
-}
 
-#[allow(dead_code)]
-#[derive(Debug)]
-enum MapRangeError {
-    AddressNotAligned(AddressNotAligned),
-    MapToError4KiB(MapToError<Size4KiB>),
-    MapToError2MiB(MapToError<Size2MiB>),
-    MapToError1GiB(MapToError<Size1GiB>),
+    Ok(())
 }
 
-#[allow(dead_code)]
-impl MapRangeError {
-    pub fn is_out_of_memory(&self) -> bool {
-        matches!(
-            self,
-            Self::MapToError4KiB(MapToError::FrameAllocationFailed)
-                | Self::MapToError2MiB(MapToError::FrameAllocationFailed)
-                | Self::MapToError1GiB(MapToError::FrameAllocationFailed)
-        )
-    }
+fn map_stack(
+    offset_page_table: &mut OffsetPageTable,
+    frame_allocator: &mut EarlyFrameAllocator,
+) -> Result<(), MemoryError> {
+    map_fresh_pages(
+        offset_page_table,
+        frame_allocator,
+        STACK_BASE,
+        STACK_PAGES,
+        MemoryRegion::Stack,
+    )
+}
 
-    pub fn is_already_mapped(&self) -> bool {
-        matches!(
-            self,
-            Self::MapToError4KiB(MapToError::ParentEntryHugePage)
-                | Self::MapToError4KiB(MapToError::PageAlreadyMapped(_))
-                | Self::MapToError2MiB(MapToError::ParentEntryHugePage)
-                | Self::MapToError2MiB(MapToError::PageAlreadyMapped(_))
-                | Self::MapToError1GiB(MapToError::ParentEntryHugePage)
-                | Self::MapToError1GiB(MapToError::PageAlreadyMapped(_))
-        )
-    }
+fn map_heap(
+    offset_page_table: &mut OffsetPageTable,
+    frame_allocator: &mut EarlyFrameAllocator,
+) -> Result<(), MemoryError> {
+    map_fresh_pages(
+        offset_page_table,
+        frame_allocator,
+        HEAP_BASE,
+        HEAP_PAGES,
+        MemoryRegion::Heap,
+    )
 }
 
 /// # SAFETY
@@ -377,7 +616,7 @@ unsafe fn map_range(
     mut virt: VirtAddr,
     size: u64,
     flags: PageTableFlags,
-) -> core::result::Result<(), MapRangeError> {
+) -> Result<(), MapRangeError> {
     let end = virt + size;
 
     while virt < end {
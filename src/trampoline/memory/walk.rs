@@ -0,0 +1,104 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use core::ops::Range;
+
+use x86_64::{
+    PhysAddr, VirtAddr,
+    structures::paging::{PageTable, PageTableFlags},
+};
+
+/// A single leaf entry found while walking the page tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mapping {
+    pub virt: VirtAddr,
+    pub phys: PhysAddr,
+    /// Size of the page in bytes: 4KiB, 2MiB or 1GiB.
+    pub size: u64,
+    /// Flags as the CPU sees them, i.e. combined over every level of the walk. A page is only
+    /// writable or user accessible if every level allows it, and is non-executable if any level
+    /// says so.
+    pub flags: PageTableFlags,
+}
+
+/// Level 4 indices covering the lower half of the address space.
+pub const LOWER_HALF: Range<usize> = 0..256;
+/// Level 4 indices covering the higher half of the address space.
+pub const HIGHER_HALF: Range<usize> = 256..512;
+
+const INHERITED_FLAGS: PageTableFlags =
+    PageTableFlags::WRITABLE.union(PageTableFlags::USER_ACCESSIBLE);
+
+/// Calls `f` for every present leaf mapping below the level 4 entries in `indices`, in order of
+/// increasing virtual address.
+///
+/// # SAFETY
+/// `hhdm_offset` must be the offset of an HHDM that is active and covers every page table
+/// reachable from `level_4_table`.
+pub unsafe fn for_each_mapping(
+    level_4_table: &PageTable,
+    hhdm_offset: u64,
+    indices: Range<usize>,
+    mut f: impl FnMut(Mapping),
+) {
+    for index in indices {
+        let entry = &level_4_table[index];
+        if !entry.flags().contains(PageTableFlags::PRESENT) {
+            continue;
+        }
+
+        // SAFETY: Passed on from the caller.
+        unsafe {
+            walk_table(
+                entry.addr(),
+                hhdm_offset,
+                3,
+                (index as u64) << 39,
+                entry.flags(),
+                &mut f,
+            );
+        }
+    }
+}
+
+/// # SAFETY
+/// See [`for_each_mapping`].
+unsafe fn walk_table(
+    table_addr: PhysAddr,
+    hhdm_offset: u64,
+    level: u8,
+    base: u64,
+    parent_flags: PageTableFlags,
+    f: &mut impl FnMut(Mapping),
+) {
+    // SAFETY: The caller guarantees the table is reachable through the HHDM.
+    let table = unsafe { &*((table_addr.as_u64() + hhdm_offset) as *const PageTable) };
+    let entry_size = 1u64 << (12 + 9 * (level as u64 - 1));
+
+    for (index, entry) in table.iter().enumerate() {
+        let flags = entry.flags();
+        if !flags.contains(PageTableFlags::PRESENT) {
+            continue;
+        }
+
+        let effective = (flags & !INHERITED_FLAGS)
+            | (flags & parent_flags & INHERITED_FLAGS)
+            | (parent_flags & PageTableFlags::NO_EXECUTE);
+        let virt = base + index as u64 * entry_size;
+
+        if level == 1 || flags.contains(PageTableFlags::HUGE_PAGE) {
+            f(Mapping {
+                virt: VirtAddr::new_truncate(virt),
+                phys: entry.addr(),
+                size: entry_size,
+                flags: effective,
+            });
+        } else {
+            // SAFETY: Passed on from the caller.
+            unsafe {
+                walk_table(entry.addr(), hhdm_offset, level - 1, virt, effective, f);
+            }
+        }
+    }
+}
@@ -5,19 +5,102 @@
 use alloc::vec::Vec;
 use bevy::ecs::resource::Resource;
 use framebuffer::Framebuffer;
+use x86_64::{VirtAddr, structures::paging::OffsetPageTable};
 
-use crate::trampoline::memory::allocators::ProperFrameAllocator;
+use crate::{
+    color::COLOR_SCHEME,
+    debug::{fatal, logger, serial},
+    trampoline::{
+        cmdline::BootOptions,
+        memory::{HHDM_OFFSET, allocators::ProperFrameAllocator, get_pagetable, map_framebuffers},
+    },
+};
 
+pub mod acpi;
+pub mod apic;
+pub mod clock;
+pub mod cmdline;
+pub mod elf;
 pub mod framebuffer;
 pub mod gdt;
 pub mod happy_new_year;
+pub mod hardening;
 pub mod interrupts;
 pub mod limine_requests;
 pub mod memory;
+pub mod pic;
+pub mod pit;
+pub mod power;
+pub mod ps2;
+pub mod rtc;
+pub mod smp;
 
 #[derive(Resource)]
 #[allow(dead_code)]
 pub struct BootInfo {
-    pub framebuffers: Vec<Framebuffer>,
+    pub framebuffers: Vec<Framebuffer<'static>>,
     pub frame_allocator: ProperFrameAllocator,
 }
+
+/// Brings the machine from the state Limine left it in to one where the kernel can run, with
+/// interrupts enabled. This still runs on the bootloader's stack, so bootloader memory is only
+/// reclaimed by [`kernel_main`](crate::kernel_main) once it has moved off it.
+///
+/// # Setup order
+/// 1. Exception handling
+/// 2. Basic stack and heap
+/// 3. Copy what the kernel keeps using out of bootloader memory
+/// 4. Initialize bevy, etc
+pub fn init() -> (BootInfo, BootOptions) {
+    clock::start();
+    debug_assert!(limine_requests::BASE_REVISION.is_supported());
+
+    gdt::init();
+    interrupts::init_idt();
+    pic::init();
+
+    let mut frame_allocator =
+        memory::initialize_paging().unwrap_or_else(|e| fatal::boot_failure(&e));
+    memory::save_memory_map();
+
+    let (options, cmdline_errors) = BootOptions::from_limine();
+    logger::init(&options.log_filter);
+    for error in &cmdline_errors {
+        log::warn!("Ignoring boot option: {error}");
+    }
+    COLOR_SCHEME.set(options.theme);
+
+    let page_table = get_pagetable();
+    // SAFETY: get_pagetable returns address from CR3 which must be valid. HHDM_OFFSET is correct
+    // as we mapped our own HHDM before with the call to initialize_paging.
+    let mut offset_page_table =
+        unsafe { OffsetPageTable::new(page_table, VirtAddr::new(HHDM_OFFSET)) };
+
+    acpi::init(&mut offset_page_table, &mut frame_allocator);
+    frame_allocator.reclaim_acpi_memory();
+    clock::init(&mut offset_page_table, &mut frame_allocator);
+
+    let framebuffers = map_framebuffers(&mut offset_page_table, &mut frame_allocator)
+        .unwrap_or_else(|e| fatal::boot_failure(&e));
+
+    hardening::harden(&mut offset_page_table);
+
+    apic::init(&mut offset_page_table, &mut frame_allocator);
+    if !smp::init(&mut offset_page_table, &mut frame_allocator) {
+        log::warn!("Not reclaiming bootloader memory, some CPUs are still parked in it.");
+        frame_allocator.keep_bootloader_memory();
+    }
+
+    serial::enable_input();
+    ps2::init();
+    pit::init();
+    x86_64::instructions::interrupts::enable();
+
+    (
+        BootInfo {
+            framebuffers,
+            frame_allocator,
+        },
+        options,
+    )
+}
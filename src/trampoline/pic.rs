@@ -0,0 +1,138 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The legacy 8259 programmable interrupt controllers.
+//!
+//! Both PICs are remapped past the CPU exceptions and start with every line masked. Drivers unmask
+//! the lines they handle with [`unmask`].
+
+use spin::Mutex;
+use x86_64::instructions::{interrupts, port::Port};
+
+pub const PIC_1_OFFSET: u8 = 32;
+pub const PIC_2_OFFSET: u8 = PIC_1_OFFSET + 8;
+
+/// The IRQ line the secondary PIC is chained to.
+const CASCADE_IRQ: u8 = 2;
+
+const ICW1_INIT: u8 = 0x11;
+const ICW4_8086: u8 = 0x01;
+const END_OF_INTERRUPT: u8 = 0x20;
+
+/// Legacy IRQ lines in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Irq {
+    Timer = 0,
+    Keyboard = 1,
+    Com1 = 4,
+    Mouse = 12,
+}
+
+impl Irq {
+    /// The interrupt vector this IRQ is delivered on.
+    pub const fn vector(self) -> u8 {
+        PIC_1_OFFSET + self as u8
+    }
+}
+
+struct Pic {
+    command: Port<u8>,
+    data: Port<u8>,
+}
+
+impl Pic {
+    const fn new(base: u16) -> Self {
+        Self {
+            command: Port::new(base),
+            data: Port::new(base + 1),
+        }
+    }
+}
+
+struct ChainedPics {
+    primary: Pic,
+    secondary: Pic,
+}
+
+static PICS: Mutex<ChainedPics> = Mutex::new(ChainedPics {
+    primary: Pic::new(0x20),
+    secondary: Pic::new(0xA0),
+});
+
+/// Writes to an unused port, which takes long enough for the PICs to settle between commands.
+fn io_wait() {
+    // SAFETY: Port 0x80 is the POST code port, writing to it has no side effects.
+    unsafe {
+        Port::<u8>::new(0x80).write(0);
+    }
+}
+
+/// Remaps both PICs and masks every IRQ line. Must be called before interrupts are enabled.
+pub fn init() {
+    let mut pics = PICS.lock();
+    let ChainedPics { primary, secondary } = &mut *pics;
+
+    // SAFETY: These are the standard PIC ports and the initialization sequence they expect.
+    // Nothing is unmasked yet, so no interrupts arrive on the new vectors before we are ready.
+    unsafe {
+        primary.command.write(ICW1_INIT);
+        io_wait();
+        secondary.command.write(ICW1_INIT);
+        io_wait();
+
+        primary.data.write(PIC_1_OFFSET);
+        io_wait();
+        secondary.data.write(PIC_2_OFFSET);
+        io_wait();
+
+        primary.data.write(1 << CASCADE_IRQ);
+        io_wait();
+        secondary.data.write(CASCADE_IRQ);
+        io_wait();
+
+        primary.data.write(ICW4_8086);
+        io_wait();
+        secondary.data.write(ICW4_8086);
+        io_wait();
+
+        primary.data.write(!(1 << CASCADE_IRQ));
+        secondary.data.write(0xFF);
+    }
+}
+
+/// Lets `irq` through to the CPU.
+pub fn unmask(irq: Irq) {
+    // Another handler sending EOI while we hold the lock would deadlock.
+    interrupts::without_interrupts(|| {
+        let mut pics = PICS.lock();
+        let line = irq as u8;
+        let pic = if line < 8 {
+            &mut pics.primary
+        } else {
+            &mut pics.secondary
+        };
+
+        // SAFETY: Only clears the mask bit of a line that has a handler installed.
+        unsafe {
+            let mask = pic.data.read();
+            pic.data.write(mask & !(1 << (line % 8)));
+        }
+    });
+}
+
+/// Acknowledges `irq`. Must be called at the end of its handler, or the line stays blocked.
+pub fn end_of_interrupt(irq: Irq) {
+    // Handlers run with interrupts disabled, and everyone else takes the lock the same way, so this
+    // can't deadlock.
+    let mut pics = PICS.lock();
+
+    // SAFETY: Sending EOI to the PICs that delivered the interrupt.
+    unsafe {
+        if irq as u8 >= 8 {
+            pics.secondary.command.write(END_OF_INTERRUPT);
+        }
+        pics.primary.command.write(END_OF_INTERRUPT);
+    }
+}
@@ -0,0 +1,42 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Channel 0 of the programmable interval timer, which interrupts [`TICK_HZ`] times a second so
+//! that sleeping tasks wake up on time.
+
+use x86_64::instructions::port::Port;
+
+use crate::{
+    task::timer,
+    trampoline::pic::{self, Irq},
+};
+
+pub const TICK_HZ: u32 = 100;
+
+const PIT_FREQUENCY: u32 = 1_193_182;
+const PIT_CHANNEL_0: u16 = 0x40;
+const PIT_COMMAND: u16 = 0x43;
+/// Channel 0, low then high byte, mode 2 (rate generator).
+const PIT_CHANNEL_0_PERIODIC: u8 = 0b0011_0100;
+
+/// Starts the periodic tick. Must be called after [`clock::init`](super::clock::init), which uses
+/// the PIT for calibration.
+pub fn init() {
+    let divisor = (PIT_FREQUENCY / TICK_HZ) as u16;
+
+    // SAFETY: Channel 0 is only used for this tick, and its IRQ is still masked.
+    unsafe {
+        Port::<u8>::new(PIT_COMMAND).write(PIT_CHANNEL_0_PERIODIC);
+        let mut channel = Port::<u8>::new(PIT_CHANNEL_0);
+        channel.write(divisor as u8);
+        channel.write((divisor >> 8) as u8);
+    }
+
+    pic::unmask(Irq::Timer);
+}
+
+/// Called from the timer interrupt handler.
+pub fn handle_interrupt() {
+    timer::tick();
+}
@@ -0,0 +1,164 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Turning the machine off and resetting it.
+
+use x86_64::{
+    instructions::{
+        interrupts,
+        port::{Port, PortWrite},
+    },
+    structures::idt::InterruptDescriptorTable,
+};
+
+use crate::{
+    hcf,
+    trampoline::{
+        acpi::{self, GenericAddress, RegisterSpace, fadt::Fadt},
+        ps2,
+    },
+};
+
+/// Bits of the PM1 control registers.
+const SCI_EN: u16 = 1 << 0;
+const SLP_TYP_SHIFT: u16 = 10;
+const SLP_TYP_MASK: u16 = 0b111 << SLP_TYP_SHIFT;
+const SLP_EN: u16 = 1 << 13;
+
+/// Ports that power off emulators without going through ACPI: QEMU, Bochs and older QEMU, and
+/// VirtualBox.
+const EMULATOR_SHUTDOWN_PORTS: [(u16, u16); 3] =
+    [(0x604, 0x2000), (0xB004, 0x2000), (0x4004, 0x3400)];
+
+/// How often to poll for SCI_EN after asking the firmware to switch to ACPI mode.
+const ACPI_ENABLE_ATTEMPTS: usize = 1_000_000;
+
+/// Reads a 16 bit register. Only registers in I/O space are supported, because memory mapped
+/// ones would need page tables that aren't at hand when the machine is going down.
+fn read_register(register: &GenericAddress) -> Option<u16> {
+    match register.space {
+        // SAFETY: The firmware says this port is the register.
+        RegisterSpace::Io => Some(unsafe { Port::<u16>::new(register.address as u16).read() }),
+        _ => None,
+    }
+}
+
+/// Writes a register, see [`read_register`]. Returns whether the write happened.
+fn write_register<T: PortWrite>(register: &GenericAddress, value: T) -> bool {
+    match register.space {
+        RegisterSpace::Io => {
+            // SAFETY: The firmware says this port is the register.
+            unsafe { Port::<T>::new(register.address as u16).write(value) };
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Hands power management over from the firmware to the OS, which is required before the PM1
+/// control registers do anything.
+fn enable_acpi(fadt: &Fadt, pm1a: &GenericAddress) {
+    if read_register(pm1a).is_some_and(|value| value & SCI_EN != 0)
+        || fadt.smi_command_port == 0
+        || fadt.acpi_enable == 0
+    {
+        return;
+    }
+
+    // SAFETY: Writing acpi_enable to the SMI command port is how the FADT says to do this.
+    unsafe { Port::<u8>::new(fadt.smi_command_port as u16).write(fadt.acpi_enable) };
+
+    for _ in 0..ACPI_ENABLE_ATTEMPTS {
+        if read_register(pm1a).is_some_and(|value| value & SCI_EN != 0) {
+            return;
+        }
+        core::hint::spin_loop();
+    }
+    log::warn!("The firmware didn't switch to ACPI mode.");
+}
+
+/// Enters S5 through the PM1 control registers. Returns if the machine is still running.
+fn acpi_shutdown() {
+    let Some(acpi) = acpi::get() else {
+        return;
+    };
+    let (Some(fadt), Some(s5)) = (&acpi.fadt, acpi.s5) else {
+        log::warn!("ACPI doesn't describe how to turn the machine off.");
+        return;
+    };
+    let Some(pm1a) = &fadt.pm1a_control_block else {
+        return;
+    };
+
+    enable_acpi(fadt, pm1a);
+
+    let sleep = |register: &GenericAddress, sleep_type: u8| {
+        let value = read_register(register).unwrap_or(0) & !SLP_TYP_MASK;
+        let sleep_type = (sleep_type as u16) << SLP_TYP_SHIFT & SLP_TYP_MASK;
+        write_register(register, value | sleep_type | SLP_EN)
+    };
+
+    if !sleep(pm1a, s5.a) {
+        log::warn!("The PM1a control register isn't in I/O space.");
+        return;
+    }
+    if let Some(pm1b) = &fadt.pm1b_control_block {
+        sleep(pm1b, s5.b);
+    }
+}
+
+/// Turns the machine off through ACPI, or through the ports emulators provide for it if that
+/// doesn't work. Halts if nothing does.
+pub fn shutdown() -> ! {
+    log::info!("Shutting down.");
+    interrupts::disable();
+
+    acpi_shutdown();
+
+    for (port, value) in EMULATOR_SHUTDOWN_PORTS {
+        // SAFETY: On emulators these ports turn the machine off, on real hardware nothing should
+        // be there.
+        unsafe { Port::<u16>::new(port).write(value) };
+    }
+
+    log::error!("Could not turn the machine off, it is safe to do so manually.");
+    hcf();
+}
+
+/// Resets the machine through the ACPI reset register, then the keyboard controller and finally
+/// by triple faulting.
+pub fn reboot() -> ! {
+    log::info!("Rebooting.");
+    interrupts::disable();
+
+    if let Some(fadt) = acpi::get().and_then(|acpi| acpi.fadt.as_ref())
+        && let Some(reset) = &fadt.reset_register
+        && !write_register(reset, fadt.reset_value)
+    {
+        log::warn!(
+            "Skipping the ACPI reset register, which is in {:?} space.",
+            reset.space
+        );
+    }
+
+    // The controller may be missing or stuck, which must not keep us from trying the next way.
+    if ps2::pulse_reset_line().is_none() {
+        log::warn!("The keyboard controller didn't take the reset command.");
+    }
+
+    triple_fault();
+}
+
+/// Loads an empty IDT and raises an exception, which the CPU can't deliver and resets instead.
+fn triple_fault() -> ! {
+    static EMPTY_IDT: InterruptDescriptorTable = InterruptDescriptorTable::new();
+
+    // SAFETY: The IDT is empty so the breakpoint below can't be delivered, which is the point.
+    unsafe {
+        EMPTY_IDT.load_unsafe();
+    }
+    x86_64::instructions::interrupts::int3();
+
+    hcf();
+}
@@ -0,0 +1,200 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! PS/2 keyboard on IRQ 1, with a US layout.
+//!
+//! Keys are turned into the bytes a terminal would send for them, so typing on the keyboard works
+//! just like typing over serial.
+
+use core::{
+    future, mem,
+    task::{Context, Poll},
+};
+
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+
+use crate::{
+    task::AtomicWaker,
+    trampoline::pic::{self, Irq},
+    util::Queue,
+};
+
+const INPUT_CAPACITY: usize = 256;
+
+const EXTENDED: u8 = 0xE0;
+const RELEASED: u8 = 0x80;
+const CTRL: u8 = 0x1D;
+const LEFT_SHIFT: u8 = 0x2A;
+const RIGHT_SHIFT: u8 = 0x36;
+const UP: u8 = 0x48;
+const PAGE_UP: u8 = 0x49;
+const LEFT: u8 = 0x4B;
+const RIGHT: u8 = 0x4D;
+const DOWN: u8 = 0x50;
+const PAGE_DOWN: u8 = 0x51;
+const KEYPAD_ENTER: u8 = 0x1C;
+
+/// Scancode set 1 make codes up to the space bar, without and with shift. Keys that don't type
+/// anything are zero.
+const UNSHIFTED: &[u8; 0x3A] =
+    b"\0\x001234567890-=\x08\tqwertyuiop[]\r\0asdfghjkl;'`\0\\zxcvbnm,./\0*\0 ";
+const SHIFTED: &[u8; 0x3A] =
+    b"\0\0!@#$%^&*()_+\x08\tQWERTYUIOP{}\r\0ASDFGHJKL:\"~\0|ZXCVBNM<>?\0*\0 ";
+
+/// Tracks modifiers and prefixes across scancodes.
+#[derive(Default)]
+struct Decoder {
+    extended: bool,
+    shift: bool,
+    ctrl: bool,
+}
+
+impl Decoder {
+    const fn new() -> Self {
+        Self {
+            extended: false,
+            shift: false,
+            ctrl: false,
+        }
+    }
+
+    /// Passes the bytes `scancode` types to `emit`.
+    fn feed(&mut self, scancode: u8, mut emit: impl FnMut(u8)) {
+        if scancode == EXTENDED {
+            self.extended = true;
+            return;
+        }
+
+        let extended = mem::take(&mut self.extended);
+        let pressed = scancode & RELEASED == 0;
+        let code = scancode & !RELEASED;
+
+        let arrow = |letter| [0x1B, b'[', letter, 0];
+        let page = |number| [0x1B, b'[', number, b'~'];
+        let bytes = match (extended, code) {
+            (false, LEFT_SHIFT | RIGHT_SHIFT) => {
+                self.shift = pressed;
+                return;
+            }
+            (_, CTRL) => {
+                self.ctrl = pressed;
+                return;
+            }
+            _ if !pressed => return,
+            (true, UP) => arrow(b'A'),
+            (true, DOWN) => arrow(b'B'),
+            (true, RIGHT) => arrow(b'C'),
+            (true, LEFT) => arrow(b'D'),
+            (true, PAGE_UP) => page(b'5'),
+            (true, PAGE_DOWN) => page(b'6'),
+            (true, KEYPAD_ENTER) => [b'\r', 0, 0, 0],
+            (true, _) => return,
+            (false, code) => {
+                let table = if self.shift { SHIFTED } else { UNSHIFTED };
+                let byte = table.get(code as usize).copied().unwrap_or(0);
+                if self.ctrl && byte.is_ascii_alphabetic() {
+                    [byte.to_ascii_uppercase() - b'@', 0, 0, 0]
+                } else {
+                    [byte, 0, 0, 0]
+                }
+            }
+        };
+
+        bytes
+            .into_iter()
+            .filter(|&byte| byte != 0)
+            .for_each(&mut emit);
+    }
+}
+
+static DECODER: Mutex<Decoder> = Mutex::new(Decoder::new());
+static INPUT: Mutex<Queue<u8, INPUT_CAPACITY>> = Mutex::new(Queue::new());
+static INPUT_WAKER: AtomicWaker = AtomicWaker::new();
+
+pub(super) fn init() {
+    pic::unmask(Irq::Keyboard);
+}
+
+/// Called from the keyboard interrupt handler with the scancode the controller received.
+pub fn handle_interrupt() {
+    let scancode = super::read_data();
+    let mut input = INPUT.lock();
+    DECODER.lock().feed(scancode, |byte| input.push(byte));
+    drop(input);
+
+    INPUT_WAKER.wake();
+}
+
+/// Types `byte` as if it came from the keyboard, for the on-screen keyboard.
+pub fn type_byte(byte: u8) {
+    interrupts::without_interrupts(|| INPUT.lock().push(byte));
+    INPUT_WAKER.wake();
+}
+
+/// Whether there are typed bytes waiting to be read.
+pub fn has_input() -> bool {
+    interrupts::without_interrupts(|| !INPUT.lock().is_empty())
+}
+
+/// Takes the next typed byte, if any.
+pub fn read_byte() -> Option<u8> {
+    // The interrupt handler takes the same lock.
+    interrupts::without_interrupts(|| INPUT.lock().pop())
+}
+
+/// Ready once there are typed bytes, waking the task when some arrive otherwise.
+pub fn poll_input(cx: &mut Context) -> Poll<()> {
+    INPUT_WAKER.poll_with(cx, || has_input().then_some(()))
+}
+
+/// Waits for the next typed byte.
+pub async fn next_byte() -> u8 {
+    future::poll_fn(|cx| INPUT_WAKER.poll_with(cx, read_byte)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    fn decode(scancodes: &[u8]) -> Vec<u8> {
+        let mut decoder = Decoder::default();
+        let mut bytes = Vec::new();
+        for &scancode in scancodes {
+            decoder.feed(scancode, |byte| bytes.push(byte));
+        }
+        bytes
+    }
+
+    #[test_case]
+    fn types_letters_and_enter() {
+        // c, r, a, n, e pressed and released, then Enter.
+        let scancodes = [
+            0x2E, 0xAE, 0x13, 0x93, 0x1E, 0x9E, 0x31, 0xB1, 0x12, 0x92, 0x1C,
+        ];
+        assert_eq!(decode(&scancodes), b"crane\r");
+    }
+
+    #[test_case]
+    fn applies_modifiers() {
+        // Shift+q, q, Ctrl+c.
+        let scancodes = [0x2A, 0x10, 0xAA, 0x10, 0x1D, 0x2E, 0x9D];
+        assert_eq!(decode(&scancodes), b"Qq\x03");
+    }
+
+    #[test_case]
+    fn sends_escape_sequences_for_arrows() {
+        // Up, then the keypad's 8 without the extended prefix, which types nothing.
+        let scancodes = [0xE0, 0x48, 0xE0, 0xC8, 0x48];
+        assert_eq!(decode(&scancodes), b"\x1b[A");
+    }
+
+    #[test_case]
+    fn sends_escape_sequences_for_page_keys() {
+        let scancodes = [0xE0, 0x49, 0xE0, 0xC9, 0xE0, 0x51, 0xE0, 0xD1];
+        assert_eq!(decode(&scancodes), b"\x1b[5~\x1b[6~");
+    }
+}
@@ -0,0 +1,112 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The 8042 PS/2 controller and the devices attached to it.
+//!
+//! Firmware leaves the controller set up with the keyboard translated to scancode set 1, which is
+//! kept as is. The mouse port is turned on here, and both devices get their interrupts.
+
+pub mod keyboard;
+pub mod mouse;
+
+use x86_64::instructions::port::Port;
+
+const DATA: u16 = 0x60;
+const STATUS_COMMAND: u16 = 0x64;
+
+const OUTPUT_FULL: u8 = 1 << 0;
+const INPUT_FULL: u8 = 1 << 1;
+
+const READ_CONFIG: u8 = 0x20;
+const WRITE_CONFIG: u8 = 0x60;
+const ENABLE_SECOND_PORT: u8 = 0xA8;
+const WRITE_SECOND_PORT: u8 = 0xD4;
+const PULSE_RESET_LINE: u8 = 0xFE;
+
+const FIRST_PORT_INTERRUPT: u8 = 1 << 0;
+const SECOND_PORT_INTERRUPT: u8 = 1 << 1;
+
+/// Gives up on a byte after this many status reads, in case there is no controller.
+const TIMEOUT: u32 = 100_000;
+
+fn status() -> u8 {
+    // SAFETY: Reading the status register has no side effects.
+    unsafe { Port::<u8>::new(STATUS_COMMAND).read() }
+}
+
+/// Reads the byte the controller has for us, whether or not it has one.
+fn read_data() -> u8 {
+    // SAFETY: Reading the data port only takes the byte the controller has ready.
+    unsafe { Port::<u8>::new(DATA).read() }
+}
+
+fn wait_for(ready: impl Fn(u8) -> bool) -> Option<()> {
+    (0..TIMEOUT).find(|_| ready(status())).map(|_| ())
+}
+
+fn write_port(port: u16, byte: u8) -> Option<()> {
+    wait_for(|status| status & INPUT_FULL == 0)?;
+    // SAFETY: The controller is ready for a command or its argument.
+    unsafe { Port::<u8>::new(port).write(byte) };
+    Some(())
+}
+
+fn read_response() -> Option<u8> {
+    wait_for(|status| status & OUTPUT_FULL != 0)?;
+    Some(read_data())
+}
+
+/// Throws away bytes that arrived before we were listening.
+fn flush() {
+    for _ in 0..16 {
+        if status() & OUTPUT_FULL == 0 {
+            break;
+        }
+        read_data();
+    }
+}
+
+/// Changes the controller's configuration byte with `f`.
+fn update_config(f: impl FnOnce(u8) -> u8) -> Option<()> {
+    write_port(STATUS_COMMAND, READ_CONFIG)?;
+    let config = read_response()?;
+    write_port(STATUS_COMMAND, WRITE_CONFIG)?;
+    write_port(DATA, f(config))
+}
+
+/// Asks the controller to pulse the CPU's reset line. Returns `None` if it never got ready for the
+/// command, e.g. because there is no controller.
+pub fn pulse_reset_line() -> Option<()> {
+    write_port(STATUS_COMMAND, PULSE_RESET_LINE)
+}
+
+/// Sets up the mouse and turns on both devices' interrupts. Must be called with interrupts
+/// disabled.
+pub fn init() {
+    flush();
+
+    // The mouse's answers are read by polling, so they mustn't raise interrupts.
+    if update_config(|config| config & !(FIRST_PORT_INTERRUPT | SECOND_PORT_INTERRUPT)).is_none() {
+        log::warn!("The PS/2 controller isn't responding, there is no keyboard or mouse input.");
+        return;
+    }
+
+    let mouse = write_port(STATUS_COMMAND, ENABLE_SECOND_PORT).is_some() && mouse::init();
+    flush();
+
+    let interrupts = if mouse {
+        FIRST_PORT_INTERRUPT | SECOND_PORT_INTERRUPT
+    } else {
+        FIRST_PORT_INTERRUPT
+    };
+    if update_config(|config| config | interrupts).is_none() {
+        log::warn!("Can't turn on PS/2 interrupts.");
+        return;
+    }
+
+    keyboard::init();
+    if mouse {
+        mouse::enable();
+    }
+}
@@ -0,0 +1,263 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! PS/2 mouse on IRQ 12. Mice that answer the IntelliMouse knock also report their scroll wheel,
+//! in a fourth byte of every packet.
+
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll},
+};
+
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+
+use crate::{
+    task::AtomicWaker,
+    trampoline::pic::{self, Irq},
+    util::Queue,
+};
+
+use super::{DATA, OUTPUT_FULL, STATUS_COMMAND, WRITE_SECOND_PORT};
+
+const PACKET_CAPACITY: usize = 64;
+
+const SET_SAMPLE_RATE: u8 = 0xF3;
+const GET_ID: u8 = 0xF2;
+const ENABLE_REPORTING: u8 = 0xF4;
+const SET_DEFAULTS: u8 = 0xF6;
+const ACK: u8 = 0xFA;
+
+const INTELLIMOUSE_ID: u8 = 3;
+/// Setting these sample rates in a row switches a mouse with a wheel into IntelliMouse mode.
+const INTELLIMOUSE_KNOCK: [u8; 3] = [200, 100, 80];
+
+const LEFT_BUTTON: u8 = 1 << 0;
+const RIGHT_BUTTON: u8 = 1 << 1;
+const MIDDLE_BUTTON: u8 = 1 << 2;
+/// Set in the first byte of every packet, which is how we find the start of one.
+const ALWAYS_ONE: u8 = 1 << 3;
+const X_SIGN: u8 = 1 << 4;
+const Y_SIGN: u8 = 1 << 5;
+const X_OVERFLOW: u8 = 1 << 6;
+const Y_OVERFLOW: u8 = 1 << 7;
+
+/// One report from the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Packet {
+    /// Movement to the right.
+    pub dx: i16,
+    /// Movement upwards, the opposite of screen coordinates.
+    pub dy: i16,
+    /// Clicks of the wheel towards the user. Always zero without IntelliMouse mode.
+    pub wheel: i8,
+    pub left: bool,
+    pub right: bool,
+    pub middle: bool,
+}
+
+impl Packet {
+    fn decode(bytes: &[u8]) -> Self {
+        let flags = bytes[0];
+        let delta = |value: u8, sign, overflow| match (flags & overflow != 0, flags & sign != 0) {
+            // The movement didn't fit, so there is no telling how far it went.
+            (true, _) => 0,
+            (false, true) => value as i16 - 256,
+            (false, false) => value as i16,
+        };
+
+        Self {
+            dx: delta(bytes[1], X_SIGN, X_OVERFLOW),
+            dy: delta(bytes[2], Y_SIGN, Y_OVERFLOW),
+            wheel: bytes.get(3).map_or(0, |&wheel| wheel as i8),
+            left: flags & LEFT_BUTTON != 0,
+            right: flags & RIGHT_BUTTON != 0,
+            middle: flags & MIDDLE_BUTTON != 0,
+        }
+    }
+}
+
+/// Collects bytes until a whole packet has arrived.
+struct Assembler {
+    bytes: [u8; 4],
+    len: usize,
+    size: usize,
+}
+
+impl Assembler {
+    const fn new(size: usize) -> Self {
+        Self {
+            bytes: [0; 4],
+            len: 0,
+            size,
+        }
+    }
+
+    fn feed(&mut self, byte: u8) -> Option<Packet> {
+        // A byte got lost somewhere, so wait for something that can start a packet.
+        if self.len == 0 && byte & ALWAYS_ONE == 0 {
+            return None;
+        }
+
+        self.bytes[self.len] = byte;
+        self.len += 1;
+        if self.len < self.size {
+            return None;
+        }
+
+        self.len = 0;
+        Some(Packet::decode(&self.bytes[..self.size]))
+    }
+}
+
+static PRESENT: AtomicBool = AtomicBool::new(false);
+static HAS_WHEEL: AtomicBool = AtomicBool::new(false);
+static ASSEMBLER: Mutex<Assembler> = Mutex::new(Assembler::new(3));
+static PACKETS: Mutex<Queue<Packet, PACKET_CAPACITY>> = Mutex::new(Queue::new());
+static PACKET_WAKER: AtomicWaker = AtomicWaker::new();
+
+/// Sends `command` to the mouse and waits for it to acknowledge.
+fn command(command: u8) -> Option<()> {
+    super::write_port(STATUS_COMMAND, WRITE_SECOND_PORT)?;
+    super::write_port(DATA, command)?;
+    (super::read_response()? == ACK).then_some(())
+}
+
+fn set_sample_rate(rate: u8) -> Option<()> {
+    command(SET_SAMPLE_RATE)?;
+    command(rate)
+}
+
+fn identify() -> Option<u8> {
+    command(GET_ID)?;
+    super::read_response()
+}
+
+/// Resets the mouse to its defaults, tries to turn on the wheel and starts reporting. Returns
+/// whether there is a mouse that answered.
+pub(super) fn init() -> bool {
+    let Some(id) = command(SET_DEFAULTS).and_then(|()| {
+        for rate in INTELLIMOUSE_KNOCK {
+            set_sample_rate(rate)?;
+        }
+        identify()
+    }) else {
+        log::info!("No PS/2 mouse found.");
+        return false;
+    };
+
+    let wheel = id == INTELLIMOUSE_ID;
+    // The knock left the sample rate at 80 reports a second, which is a fine rate anyway.
+    if command(ENABLE_REPORTING).is_none() {
+        log::warn!("The PS/2 mouse won't start reporting.");
+        return false;
+    }
+
+    HAS_WHEEL.store(wheel, Ordering::Relaxed);
+    *ASSEMBLER.lock() = Assembler::new(if wheel { 4 } else { 3 });
+    PRESENT.store(true, Ordering::Relaxed);
+    log::info!(
+        "Found a PS/2 mouse {} a scroll wheel.",
+        if wheel { "with" } else { "without" }
+    );
+
+    true
+}
+
+pub(super) fn enable() {
+    pic::unmask(Irq::Mouse);
+}
+
+/// Whether a mouse was found while booting.
+pub fn is_present() -> bool {
+    PRESENT.load(Ordering::Relaxed)
+}
+
+pub fn has_wheel() -> bool {
+    HAS_WHEEL.load(Ordering::Relaxed)
+}
+
+/// Called from the mouse interrupt handler with each byte the mouse sends.
+pub fn handle_interrupt() {
+    // The interrupt can outlive the byte that raised it, if something else read it already.
+    if super::status() & OUTPUT_FULL == 0 {
+        return;
+    }
+
+    let byte = super::read_data();
+    if let Some(packet) = ASSEMBLER.lock().feed(byte) {
+        PACKETS.lock().push(packet);
+        PACKET_WAKER.wake();
+    }
+}
+
+/// Takes the oldest packet nobody has read yet, if any.
+pub fn read_packet() -> Option<Packet> {
+    // The interrupt handler takes the same lock.
+    interrupts::without_interrupts(|| PACKETS.lock().pop())
+}
+
+/// Ready once there are packets to read, waking the task when one arrives otherwise.
+pub fn poll_input(cx: &mut Context) -> Poll<()> {
+    let has_packets = || interrupts::without_interrupts(|| !PACKETS.lock().is_empty());
+    PACKET_WAKER.poll_with(cx, || has_packets().then_some(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    fn assemble(size: usize, bytes: &[u8]) -> Vec<Packet> {
+        let mut assembler = Assembler::new(size);
+        bytes
+            .iter()
+            .filter_map(|&byte| assembler.feed(byte))
+            .collect()
+    }
+
+    #[test_case]
+    fn decodes_movement_and_buttons() {
+        // Left button held, 5 to the right and 3 down.
+        let packets = assemble(3, &[0x29, 0x05, 0xFD]);
+        assert_eq!(
+            packets,
+            [Packet {
+                dx: 5,
+                dy: -3,
+                left: true,
+                ..Packet::default()
+            }]
+        );
+    }
+
+    #[test_case]
+    fn reads_the_wheel_and_drops_overflowing_movement() {
+        // X overflowed, one click of the wheel away from the user.
+        let packets = assemble(4, &[0x48, 0xFF, 0x01, 0xFF]);
+        assert_eq!(
+            packets,
+            [Packet {
+                dx: 0,
+                dy: 1,
+                wheel: -1,
+                ..Packet::default()
+            }]
+        );
+    }
+
+    #[test_case]
+    fn resynchronizes_after_a_lost_byte() {
+        // The first packet lost its first byte, so its other two can't start a packet.
+        let packets = assemble(3, &[0x01, 0x02, 0x08, 0x01, 0x00]);
+        assert_eq!(
+            packets,
+            [Packet {
+                dx: 1,
+                ..Packet::default()
+            }]
+        );
+    }
+}
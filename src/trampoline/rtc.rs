@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The CMOS real time clock, only used to find out today's date.
+
+use x86_64::instructions::{interrupts, port::Port};
+
+use crate::trampoline::acpi;
+
+const CMOS_ADDRESS: u16 = 0x70;
+const CMOS_DATA: u16 = 0x71;
+
+const REGISTER_DAY: u8 = 0x07;
+const REGISTER_MONTH: u8 = 0x08;
+const REGISTER_YEAR: u8 = 0x09;
+const REGISTER_STATUS_A: u8 = 0x0A;
+const REGISTER_STATUS_B: u8 = 0x0B;
+
+const UPDATE_IN_PROGRESS: u8 = 1 << 7;
+const BINARY_MODE: u8 = 1 << 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// Days since 1970-01-01, using Howard Hinnant's `days_from_civil`.
+    pub fn days_since_epoch(&self) -> u64 {
+        let year = self.year as i64 - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        (era * 146_097 + day_of_era - 719_468) as u64
+    }
+}
+
+fn read_register(register: u8) -> u8 {
+    let mut address = Port::<u8>::new(CMOS_ADDRESS);
+    let mut data = Port::<u8>::new(CMOS_DATA);
+
+    // SAFETY: Selecting and reading a CMOS register has no side effects. Callers disable
+    // interrupts so nothing else selects a different register in between.
+    unsafe {
+        address.write(register);
+        data.read()
+    }
+}
+
+fn read_date_once() -> Date {
+    while read_register(REGISTER_STATUS_A) & UPDATE_IN_PROGRESS != 0 {
+        core::hint::spin_loop();
+    }
+
+    let binary = read_register(REGISTER_STATUS_B) & BINARY_MODE != 0;
+    let decode = |value: u8| {
+        if binary {
+            value
+        } else {
+            (value >> 4) * 10 + (value & 0x0F)
+        }
+    };
+
+    // Only ACPI says whether there is a century register, otherwise assume this century.
+    let century = acpi::get()
+        .and_then(|acpi| acpi.fadt.as_ref()?.century_register)
+        .map_or(20, |register| decode(read_register(register)));
+
+    Date {
+        year: century as u16 * 100 + decode(read_register(REGISTER_YEAR)) as u16,
+        month: decode(read_register(REGISTER_MONTH)),
+        day: decode(read_register(REGISTER_DAY)),
+    }
+}
+
+/// Reads the current date, retrying until two reads agree so that we never see a half updated
+/// clock.
+pub fn today() -> Date {
+    interrupts::without_interrupts(|| {
+        let mut date = read_date_once();
+        loop {
+            let again = read_date_once();
+            if again == date {
+                return date;
+            }
+            date = again;
+        }
+    })
+}
@@ -0,0 +1,441 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Starting the application processors (APs) and handing them work.
+//!
+//! Limine parks every AP until we give it an entry point. Each one then switches to the kernel's
+//! page tables and a stack of its own, loads its own GDT and TSS and the shared IDT, and waits for
+//! tasks. Code finds out which CPU it runs on through the GS base, which points at that CPU's
+//! [`PerCpu`].
+//!
+//! Bevy's multithreaded executor needs `std`, so the game's systems all run on the boot processor
+//! (BSP). Other work can be sent to the APs with [`spawn`] and [`run_on`], or split up between
+//! all CPUs with [`for_each`], which is how the screen gets flushed.
+
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use core::{
+    arch::asm,
+    mem,
+    ops::Range,
+    sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use limine::mp::Cpu;
+use spin::{Mutex, Once};
+use x86_64::{
+    VirtAddr,
+    instructions::interrupts,
+    registers::{control::Cr3, model_specific::GsBase},
+    structures::paging::{OffsetPageTable, PageSize, Size4KiB},
+};
+
+use crate::{
+    hcf,
+    trampoline::{
+        apic,
+        clock::Instant,
+        gdt::{self, IstStacks},
+        hardening,
+        interrupts::init_idt,
+        limine_requests::MP_REQUEST,
+        memory::{
+            self, STACK_BASE, STACK_PAGES, allocate_stack, allocators::ProperFrameAllocator,
+            error::MemoryRegion,
+        },
+    },
+};
+
+/// More processors than this are left parked.
+pub const MAX_CPUS: usize = 64;
+const STACK_SIZE: u64 = 64 * 1024;
+/// How long to wait for the APs to come online before booting without the missing ones.
+const START_TIMEOUT: Duration = Duration::from_secs(1);
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// Everything that belongs to one CPU.
+#[repr(C)]
+pub struct PerCpu {
+    /// Points at this struct, so that `gs:[0]` gives its address. Must stay the first field.
+    this: *const PerCpu,
+    /// 0 for the BSP, counting up in the order Limine lists the APs.
+    pub index: usize,
+    pub lapic_id: u32,
+    /// The CPU's kernel stack, without its guard page.
+    pub stack: Range<u64>,
+    /// The stacks the CPU switches to for exceptions that can't trust the current one.
+    pub ist_stacks: IstStacks,
+    online: AtomicBool,
+    tasks: Mutex<VecDeque<Task>>,
+}
+
+// SAFETY: `this` is only written before the struct is shared and only ever read afterwards.
+unsafe impl Sync for PerCpu {}
+
+impl PerCpu {
+    fn leak(
+        index: usize,
+        lapic_id: u32,
+        stack: Range<u64>,
+        ist_stacks: IstStacks,
+    ) -> &'static PerCpu {
+        let cpu = Box::leak(Box::new(PerCpu {
+            this: core::ptr::null(),
+            index,
+            lapic_id,
+            stack,
+            ist_stacks,
+            online: AtomicBool::new(index == 0),
+            tasks: Mutex::new(VecDeque::new()),
+        }));
+        cpu.this = cpu;
+        cpu
+    }
+
+    pub fn is_bsp(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Whether the CPU has started and is taking tasks, or is the BSP.
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::Acquire)
+    }
+
+    /// Points the GS base of the CPU this is called on at `self`.
+    fn install(&'static self) {
+        GsBase::write(VirtAddr::from_ptr(self));
+    }
+}
+
+static CPUS: Once<Vec<&'static PerCpu>> = Once::new();
+
+/// Every CPU the kernel knows about, the BSP first. Empty until [`init`].
+pub fn cpus() -> &'static [&'static PerCpu] {
+    CPUS.get().map_or(&[], Vec::as_slice)
+}
+
+/// The CPU this runs on, or `None` before [`init`].
+pub fn current() -> Option<&'static PerCpu> {
+    // APs install their GS base before running anything that could get here.
+    CPUS.get()?;
+
+    let this: *const PerCpu;
+    // SAFETY: Once CPUS is set, every CPU's GS base points at its PerCpu, whose first field is
+    // `this`.
+    unsafe {
+        asm!("mov {}, gs:[0]", out(reg) this, options(nostack, readonly, preserves_flags));
+        Some(&*this)
+    }
+}
+
+/// The index of the CPU this runs on, which is the BSP's 0 before [`init`].
+pub fn current_index() -> usize {
+    current().map_or(0, |cpu| cpu.index)
+}
+
+/// What an AP needs to know while it still runs on Limine's page tables, which only map the kernel
+/// image and not the heap. Matched to the AP by its APIC ID.
+struct Startup {
+    lapic_id: AtomicU32,
+    stack_top: AtomicU64,
+    per_cpu: AtomicU64,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_STARTUP: Startup = Startup {
+    lapic_id: AtomicU32::new(0),
+    stack_top: AtomicU64::new(0),
+    per_cpu: AtomicU64::new(0),
+};
+
+static STARTUP: [Startup; MAX_CPUS] = [EMPTY_STARTUP; MAX_CPUS];
+static KERNEL_CR3: AtomicU64 = AtomicU64::new(0);
+
+/// Where Limine sends an AP. Runs on Limine's page tables and stack, with interrupts disabled.
+unsafe extern "C" fn ap_entry(cpu: &Cpu) -> ! {
+    let Some(startup) = STARTUP.iter().find(|startup| {
+        startup.per_cpu.load(Ordering::Acquire) != 0
+            && startup.lapic_id.load(Ordering::Relaxed) == cpu.lapic_id
+    }) else {
+        hcf();
+    };
+
+    // Our page tables set NX bits, which are reserved until NX is enabled.
+    memory::enable_protection_bits();
+
+    // SAFETY: The kernel's page tables map the kernel where Limine's do, so this code keeps
+    // running after the switch. The new stack was mapped for this CPU, and nothing on Limine's
+    // stack is used afterwards.
+    unsafe {
+        asm!(
+            "mov cr3, {cr3}",
+            "mov rsp, {stack}",
+            "xor ebp, ebp",
+            "call {main}",
+            cr3 = in(reg) KERNEL_CR3.load(Ordering::Relaxed),
+            stack = in(reg) startup.stack_top.load(Ordering::Relaxed),
+            main = sym ap_main,
+            in("rdi") startup.per_cpu.load(Ordering::Relaxed),
+            options(noreturn),
+        );
+    }
+}
+
+extern "C" fn ap_main(cpu: &'static PerCpu) -> ! {
+    cpu.install();
+    gdt::init_cpu(&cpu.ist_stacks);
+    init_idt();
+    hardening::protect_cpu();
+    apic::enable();
+
+    cpu.online.store(true, Ordering::Release);
+    log::debug!("CPU {} (APIC ID {}) is online.", cpu.index, cpu.lapic_id);
+
+    run_tasks(cpu);
+}
+
+/// Runs the tasks sent to `cpu`, sleeping while there are none.
+fn run_tasks(cpu: &PerCpu) -> ! {
+    loop {
+        let task = cpu.tasks.lock().pop_front();
+        if let Some(task) = task {
+            task();
+            continue;
+        }
+
+        // Check for tasks with interrupts off so that a wake up right after the check isn't lost.
+        interrupts::disable();
+        if cpu.tasks.lock().is_empty() && apic::is_available() {
+            interrupts::enable_and_hlt();
+        } else {
+            interrupts::enable();
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// Runs `task` on the AP with `index`. Returns `false` without running it if that CPU isn't an
+/// online AP.
+pub fn run_on(index: usize, task: impl FnOnce() + Send + 'static) -> bool {
+    let Some(cpu) = cpus()
+        .get(index)
+        .filter(|cpu| !cpu.is_bsp() && cpu.is_online())
+    else {
+        return false;
+    };
+
+    cpu.tasks.lock().push_back(Box::new(task));
+    apic::send_ipi(cpu.lapic_id, apic::WAKE_VECTOR);
+    true
+}
+
+/// Runs `task` on one of the APs, taking turns. Runs it right away if there are none.
+pub fn spawn(task: impl FnOnce() + Send + 'static) {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    let aps: Vec<usize> = cpus()
+        .iter()
+        .filter(|cpu| !cpu.is_bsp() && cpu.is_online())
+        .map(|cpu| cpu.index)
+        .collect();
+
+    if aps.is_empty() {
+        task();
+        return;
+    }
+
+    let index = aps[NEXT.fetch_add(1, Ordering::Relaxed) % aps.len()];
+    run_on(index, task);
+}
+
+/// Calls `f` with each of `items`, spread over the online APs and the current CPU, and returns once
+/// every call is done. Must be called on the BSP, since an AP waiting here could be handed an item
+/// that it would never get to.
+pub fn for_each<T: Send>(items: impl IntoIterator<Item = T>, f: impl Fn(T) + Sync) {
+    let remaining = AtomicUsize::new(0);
+    let mut items = items.into_iter().peekable();
+
+    while let Some(item) = items.next() {
+        // The current CPU takes the last item instead of waiting idly.
+        if items.peek().is_none() {
+            f(item);
+            break;
+        }
+
+        remaining.fetch_add(1, Ordering::AcqRel);
+        let (f, remaining) = (&f, &remaining);
+        let task: Box<dyn FnOnce() + Send + '_> = Box::new(move || {
+            f(item);
+            remaining.fetch_sub(1, Ordering::Release);
+        });
+        // SAFETY: The loop below waits for every task to finish before anything they borrow goes
+        // away. Panics don't unwind, so nothing can return early.
+        spawn(unsafe { mem::transmute::<Box<dyn FnOnce() + Send + '_>, Task>(task) });
+    }
+
+    while remaining.load(Ordering::Acquire) != 0 {
+        core::hint::spin_loop();
+    }
+}
+
+/// Allocates what the AP needs and records it for [`ap_entry`].
+fn prepare(
+    offset_page_table: &mut OffsetPageTable,
+    frame_allocator: &mut ProperFrameAllocator,
+    index: usize,
+    lapic_id: u32,
+) -> Result<&'static PerCpu, memory::error::MemoryError> {
+    let stack = allocate_stack(
+        offset_page_table,
+        frame_allocator,
+        STACK_SIZE,
+        MemoryRegion::Dynamic("AP stack"),
+    )?;
+    let ist_stacks = IstStacks::allocate(offset_page_table, frame_allocator)?;
+
+    let cpu = PerCpu::leak(index, lapic_id, stack, ist_stacks);
+
+    let startup = &STARTUP[index];
+    startup.lapic_id.store(lapic_id, Ordering::Relaxed);
+    startup.stack_top.store(cpu.stack.end, Ordering::Relaxed);
+    startup
+        .per_cpu
+        .store(cpu as *const PerCpu as u64, Ordering::Release);
+
+    Ok(cpu)
+}
+
+/// Sets up the BSP's [`PerCpu`] and starts every AP Limine found. Must be called after the
+/// local APIC is set up and before bootloader memory is reclaimed.
+///
+/// Returns whether every AP has left Limine's memory. APs that are still parked watch it for an
+/// entry point, so it must not be reclaimed otherwise.
+pub fn init(
+    offset_page_table: &mut OffsetPageTable,
+    frame_allocator: &mut ProperFrameAllocator,
+) -> bool {
+    let response = MP_REQUEST.get_response();
+    let bsp_lapic_id = response.map_or_else(apic::id, |response| response.bsp_lapic_id());
+
+    let kernel_stack = STACK_BASE.start_address().as_u64()
+        ..STACK_BASE.start_address().as_u64() + STACK_PAGES * Size4KiB::SIZE;
+    let ist_stacks =
+        IstStacks::allocate(offset_page_table, frame_allocator).unwrap_or_else(|error| {
+            log::warn!("Keeping the boot interrupt stacks on the BSP: {error}.");
+            IstStacks::boot()
+        });
+    let bsp = PerCpu::leak(0, bsp_lapic_id, kernel_stack, ist_stacks);
+    bsp.install();
+    gdt::init_cpu(&bsp.ist_stacks);
+
+    let mut cpus = alloc::vec![bsp];
+    let mut parked = Vec::new();
+
+    let limine_cpus = response.map_or(&[][..], |response| response.cpus());
+    for &limine_cpu in limine_cpus
+        .iter()
+        .filter(|cpu| cpu.lapic_id != bsp_lapic_id)
+    {
+        if cpus.len() == MAX_CPUS {
+            log::warn!("Only using the first {MAX_CPUS} CPUs.");
+            break;
+        }
+
+        match prepare(
+            offset_page_table,
+            frame_allocator,
+            cpus.len(),
+            limine_cpu.lapic_id,
+        ) {
+            Ok(cpu) => {
+                cpus.push(cpu);
+                parked.push(limine_cpu);
+            }
+            Err(error) => {
+                log::warn!(
+                    "Not starting the CPU with APIC ID {}: {error}.",
+                    limine_cpu.lapic_id
+                );
+                break;
+            }
+        }
+    }
+
+    let (cr3, _) = Cr3::read();
+    KERNEL_CR3.store(cr3.start_address().as_u64(), Ordering::Relaxed);
+    CPUS.call_once(|| cpus);
+
+    for limine_cpu in parked {
+        limine_cpu.goto_address.write(ap_entry);
+    }
+
+    let all_online = || self::cpus().iter().all(|cpu| cpu.is_online());
+    let start = Instant::now();
+    while !all_online() && start.elapsed() < START_TIMEOUT {
+        core::hint::spin_loop();
+    }
+
+    let online = self::cpus().iter().filter(|cpu| cpu.is_online()).count();
+    log::info!("{online} of {} CPUs online.", limine_cpus.len().max(1));
+
+    online == limine_cpus.len().max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+    use core::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[test_case]
+    fn tests_run_on_the_bsp() {
+        let cpu = current().expect("SMP is set up while booting.");
+        assert!(cpu.is_bsp());
+        assert!(core::ptr::eq(cpu, cpus()[0]));
+    }
+
+    #[test_case]
+    fn tasks_run_on_the_chosen_cpu() {
+        for cpu in cpus().iter().filter(|cpu| !cpu.is_bsp()) {
+            let ran_on = Arc::new(AtomicUsize::new(usize::MAX));
+            let sent = run_on(cpu.index, {
+                let ran_on = ran_on.clone();
+                move || ran_on.store(current().unwrap().index, Ordering::Release)
+            });
+            assert!(sent, "CPU {} is offline", cpu.index);
+
+            let start = Instant::now();
+            while ran_on.load(Ordering::Acquire) == usize::MAX {
+                assert!(start.elapsed() < Duration::from_secs(1));
+                core::hint::spin_loop();
+            }
+            assert_eq!(ran_on.load(Ordering::Acquire), cpu.index);
+        }
+    }
+
+    #[test_case]
+    fn for_each_waits_for_every_item() {
+        let sum = AtomicUsize::new(0);
+        for_each(1..=100, |n| {
+            sum.fetch_add(n, Ordering::Relaxed);
+        });
+        assert_eq!(sum.load(Ordering::Relaxed), 5050);
+    }
+
+    #[test_case]
+    fn stacks_are_disjoint() {
+        let stacks: Vec<_> = cpus()
+            .iter()
+            .flat_map(|cpu| core::iter::once(&cpu.stack).chain(cpu.ist_stacks.iter()))
+            .collect();
+
+        for (i, a) in stacks.iter().enumerate() {
+            for b in &stacks[i + 1..] {
+                assert!(a.end <= b.start || b.end <= a.start);
+            }
+        }
+    }
+}
@@ -24,3 +24,49 @@ pub const fn page_from_addr<S: PageSize>(addr: u64) -> Page<S> {
     // SAFETY: Alignment asserted in previous line.
     unsafe { Page::from_start_address_unchecked(VirtAddr::new(addr)) }
 }
+
+/// Fixed size FIFO, for input received in interrupt handlers, which must not allocate.
+pub struct Queue<T, const CAPACITY: usize> {
+    buffer: [Option<T>; CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl<T: Copy, const CAPACITY: usize> Queue<T, CAPACITY> {
+    pub const fn new() -> Self {
+        Self {
+            buffer: [None; CAPACITY],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Drops the item if the queue is full, like a device overrunning its buffer.
+    pub fn push(&mut self, item: T) {
+        if self.len < CAPACITY {
+            self.buffer[(self.head + self.len) % CAPACITY] = Some(item);
+            self.len += 1;
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let item = self.buffer[self.head].take();
+        self.head = (self.head + 1) % CAPACITY;
+        self.len -= 1;
+        item
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T: Copy, const CAPACITY: usize> Default for Queue<T, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The game as ECS resources. The rules themselves live in the `wordle_engine` crate.
+
+use bevy::prelude::*;
+pub use wordle_engine::{
+    Guess, GuessError, LetterScore, MAX_GUESSES, Status, WORD_LENGTH, Word, is_in_dictionary,
+    pick_answer, score,
+};
+
+/// The round being played.
+#[derive(Resource, Deref, DerefMut, Debug, Clone)]
+pub struct Game(pub wordle_engine::Game);
+
+impl Game {
+    pub fn new(answer: Word, hard_mode: bool) -> Self {
+        Self(wordle_engine::Game::new(answer, hard_mode))
+    }
+}
+
+/// Results of the rounds finished since boot.
+#[derive(Resource, Deref, DerefMut, Debug, Default)]
+pub struct Stats(pub wordle_engine::Stats);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The rules are tested on the host in `wordle_engine`, this only checks the wrappers.
+    #[test_case]
+    fn resources_pass_through_to_the_engine() {
+        let mut world = World::new();
+        world.insert_resource(Game::new("apple".parse().unwrap(), false));
+        world.init_resource::<Stats>();
+
+        world.resource_mut::<Game>().guess("apple").unwrap();
+        let game = world.resource::<Game>().clone();
+        world.resource_mut::<Stats>().record(&game);
+
+        assert_eq!(game.status(), Status::Won);
+        assert_eq!(world.resource::<Stats>().won(), 1);
+    }
+}
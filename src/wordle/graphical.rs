@@ -0,0 +1,218 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Front end that draws the board and keyboard onto a framebuffer, with a mouse cursor to click the
+//! keys and the menu with.
+
+use alloc::{format, string::String};
+use core::mem;
+
+use bevy::{input::mouse::MouseMotion, prelude::*};
+use embedded_graphics::{
+    Drawable,
+    mono_font::{MonoTextStyle, ascii::FONT_10X20},
+    pixelcolor::Rgb888,
+    prelude::{Dimensions, DrawTarget, OriginDimensions, Point, Size},
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+use wordle_graphics::{Palette, cursor, keyboard_layout};
+
+use crate::{
+    color::{COLOR_SCHEME, ColorScheme, Theme},
+    debug::{
+        shell::{SerialLine, ShellInput},
+        text::DEBUG_WRITER,
+    },
+    trampoline::{
+        BootInfo,
+        clock::Instant,
+        cmdline::{BootOptions, GameMode},
+        framebuffer::{self, Framebuffer},
+        ps2::{keyboard, mouse},
+    },
+    util::InfallibleResultExt,
+    wordle::{
+        GameLogic, MenuItem,
+        game::{Game, Status},
+        menu,
+    },
+};
+
+/// The colors of the active theme.
+pub fn palette() -> Palette {
+    Palette {
+        background: COLOR_SCHEME.background(),
+        foreground: COLOR_SCHEME.foreground(),
+        error: COLOR_SCHEME.error_foreground(),
+        correct: COLOR_SCHEME.correct(),
+        present: COLOR_SCHEME.present(),
+        absent: COLOR_SCHEME.absent(),
+        empty_tile: COLOR_SCHEME.empty_tile(),
+    }
+}
+
+const MENU_GAP: i32 = 30;
+
+fn menu_text(item: MenuItem) -> String {
+    format!("[{}] {}", item.key(), item.label())
+}
+
+/// Where each [`menu`] item goes, in one line along the bottom of the screen.
+fn menu_layout(size: Size, mode: GameMode) -> impl Iterator<Item = (MenuItem, Rectangle)> {
+    let character = FONT_10X20.character_size;
+    let width = move |item| menu_text(item).len() as i32 * character.width as i32;
+    let total = menu(mode).map(|item| width(item) + MENU_GAP).sum::<i32>() - MENU_GAP;
+    let top = size.height as i32 - 8 - character.height as i32;
+
+    menu(mode).scan((size.width as i32 - total) / 2, move |x, item| {
+        let area = Rectangle::new(
+            Point::new(*x, top),
+            Size::new(width(item) as u32, character.height),
+        );
+        *x += width(item) + MENU_GAP;
+        Some((item, area))
+    })
+}
+
+fn draw_menu<D: DrawTarget<Color = Rgb888>>(
+    target: &mut D,
+    mode: GameMode,
+    palette: &Palette,
+) -> Result<(), D::Error> {
+    let style = MonoTextStyle::new(&FONT_10X20, palette.foreground);
+    for (item, area) in menu_layout(target.bounding_box().size, mode) {
+        Text::with_baseline(&menu_text(item), area.top_left, style, Baseline::Top).draw(target)?;
+    }
+
+    Ok(())
+}
+
+/// The framebuffer the game is shown on.
+fn display<'a>(boot_info: &'a mut BootInfo, options: &BootOptions) -> &'a mut Framebuffer<'static> {
+    let display = options.display.min(boot_info.framebuffers.len() - 1);
+    &mut boot_info.framebuffers[display]
+}
+
+/// Whether the debug log has been scrolled back over the game, see [`shell`](crate::debug::shell).
+fn log_shown() -> bool {
+    DEBUG_WRITER.lock().is_scrolled_back()
+}
+
+/// Redraws the screen whenever the game or the theme changes, or the debug log was put away.
+fn redraw(
+    mut boot_info: ResMut<BootInfo>,
+    game: Res<Game>,
+    options: Res<BootOptions>,
+    mut drawn_theme: Local<Option<Theme>>,
+    mut covered: Local<bool>,
+) {
+    if log_shown() {
+        *covered = true;
+        return;
+    }
+
+    let theme = COLOR_SCHEME.get();
+    if !game.is_changed() && *drawn_theme == Some(theme) && !mem::take(&mut *covered) {
+        return;
+    }
+    *drawn_theme = Some(theme);
+
+    let start = Instant::now();
+    let framebuffer = display(&mut boot_info, &options);
+
+    let palette = palette();
+    wordle_graphics::draw(framebuffer, &game, &palette).infallible();
+    if game.status() != Status::Playing {
+        draw_menu(framebuffer, options.mode, &palette).infallible();
+    }
+    framebuffer.flush_with(framebuffer::copy_in_parallel);
+    log::trace!("Redrew the screen in {:?}.", start.elapsed());
+}
+
+/// Where the tip of the mouse cursor is, if there is a mouse.
+#[derive(Resource, Default)]
+struct Cursor(Option<Point>);
+
+fn place_cursor(
+    mut cursor: ResMut<Cursor>,
+    mut boot_info: ResMut<BootInfo>,
+    options: Res<BootOptions>,
+) {
+    if mouse::is_present() {
+        cursor.0 = Some(display(&mut boot_info, &options).bounding_box().center());
+    }
+}
+
+fn move_cursor(
+    mut motion: MessageReader<MouseMotion>,
+    mut cursor: ResMut<Cursor>,
+    mut boot_info: ResMut<BootInfo>,
+    options: Res<BootOptions>,
+) {
+    let Some(mut position) = cursor.0 else {
+        return;
+    };
+
+    let size = display(&mut boot_info, &options).size();
+    for MouseMotion { delta } in motion.read() {
+        position.x = (position.x + delta.x as i32).clamp(0, size.width as i32 - 1);
+        position.y = (position.y + delta.y as i32).clamp(0, size.height as i32 - 1);
+    }
+
+    // Only count as a change if it moved, so that the cursor isn't redrawn needlessly.
+    if cursor.0 != Some(position) {
+        cursor.0 = Some(position);
+    }
+}
+
+/// Clicking a key types it like the keyboard would, and clicking a menu item picks it.
+fn click(
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<Cursor>,
+    game: Res<Game>,
+    mut boot_info: ResMut<BootInfo>,
+    options: Res<BootOptions>,
+    mut lines: MessageWriter<SerialLine>,
+) {
+    let (true, Some(position)) = (buttons.just_pressed(MouseButton::Left), cursor.0) else {
+        return;
+    };
+    let size = display(&mut boot_info, &options).size();
+
+    if let Some((key, _)) = keyboard_layout(size).find(|(_, area)| area.contains(position)) {
+        keyboard::type_byte(key);
+    } else if game.status() != Status::Playing
+        && let Some((item, _)) =
+            menu_layout(size, options.mode).find(|(_, area)| area.contains(position))
+    {
+        lines.write(SerialLine(item.line().into()));
+    }
+}
+
+/// Shows the cursor over the game. It is drawn on the front buffer only, so moving it doesn't
+/// need a redraw.
+fn draw_cursor(cursor: Res<Cursor>, mut boot_info: ResMut<BootInfo>, options: Res<BootOptions>) {
+    if let Some(position) = cursor.0 {
+        display(&mut boot_info, &options).set_overlay(Some(cursor::cursor(position, &palette())));
+    }
+}
+
+pub struct GraphicalPlugin;
+
+impl Plugin for GraphicalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Cursor>()
+            .add_systems(Startup, place_cursor)
+            .add_systems(
+                Update,
+                (
+                    (move_cursor, click).chain().before(ShellInput),
+                    (redraw, draw_cursor.run_if(not(log_shown)))
+                        .chain()
+                        .after(GameLogic),
+                ),
+            );
+    }
+}
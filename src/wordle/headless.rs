@@ -0,0 +1,100 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Front end for machines without a framebuffer: the board is printed over COM1 with ANSI colors
+//! and guesses are typed on the serial console.
+
+use bevy::prelude::*;
+use wordle_graphics::KEYBOARD_ROWS;
+
+use crate::{
+    serial_print, serial_println,
+    trampoline::cmdline::BootOptions,
+    wordle::{
+        GameLogic,
+        game::{Game, LetterScore, MAX_GUESSES, Stats, Status, WORD_LENGTH},
+        menu,
+    },
+};
+
+/// SGR parameters that color a tile by its score.
+fn tile_style(score: Option<LetterScore>) -> &'static str {
+    match score {
+        Some(LetterScore::Correct) => "30;42",
+        Some(LetterScore::Present) => "30;43",
+        Some(LetterScore::Absent) => "97;100",
+        None => "0",
+    }
+}
+
+fn print_tile(letter: u8, score: Option<LetterScore>) {
+    serial_print!(
+        "\x1b[{}m {} \x1b[0m",
+        tile_style(score),
+        letter.to_ascii_uppercase() as char
+    );
+}
+
+fn print_board(game: Res<Game>, stats: Res<Stats>, options: Res<BootOptions>) {
+    serial_println!();
+    for row in 0..MAX_GUESSES {
+        serial_print!("  ");
+        match game.guesses().get(row) {
+            Some(guess) => {
+                for (&letter, score) in guess.word.letters().iter().zip(guess.scores) {
+                    print_tile(letter, Some(score));
+                }
+            }
+            None => {
+                for _ in 0..WORD_LENGTH {
+                    serial_print!(" _ ");
+                }
+            }
+        }
+        serial_println!();
+    }
+
+    serial_println!();
+    let letters = game.letter_scores();
+    for (indent, row) in KEYBOARD_ROWS.iter().enumerate() {
+        serial_print!("{:width$}", "", width = 2 + indent * 2);
+        for letter in row.bytes() {
+            print_tile(letter, letters[(letter - b'a') as usize]);
+        }
+        serial_println!();
+    }
+    serial_println!();
+
+    match game.status() {
+        Status::Playing => serial_println!("Type a {WORD_LENGTH} letter guess and press Enter."),
+        Status::Won => serial_println!("You got it in {} of {MAX_GUESSES}!", game.guesses().len()),
+        Status::Lost => serial_println!("Out of guesses, the word was {}.", game.answer()),
+    }
+
+    if game.status() != Status::Playing {
+        serial_println!(
+            "Played {}, won {}%, streak {}.",
+            stats.played(),
+            stats.win_percentage(),
+            stats.current_streak()
+        );
+        for item in menu(options.mode) {
+            serial_print!("[{}] {}  ", item.key(), item.label());
+        }
+        serial_println!();
+    }
+}
+
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            print_board
+                .after(GameLogic)
+                .run_if(resource_changed::<Game>),
+        );
+    }
+}
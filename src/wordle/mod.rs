@@ -2,25 +2,194 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use bevy::prelude::*;
-use embedded_graphics::prelude::DrawTarget;
+use core::{future, task::Poll};
+
+use bevy::{app::AppExit, prelude::*, time::TimePlugin};
 
 use crate::{
-    color::{COLOR_SCHEME, ColorScheme},
-    trampoline::BootInfo,
-    util::InfallibleResultExt,
+    debug::{
+        logger::{self, Sinks},
+        serial,
+        shell::{SerialLine, ShellInput, ShellPlugin},
+    },
+    serial_println, task,
+    trampoline::{
+        BootInfo,
+        cmdline::{BootOptions, GameMode},
+        power,
+        ps2::{self, keyboard},
+    },
+    wordle::game::{Game, Stats, Status},
 };
 
-pub fn run(boot_info: BootInfo) {
-    App::new()
+pub mod game;
+pub mod graphical;
+pub mod headless;
+pub mod mouse;
+
+pub fn run(boot_info: BootInfo, options: BootOptions) {
+    let headless = boot_info.framebuffers.is_empty();
+
+    let mut app = App::new();
+    app.set_runner(main_loop)
+        .add_plugins((TimePlugin, ShellPlugin, mouse::MousePlugin))
+        .insert_resource(new_round(&options, 0))
+        .insert_resource(Round(0))
+        .init_resource::<Stats>()
         .insert_resource(boot_info)
-        .add_systems(Startup, hello_world)
-        .run();
+        .insert_resource(options)
+        .add_systems(Update, handle_guesses.in_set(GameLogic).after(ShellInput));
+
+    if headless {
+        log::info!("No framebuffer available, playing over serial.");
+        app.add_plugins(headless::HeadlessPlugin);
+    } else {
+        // The game owns the screen from now on.
+        logger::set_sinks(Sinks::SERIAL | Sinks::MEMORY);
+        app.add_plugins(graphical::GraphicalPlugin);
+    }
+
+    app.run();
+}
+
+/// Runs an update whenever there is new input, sleeping in between.
+fn main_loop(mut app: App) -> AppExit {
+    task::block_on(async move {
+        loop {
+            app.update();
+            if let Some(exit) = app.should_exit() {
+                return exit;
+            }
+
+            input().await;
+        }
+    })
+}
+
+/// Waits until something was typed over serial or on the keyboard, or the mouse did something.
+fn input() -> impl Future<Output = ()> {
+    future::poll_fn(|cx| {
+        // Poll all of them, so that each one wakes us.
+        let polls = [
+            serial::poll_input(cx),
+            keyboard::poll_input(cx),
+            ps2::mouse::poll_input(cx),
+        ];
+        if polls.iter().any(Poll::is_ready) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+}
+
+/// Systems that update the [`Game`]. Front ends draw after this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameLogic;
+
+/// How many rounds have been played since boot.
+#[derive(Resource)]
+struct Round(u64);
+
+fn new_round(options: &BootOptions, round: u64) -> Game {
+    Game::new(
+        game::pick_answer(options.answer_seed(round)),
+        options.hard_mode,
+    )
 }
 
-fn hello_world(mut boot_info: ResMut<BootInfo>) {
-    boot_info.framebuffers[0]
-        .clear(COLOR_SCHEME.background())
-        .infallible();
-    boot_info.framebuffers[0].flush();
+/// Choices offered once a round is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    NewWord,
+    ShutDown,
+    Reboot,
+}
+
+impl MenuItem {
+    pub const ALL: [Self; 3] = [Self::NewWord, Self::ShutDown, Self::Reboot];
+
+    /// The key to type, followed by Enter, to pick this item.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::NewWord => "Enter",
+            Self::ShutDown => "Q",
+            Self::Reboot => "R",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::NewWord => "new word",
+            Self::ShutDown => "shut down",
+            Self::Reboot => "reboot",
+        }
+    }
+
+    /// What to type to pick this item.
+    fn line(self) -> &'static str {
+        match self {
+            Self::NewWord => "",
+            Self::ShutDown => "q",
+            Self::Reboot => "r",
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        match line {
+            "" => Some(Self::NewWord),
+            "q" | "Q" => Some(Self::ShutDown),
+            "r" | "R" => Some(Self::Reboot),
+            _ => None,
+        }
+    }
+}
+
+/// The items offered in `mode`. There is only one daily word, so it can't be replaced.
+pub fn menu(mode: GameMode) -> impl Iterator<Item = MenuItem> {
+    MenuItem::ALL
+        .into_iter()
+        .filter(move |&item| mode == GameMode::Unlimited || item != MenuItem::NewWord)
+}
+
+/// Applies lines typed over serial as guesses. Both front ends show the result from [`Game`].
+fn handle_guesses(
+    mut lines: MessageReader<SerialLine>,
+    mut game: ResMut<Game>,
+    mut stats: ResMut<Stats>,
+    mut round: ResMut<Round>,
+    options: Res<BootOptions>,
+) {
+    for SerialLine(line) in lines.read() {
+        let line = line.trim();
+
+        if game.status() != Status::Playing {
+            match (MenuItem::parse(line), options.mode) {
+                (Some(MenuItem::ShutDown), _) => power::shutdown(),
+                (Some(MenuItem::Reboot), _) => power::reboot(),
+                (_, GameMode::Daily) => {
+                    serial_println!("That was today's word, come back tomorrow!")
+                }
+                // Anything else starts a new round too, like Enter does.
+                (_, GameMode::Unlimited) => {
+                    round.0 += 1;
+                    *game = new_round(&options, round.0);
+                }
+            }
+            continue;
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        // Only count as a change if the guess was accepted, so rejected ones don't redraw.
+        match game.bypass_change_detection().guess(line) {
+            Ok(_) => {
+                game.set_changed();
+                stats.record(&game);
+            }
+            Err(error) => serial_println!("Can't guess `{line}`: {error}."),
+        }
+    }
 }
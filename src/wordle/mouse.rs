@@ -0,0 +1,76 @@
+// SPDX-FileCopyrightText: 2026 SunnyMonster
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Passes the PS/2 mouse's packets on to Bevy as mouse messages, which keep
+//! [`ButtonInput<MouseButton>`] up to date. There are no windows, so the messages name
+//! [`Entity::PLACEHOLDER`] as theirs.
+
+use bevy::{
+    input::{
+        ButtonState, InputPlugin, InputSystems,
+        mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
+    },
+    prelude::*,
+};
+
+use crate::trampoline::ps2::mouse::{self, Packet};
+
+fn buttons(packet: &Packet) -> [(MouseButton, bool); 3] {
+    [
+        (MouseButton::Left, packet.left),
+        (MouseButton::Right, packet.right),
+        (MouseButton::Middle, packet.middle),
+    ]
+}
+
+fn read_packets(
+    mut motion: MessageWriter<MouseMotion>,
+    mut wheel: MessageWriter<MouseWheel>,
+    mut button_input: MessageWriter<MouseButtonInput>,
+    mut previous: Local<Packet>,
+) {
+    while let Some(packet) = mouse::read_packet() {
+        if packet.dx != 0 || packet.dy != 0 {
+            motion.write(MouseMotion {
+                delta: Vec2::new(packet.dx as f32, -packet.dy as f32),
+            });
+        }
+
+        if packet.wheel != 0 {
+            wheel.write(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.0,
+                y: -packet.wheel as f32,
+                window: Entity::PLACEHOLDER,
+            });
+        }
+
+        for ((button, pressed), (_, was_pressed)) in
+            buttons(&packet).into_iter().zip(buttons(&previous))
+        {
+            if pressed != was_pressed {
+                button_input.write(MouseButtonInput {
+                    button,
+                    state: if pressed {
+                        ButtonState::Pressed
+                    } else {
+                        ButtonState::Released
+                    },
+                    window: Entity::PLACEHOLDER,
+                });
+            }
+        }
+
+        *previous = packet;
+    }
+}
+
+pub struct MousePlugin;
+
+impl Plugin for MousePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(InputPlugin)
+            .add_systems(PreUpdate, read_packets.before(InputSystems));
+    }
+}